@@ -216,9 +216,11 @@ class Table extends React.Component {
     }
 
     handleBet(_) {
+        // the bet size is the new total for the street, which the server
+        // treats as a bet or a raise depending on whether there is already a bet
         let data = {
             "msg_type": "player_action",
-            "action": "bet",
+            "action": "raise_to",
             "amount": Math.floor(this.state.betSize).toString()
         };
 
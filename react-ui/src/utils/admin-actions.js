@@ -3,11 +3,54 @@ export const ADMIN_PREFIX = "!";
 const ADMIN_CMDS = [
     new RegExp(`(${ADMIN_PREFIX})(small_blind) (\\d)`),
     new RegExp(`(${ADMIN_PREFIX})(big_blind) (\\d)`),
+    new RegExp(`(${ADMIN_PREFIX})(ante) (\\d)`),
+    new RegExp(`(${ADMIN_PREFIX})(big_blind_ante) (\\d)`),
+    new RegExp(`(${ADMIN_PREFIX})(ante_order) (before_blinds|after_blinds)`),
+    new RegExp(`(${ADMIN_PREFIX})(set_blind_schedule) (\\d+ [\\d/,]+)`),
     new RegExp(`(${ADMIN_PREFIX})(starting_stack) (\\d)`),
     new RegExp(`(${ADMIN_PREFIX})(set_password) ([^\\s]*)`),
     new RegExp(`(${ADMIN_PREFIX})(show_password)`),
+    new RegExp(`(${ADMIN_PREFIX})(min_raise_rule) (raise_increment|full_bet)`),
+    new RegExp(`(${ADMIN_PREFIX})(betting_structure) (no_limit|pot_limit|fixed_limit)`),
+    new RegExp(`(${ADMIN_PREFIX})(game_variant) (holdem|omaha|seven_stud|short_deck)`),
+    new RegExp(`(${ADMIN_PREFIX})(bad_beat_threshold) ([A-Za-z]*)`),
+    new RegExp(`(${ADMIN_PREFIX})(action_timeout) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(clock_scaling) (off|pot_size|players_in_hand)`),
+    new RegExp(`(${ADMIN_PREFIX})(time_bank) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(time_bank_replenish) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(finish_delay) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(max_finish_delay) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(bots_only_max_pause) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(finish_delay_pot_scaling) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(allow_chop) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(run_it_twice) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(show_runout_equities) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(all_in_reveal_order) (last_aggressor|position)`),
+    new RegExp(`(${ADMIN_PREFIX})(all_in_reveal_delay) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(anonymous) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(allow_rebuys) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(max_stack) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(rake_percent) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(rake_cap) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(min_players_to_start) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(min_stack_to_play) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(rebuys_at_orbit_start) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(remove_busted) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(max_straddles) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(rematch_window) (\\d+)`),
+    new RegExp(`(${ADMIN_PREFIX})(check_it_down) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(expose_bot_decisions) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(reshuffle_policy) (every_hand|when_needed)`),
+    new RegExp(`(${ADMIN_PREFIX})(commit_shuffle) (true|false)`),
+    new RegExp(`(${ADMIN_PREFIX})(exposure_policy) (continue|void_hand)`),
+    new RegExp(`(${ADMIN_PREFIX})(freeze_clocks)`),
+    new RegExp(`(${ADMIN_PREFIX})(unfreeze_clocks)`),
+    new RegExp(`(${ADMIN_PREFIX})(close_in) (\\d+)`),
     new RegExp(`(${ADMIN_PREFIX})(add_bot)`),
     new RegExp(`(${ADMIN_PREFIX})(remove_bot)`),
+    new RegExp(`(${ADMIN_PREFIX})(kick) ([0-9a-fA-F-]+)`),
+    new RegExp(`(${ADMIN_PREFIX})(mute) ([0-9a-fA-F-]+)`),
+    new RegExp(`(${ADMIN_PREFIX})(unmute) ([0-9a-fA-F-]+)`),
     new RegExp(`(${ADMIN_PREFIX})(restart)`)
 ];
 
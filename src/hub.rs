@@ -6,16 +6,18 @@
 
 use std::{
     collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::PathBuf,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-use crate::logic::{Table, PlayerAction, PlayerConfig};
+use crate::logic::{AnteMode, Table, PlayerAction, PlayerConfig};
 use crate::messages::{
-    Connect, Create, CreateFields, CreateTableError, GameOver, Join, ListTables, MetaAction, MetaActionMessage,
-    PlayerActionMessage, PlayerName, Returned, ReturnedReason, WsMessage,
+    Connect, ConnectError, Create, CreateFields, CreateTableError, GameEvent, GameOver, Join, JoinTableError, ListTables, MetaAction,
+    MetaActionMessage, PlayerActionMessage, PlayerName, Returned, ReturnedReason, SubscribeEvents, WsMessage,
 };
-use actix::prelude::{Actor, Context, Handler, MessageResult};
+use actix::prelude::{Actor, Context, Handler, MessageResult, Recipient};
 use actix::AsyncContext;
 use json::object;
 use rand::Rng;
@@ -24,6 +26,8 @@ use uuid::Uuid;
 // for generator random game names
 const CHAR_SET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
 const GAME_NAME_LEN: usize = 4;
+// the default for how long a table name chosen by its creator can be
+pub const MAX_TABLE_NAME_LEN: usize = 32;
 
 /// `Tablelobby` manages chat tables and responsible for coordinating chat session.
 #[derive(Debug)]
@@ -41,6 +45,20 @@ pub struct TableHub {
 
     private_tables: HashSet<String>, // which games do not show up in the loby
 
+    // external clients listening to the game events, and optionally which tables they care about
+    event_subscribers: Vec<(Recipient<WsMessage>, Option<HashSet<String>>)>,
+
+    max_table_name_len: usize, // how long a table name chosen by its creator can be
+
+    audit_deck: bool, // for debugging, tables record the deck order of every hand
+    deck_seed: Option<u64>, // for debugging, every table shuffles from this seed, so the deals can be reproduced
+
+    show_deck_count: bool, // for training, tables show how many cards are left in the deck
+
+    max_sessions: Option<usize>, // to protect a small server, cap how many sessions can be connected at once
+
+    state_dir: Option<PathBuf>, // where every table is saved after each hand, and restored from on startup
+
     //visitor_count: Arc<AtomicUsize>,
 }
 
@@ -52,8 +70,143 @@ impl TableHub {
             tables_to_actions: HashMap::new(),
             tables_to_meta_actions: HashMap::new(),
             private_tables: HashSet::new(),
+            event_subscribers: Vec::new(),
+            max_table_name_len: MAX_TABLE_NAME_LEN,
+            audit_deck: false,
+            deck_seed: None,
+            show_deck_count: false,
+            max_sessions: None,
+            state_dir: None,
         }
-    }    
+    }
+
+    pub fn with_max_table_name_len(mut self, max_table_name_len: usize) -> TableHub {
+        self.max_table_name_len = max_table_name_len;
+        self
+    }
+
+    pub fn with_audit_deck(mut self, audit_deck: bool) -> TableHub {
+        self.audit_deck = audit_deck;
+        self
+    }
+
+    pub fn with_deck_seed(mut self, deck_seed: Option<u64>) -> TableHub {
+        self.deck_seed = deck_seed;
+        self
+    }
+
+    pub fn with_show_deck_count(mut self, show_deck_count: bool) -> TableHub {
+        self.show_deck_count = show_deck_count;
+        self
+    }
+
+    pub fn with_max_sessions(mut self, max_sessions: Option<usize>) -> TableHub {
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    pub fn with_state_dir(mut self, state_dir: Option<PathBuf>) -> TableHub {
+        self.state_dir = state_dir;
+        self
+    }
+
+    /// the file a new table is saved to, if tables are being saved.
+    /// the table's name is picked by its creator, so the file is named by a generated id
+    /// and the name is kept inside the snapshot instead
+    fn save_path(&self) -> Option<PathBuf> {
+        self.state_dir.as_ref().map(|dir| dir.join(format!("{}.json", Uuid::new_v4())))
+    }
+
+    /// run the table on its own thread, keeping the queues for relaying the players' actions to it
+    fn spawn_table(&mut self, mut game: Table) {
+        let table_name = game.name.clone();
+        let actions = Arc::new(Mutex::new(HashMap::new()));
+        let meta_actions = Arc::new(Mutex::new(VecDeque::new()));
+        let cloned_actions = actions.clone();
+        let cloned_meta_actions = meta_actions.clone();
+        let span = tracing::info_span!("table", name = %table_name);
+        std::thread::spawn(move || {
+            // everything the table logs on its thread can be correlated by the table's name
+            let _entered = span.entered();
+            // Note: I tried having the actions and meta actions as part of the game struct,
+            // but this led to lifetime concerns.
+            // Then I changed to using scoped threads, and this sort of "solved" it,
+            // but it did not play nicely with actix async (i.e. the tests worked but the app did not)
+            // TLDR keep the actions as something passed in to play()
+            game.play(&cloned_actions, &cloned_meta_actions, None);
+        });
+        self.tables_to_actions.insert(table_name.clone(), actions);
+        self.tables_to_meta_actions.insert(table_name, meta_actions);
+    }
+
+    /// after a restart, bring back every table that was saved in the state directory.
+    /// The players are put back at their tables, so they can reconnect with their ids
+    fn restore_tables(&mut self, ctx: &mut Context<Self>) {
+        let Some(state_dir) = self.state_dir.clone() else {
+            return;
+        };
+        let Ok(entries) = fs::read_dir(&state_dir) else {
+            log::warn!("unable to read the state directory {:?}", state_dir);
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let mut game = match Table::load(&path, ctx.address()) {
+                Ok(game) => game,
+                Err(e) => {
+                    log::warn!("unable to restore the table from {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            log::info!("restored table {} from {:?}", game.name, path);
+            game.set_audit_deck(self.audit_deck);
+            game.set_show_deck_count(self.show_deck_count);
+            game.set_save_path(Some(path));
+            if game.has_password() {
+                self.private_tables.insert(game.name.clone());
+            }
+            for id in game.human_ids() {
+                self.players_to_table.insert(id, game.name.clone());
+            }
+            self.spawn_table(game);
+        }
+    }
+
+    /// how many sessions we know about, whether in the lobby or at a table
+    fn connected_sessions(&self) -> usize {
+        self.main_lobby_connections.len() + self.players_to_table.len()
+    }
+
+    /// the player couldn't join a table, so tell them why and put them back in the lobby
+    fn reject_join(&mut self, player_config: PlayerConfig, err: JoinTableError) {
+        if let Some(addr) = &player_config.player_addr {
+            let message = object! {
+                msg_type: "error".to_owned(),
+                error: "unable_to_join".to_owned(),
+                reason: err.to_string(),
+            };
+            addr.do_send(WsMessage(message.dump()));
+        }
+        self.main_lobby_connections
+            .insert(player_config.id, player_config);
+    }
+}
+
+/// a table name chosen by a client ends up in every listing and broadcast,
+/// so strip out any control characters and surrounding whitespace,
+/// and make sure what is left is a reasonable length
+fn sanitize_table_name(name: &str, max_len: usize) -> Result<String, CreateTableError> {
+    let sanitized: String = name.chars().filter(|c| !c.is_control()).collect();
+    let sanitized = sanitized.trim();
+    if sanitized.is_empty() {
+        return Err(CreateTableError::EmptyTableName);
+    }
+    if sanitized.chars().count() > max_len {
+        return Err(CreateTableError::TableNameTooLong(max_len));
+    }
+    Ok(sanitized.to_string())
 }
 
 /// Make actor from `TableHub`
@@ -66,6 +219,7 @@ impl Actor for TableHub {
     /// we start the heart beat interval process, checking all
     /// player configs in the lobby for inaction
     fn started(&mut self, ctx: &mut Self::Context) {
+        self.restore_tables(ctx);
         ctx.run_interval(Duration::from_secs(10), |this_actor, _ctx| {
             // check client heartbeats
 	    this_actor.main_lobby_connections.retain(|_uuid, config| {
@@ -79,15 +233,15 @@ impl Actor for TableHub {
 ///
 /// Register new session with a given uuid.It could be brand new or a reconnection of an existing uuid
 impl Handler<Connect> for TableHub {
-    type Result = MessageResult<Connect>; // use MessageResult so that we can return a Uuid
+    type Result = Result<Uuid, ConnectError>;
 
     fn handle(&mut self, msg: Connect, _: &mut Context<Self>) -> Self::Result {
         let Connect { id, addr } = msg; // the message contains the uuid
 
-        println!("Someone is connecting with uuid = {id}!");
-	println!("self.main_lobby_connections = {:?}", self.main_lobby_connections);
-	println!("self.players_to_table = {:?}", self.players_to_table);
-	println!("self.tables_to_meta_actions = {:?}", self.tables_to_meta_actions);		
+        log::info!("Someone is connecting with uuid = {id}!");
+	log::debug!("self.main_lobby_connections = {:?}", self.main_lobby_connections);
+	log::debug!("self.players_to_table = {:?}", self.players_to_table);
+	log::debug!("self.tables_to_meta_actions = {:?}", self.tables_to_meta_actions);		
 
         let message = object! {
             msg_type: "connected".to_owned(),
@@ -98,7 +252,7 @@ impl Handler<Connect> for TableHub {
 	if let Some(config) = self.main_lobby_connections.get_mut(&id) {
 	    // the player happens to be in the lobby at this moment
 	    // simply update the address in the player config
-	    println!("connecting session uuid already in the lobby");
+	    log::debug!("connecting session uuid already in the lobby");
 	    config.player_addr = Some(addr);
 	    config.send_player_name();
 	}
@@ -106,7 +260,7 @@ impl Handler<Connect> for TableHub {
 	    // the player is currently at a table, so we need to tell the table
 	    // that the player has a new address
             if let Some(meta_actions) = self.tables_to_meta_actions.get_mut(table_name) {
-                println!("updating player's address in an existing game");
+                log::debug!("updating player's address in an existing game");
                 meta_actions
                     .lock()
                     .unwrap()
@@ -126,6 +280,12 @@ impl Handler<Connect> for TableHub {
                 );
 	    }
 	}
+	else if self.max_sessions.is_some_and(|max_sessions| self.connected_sessions() >= max_sessions) {
+	    // a brand new session, but there is no room for it.
+	    // Note: reconnections above are always let back in
+	    log::warn!("rejecting connection with uuid = {id}, the server is full");
+	    return Err(ConnectError::ServerFull(self.max_sessions.unwrap()));
+	}
 	else {
 	    // we don't have a record of the uuid
 	    // in either the lobby or in any existing table.
@@ -137,7 +297,7 @@ impl Handler<Connect> for TableHub {
 	}
 	cloned_addr.do_send(WsMessage(message.dump())); // send the connection message	    	
         // send id back
-        MessageResult(id)
+        Ok(id)
     }
 }
 
@@ -170,19 +330,19 @@ impl Handler<PlayerName> for TableHub {
     fn handle(&mut self, msg: PlayerName, _: &mut Context<Self>) {
         // if the player is the main lobby, find them and set their name
         if let Some(player_config) = self.main_lobby_connections.get_mut(&msg.id) {
-            println!("setting player name in the main lobby");
+            log::debug!("setting player name in the main lobby");
             player_config.name = Some(msg.name);
 	    player_config.heart_beat = Instant::now(); // this counts as activity
 	    player_config.send_player_name();
         } else if let Some(table_name) = self.players_to_table.get(&msg.id) {
             // otherwise, find which game they are in, and tell the game there has been a name change
             if let Some(meta_actions) = self.tables_to_meta_actions.get_mut(table_name) {
-                println!("passing player name to the game");
+                log::debug!("passing player name to the game");
                 meta_actions
                     .lock()
                     .unwrap()
                     .push_back(MetaAction::SetPlayerName(msg.id, msg.name));
-                println!("meta actions = {:?}", meta_actions);
+                log::debug!("meta actions = {:?}", meta_actions);
             } else {
                 // this should never happen. the player is allegedly at a table, but we
                 // have no record of it in tables_to_meta_actions
@@ -196,7 +356,7 @@ impl Handler<PlayerName> for TableHub {
 	    // they were removed from the lobby.
 	    // TODO: I think this should stop the session actor that sent this message, otherwise
 	    // we can have a session actor living indefinitely with no corresponding playerconfig?
-            println!("we cannot set the name since no config exists anywhere!");
+            log::warn!("we cannot set the name since no config exists anywhere!");
         }
     }
 }
@@ -211,13 +371,15 @@ impl Handler<Join> for TableHub {
             id,
             table_name,
             password,
+            spectate,
+            seat,
         } = msg;
 	
         let player_config_option = self.main_lobby_connections.remove(&id);
         if player_config_option.is_none() {
             // the player is not in the main lobby,
             // so we must be waiting for the game to remove the player still
-            println!("player config not in the main lobby, so they must already be at a game");
+            log::debug!("player config not in the main lobby, so they must already be at a game");
             return;
         }
         let mut player_config = player_config_option.unwrap();
@@ -226,47 +388,25 @@ impl Handler<Join> for TableHub {
 	
         if player_config.name.is_none() {
             // they are not allowed to join a game without a Name set
-            let message = json::object! {
-                    msg_type: "error".to_owned(),
-            error: "unable_to_join".to_owned(),
-                    reason: "You cannot join a game until you set your name!"
-                };
-            player_config
-                .player_addr
-                .as_ref()
-                .unwrap()
-                .do_send(WsMessage(message.dump()));
-            // put them back in the lobby
-            self.main_lobby_connections
-                .insert(player_config.id, player_config);
+            self.reject_join(player_config, JoinTableError::NameNotSet);
             return;
         }
 
         if let Some(meta_actions) = self.tables_to_meta_actions.get_mut(&table_name) {
             // since the meta actions already exist, this means the game already exists
             // so we can simply join it
-            println!("joining existing game! {:?}", meta_actions);
-            meta_actions
-                .lock()
-                .unwrap()
-                .push_back(MetaAction::Join(player_config, password));
+            log::debug!("joining existing game! {:?}", meta_actions);
+            let meta_action = if spectate {
+                MetaAction::Spectate(player_config, password)
+            } else {
+                MetaAction::Join(player_config, password, seat)
+            };
+            meta_actions.lock().unwrap().push_back(meta_action);
             // update the mapping to find the player at a table
             self.players_to_table.insert(id, table_name.clone());
 	    
         } else {
-            let message = json::object! {
-                    msg_type: "error".to_owned(),
-            error: "unable_to_join".to_owned(),
-                    reason: format!("no table named {} exisits", table_name),
-                };
-            player_config
-                .player_addr
-                .as_ref()
-                .unwrap()
-                .do_send(WsMessage(message.dump()));
-            // put them back in the lobby
-            self.main_lobby_connections
-                .insert(player_config.id, player_config);
+            self.reject_join(player_config, JoinTableError::TableDoesNotExist(table_name));
         }
     }
 }
@@ -278,10 +418,10 @@ impl Handler<Returned> for TableHub {
 
     fn handle(&mut self, msg: Returned, _: &mut Context<Self>) {
         let Returned { config, reason } = msg;
-        println!("Handling player {:?} removed", config);
+        log::debug!("Handling player {:?} removed", config);
         if let Some(table_name) = self.players_to_table.remove(&config.id) {
             // we stil think this player is at table in our mapping, so remove it
-            println!("removing player {:?} removed from {:?}", config, table_name);
+            log::debug!("removing player {:?} removed from {:?}", config, table_name);
         }
 
         // tell the player what happened (successful leave/why couldn't they join)
@@ -292,6 +432,14 @@ impl Handler<Returned> for TableHub {
                 ReturnedReason::Left => {
                     message["msg_type"] = "left_game".into();
                 }
+                ReturnedReason::Busted => {
+                    message["msg_type"] = "left_game".into();
+                    message["reason"] = "You ran out of chips.".into();
+                }
+                ReturnedReason::Kicked => {
+                    message["msg_type"] = "kicked".into();
+                    message["reason"] = "The admin removed you from the table.".into();
+                }
                 ReturnedReason::HeartBeatFailed => {
 		    message["msg_type"] = "error".into();
                     message["error"] = "disconnected_from_server".into();		    
@@ -324,11 +472,11 @@ impl Handler<Create> for TableHub {
         if player_config_option.is_none() {
             // the player is not in the main lobby,
             // so we must be waiting for the game to remove the player still
-            println!("player config not in the main lobby, so they must already be at a game");
+            log::debug!("player config not in the main lobby, so they must already be at a game");
             if let Some(table_name) = self.players_to_table.get(&id) {
                 return Err(CreateTableError::AlreadyAtTable(table_name.to_string()));
             } else {
-                println!("player not at lobby nor at a table");
+                log::warn!("player not at lobby nor at a table");
 		// TODO: I think this should stop the session actor that sent this message, otherwise
 		// we can have a session actor living indefinitely with no corresponding playerconfig?		
                 return Err(CreateTableError::PlayerDoesNotExist);
@@ -354,21 +502,35 @@ impl Handler<Create> for TableHub {
 		    buy_in,
 		    num_bots,
 		    password,
+		    table_name,
 		} = create_fields;
-		println!("password in create game = {:?}", password);
+		log::debug!("password in create game = {:?}", password);
 		
 		if num_bots >= max_players {
 		    self.main_lobby_connections.insert(player_config.id, player_config);
 		    return Err(CreateTableError::TooManyBots);
 		}
 		
-		if big_blind > buy_in || small_blind > buy_in {
+		if let Err(err) = Table::validate_config(small_blind, big_blind, AnteMode::None, buy_in) {
 		    self.main_lobby_connections.insert(player_config.id, player_config);
-		    return Err(CreateTableError::TooLargeBlinds);		
+		    return Err(CreateTableError::InvalidConfig(err));
 		}
 		
+		let chosen_name = match table_name.map(|name| sanitize_table_name(&name, self.max_table_name_len)) {
+		    Some(Ok(name)) if self.tables_to_actions.contains_key(&name) => {
+			self.main_lobby_connections.insert(player_config.id, player_config);
+			return Err(CreateTableError::TableNameTaken(name));
+		    }
+		    Some(Err(e)) => {
+			self.main_lobby_connections.insert(player_config.id, player_config);
+			return Err(e);
+		    }
+		    Some(Ok(name)) => Some(name),
+		    None => None,
+		};
+		
 		let mut rng = rand::thread_rng();
-		let table_name = loop {
+		let table_name = chosen_name.unwrap_or_else(|| loop {
                     // create a new 4-char unique name for the table
                     let genned_name: String = (0..GAME_NAME_LEN)
 			.map(|_| {
@@ -382,17 +544,13 @@ impl Handler<Create> for TableHub {
                     }
                     // we genned a name that is new
                     break genned_name;
-		};
-		
-		let actions = Arc::new(Mutex::new(HashMap::new()));
-		let meta_actions = Arc::new(Mutex::new(VecDeque::new()));
-		let cloned_actions = actions.clone();
-		let cloned_meta_actions = meta_actions.clone();
+		});
 		
 		let mut game = Table::new(
                     ctx.address(),
                     table_name.clone(),
                     None, // no deck needed to pass in
+                    self.deck_seed,
                     max_players,
                     small_blind,
                     big_blind,
@@ -400,6 +558,9 @@ impl Handler<Create> for TableHub {
                     password.clone(),
 		    id, // the creator is the admin
 		);
+		game.set_audit_deck(self.audit_deck);
+		game.set_show_deck_count(self.show_deck_count);
+		game.set_save_path(self.save_path());
 		
 		for i in 0..num_bots {
                     let name = format!("Bot {}", i);
@@ -415,30 +576,18 @@ impl Handler<Create> for TableHub {
 		// update the mapping to find the player at a table
 		self.players_to_table.insert(id, table_name.clone());
 
-		meta_actions
+		self.spawn_table(game);
+		self.tables_to_meta_actions[&table_name]
                     .lock()
                     .unwrap()
-                    .push_back(MetaAction::Join(player_config, password));
-		
-		std::thread::spawn(move || {
-                    // Note: I tried having the actions and meta actions as part of the game struct,
-                    // but this led to lifetime concerns.
-                    // Then I changed to using scoped threads, and this sort of "solved" it,
-                    // but it did not play nicely with actix async (i.e. the tests worked but the app did not)
-                    // TLDR keep the actions as something passed in to play()
-                    game.play(&cloned_actions, &cloned_meta_actions, None);
-		});
-		
-		self.tables_to_actions.insert(table_name.clone(), actions);
-		self.tables_to_meta_actions
-                    .insert(table_name.clone(), meta_actions);
+                    .push_back(MetaAction::Join(player_config, password, None));
 		Ok(table_name) // return the table name
             }
 	    Err(e) => {
-		println!("create message unable to deserialize");
-		println!("{:?}", e);
+		log::warn!("create message unable to deserialize");
+		log::warn!("{:?}", e);
 		self.main_lobby_connections.insert(player_config.id, player_config);	    
-		return Err(CreateTableError::UnableToParseJson(e.to_string()));
+		Err(CreateTableError::UnableToParseJson(e.to_string()))
             }
 	}
     }
@@ -454,16 +603,16 @@ impl Handler<PlayerActionMessage> for TableHub {
         if let Some(table_name) = self.players_to_table.get(&msg.id) {
             // the player was at a table, so tell the Table this player's message
             if let Some(actions_map) = self.tables_to_actions.get_mut(table_name) {
-                println!("handling player action in the hub!");
+                log::debug!("handling player action in the hub!");
                 actions_map
                     .lock()
                     .unwrap()
                     .insert(msg.id, msg.player_action);
-                println!("actions map = {:?}", actions_map);
+                log::debug!("actions map = {:?}", actions_map);
             } else {
                 // TODO: this should never happen. the player is allegedly at a table, but we
                 // have no record of it in tables_to_game
-                println!("blah blah mp actioms queue!");
+                log::warn!("player {} is at table {}, but the table has no actions queue", msg.id, table_name);
             }
         }
     }
@@ -475,19 +624,20 @@ impl Handler<GameOver> for TableHub {
     type Result = ();
 
     fn handle(&mut self, msg: GameOver, _: &mut Context<Self>) {
-        let GameOver { table_name } = msg;
-        println!(
+        let GameOver { table_name, reconciliation_report } = msg;
+        log::info!(
             "Handling game over in the hub for table name: {:?}",
             table_name
         );
+        log::info!("reconciliation report: {}", reconciliation_report.dump());
         if self.tables_to_actions.remove(&table_name).is_some() {
-            println!("removed properly from tables_to_actions");
+            log::debug!("removed properly from tables_to_actions");
         }
         if self.tables_to_meta_actions.remove(&table_name).is_some() {
-            println!("removed properly from tables_to_meta_actions");
+            log::debug!("removed properly from tables_to_meta_actions");
         }
         if self.private_tables.remove(&table_name) {
-            println!("removed properly from private_tables");
+            log::debug!("removed properly from private_tables");
         }
     }
 }
@@ -500,7 +650,7 @@ impl Handler<MetaActionMessage> for TableHub {
 
     fn handle(&mut self, msg: MetaActionMessage, _: &mut Context<Self>) {
         let MetaActionMessage { id, meta_action } = msg;
-        println!("handling MetaActionMessage in the hub! {:?}", meta_action);
+        log::debug!("handling MetaActionMessage in the hub! {:?}", meta_action);
         if let Some(table_name) = self.players_to_table.get(&id) {
             // tell the table that a player is gone
             if let Some(meta_actions) = self.tables_to_meta_actions.get_mut(table_name) {
@@ -511,3 +661,185 @@ impl Handler<MetaActionMessage> for TableHub {
         }
     }
 }
+
+/// an external client wants to listen to game events
+impl Handler<SubscribeEvents> for TableHub {
+    type Result = ();
+
+    fn handle(&mut self, msg: SubscribeEvents, _: &mut Context<Self>) {
+        let SubscribeEvents { addr, tables } = msg;
+        log::info!("new event subscriber for tables: {:?}", tables);
+        self.event_subscribers.push((addr, tables));
+    }
+}
+
+/// a table published an event, so fan it out to every subscriber that wants it
+impl Handler<GameEvent> for TableHub {
+    type Result = ();
+
+    fn handle(&mut self, msg: GameEvent, _: &mut Context<Self>) {
+        let GameEvent { table_name, event } = msg;
+        // forget about any subscribers that have gone away
+        self.event_subscribers.retain(|(addr, _)| addr.connected());
+        if self.event_subscribers.is_empty() {
+            return;
+        }
+        let message = object! {
+            msg_type: "game_event".to_owned(),
+            table_name: table_name.clone(),
+            event: json::parse(&event).unwrap_or(json::Null),
+        };
+        for (addr, tables) in self.event_subscribers.iter() {
+            if tables.as_ref().is_none_or(|tables| tables.contains(&table_name)) {
+                addr.do_send(WsMessage(message.dump()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::test_helpers::start_collector;
+
+    /// a table's name can't steer its snapshot out of the state directory
+    #[test]
+    fn save_path_stays_in_state_dir() {
+        let state_dir = std::env::temp_dir().join("pokerjkji-state");
+        let hub = TableHub::new().with_state_dir(Some(state_dir.clone()));
+        let first = hub.save_path().unwrap();
+        assert_eq!(first.parent(), Some(state_dir.as_path()));
+        assert_eq!(first.extension().and_then(|ext| ext.to_str()), Some("json"));
+        // every table gets its own file, even if two share a name over time
+        assert_ne!(first, hub.save_path().unwrap());
+        assert!(TableHub::new().save_path().is_none());
+    }
+
+    /// a subscriber to a table receives the events of a hand being played there,
+    /// while a subscriber to a different table hears nothing
+    #[actix::test]
+    async fn subscriber_receives_table_events() {
+        let hub = TableHub::new().start();
+        let (addr, messages) = start_collector();
+        hub.send(SubscribeEvents {
+            addr,
+            tables: Some(HashSet::from(["ABCD".to_string()])),
+        })
+        .await
+        .unwrap();
+        let (other_addr, other_messages) = start_collector();
+        hub.send(SubscribeEvents {
+            addr: other_addr,
+            tables: Some(HashSet::from(["WXYZ".to_string()])),
+        })
+        .await
+        .unwrap();
+
+        let mut table = Table::new(hub.clone(), "ABCD".to_string(), None, None, 9, 4, 8, 1000, None, Uuid::new_v4());
+        table.add_bot("Bot 0".to_string()).unwrap();
+        table.add_bot("Bot 1".to_string()).unwrap();
+        let actions = Arc::new(Mutex::new(HashMap::new()));
+        let meta_actions = Arc::new(Mutex::new(VecDeque::new()));
+        let handler = std::thread::spawn(move || {
+            table.play(&actions, &meta_actions, Some(1));
+        });
+        while !handler.is_finished() {
+            actix::clock::sleep(Duration::from_millis(100)).await;
+        }
+        // give the hub a moment to relay the last events
+        actix::clock::sleep(Duration::from_millis(200)).await;
+
+        let event_types: Vec<String> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .inspect(|message| {
+                assert_eq!(message["msg_type"], "game_event");
+                assert_eq!(message["table_name"], "ABCD");
+            })
+            .map(|message| message["event"]["msg_type"].to_string())
+            .collect();
+        assert!(event_types.contains(&"new_hand".to_string()));
+        assert!(event_types.contains(&"game_state".to_string()));
+        assert!(event_types.contains(&"finish_hand".to_string()));
+        assert!(other_messages.lock().unwrap().is_empty());
+    }
+
+    /// anyone can subscribe to the events, so a private table publishes none of them,
+    /// and the game state a public table publishes never carries a password
+    #[actix::test]
+    async fn private_table_events_not_published() {
+        let hub = TableHub::new().start();
+        let (addr, messages) = start_collector();
+        hub.send(SubscribeEvents { addr, tables: None }).await.unwrap();
+
+        for (name, password) in [("PRIV", Some("secret".to_string())), ("PUBL", None)] {
+            let mut table = Table::new(hub.clone(), name.to_string(), None, None, 9, 4, 8, 1000, password, Uuid::new_v4());
+            table.add_bot("Bot 0".to_string()).unwrap();
+            table.add_bot("Bot 1".to_string()).unwrap();
+            let actions = Arc::new(Mutex::new(HashMap::new()));
+            let meta_actions = Arc::new(Mutex::new(VecDeque::new()));
+            let handler = std::thread::spawn(move || {
+                table.play(&actions, &meta_actions, Some(1));
+            });
+            while !handler.is_finished() {
+                actix::clock::sleep(Duration::from_millis(100)).await;
+            }
+        }
+        // give the hub a moment to relay the last events
+        actix::clock::sleep(Duration::from_millis(200)).await;
+
+        let events: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|message| message["table_name"] == "PUBL"));
+        assert!(events
+            .iter()
+            .filter(|message| message["event"]["msg_type"] == "game_state")
+            .all(|message| message["event"]["password"].is_null()));
+    }
+
+    /// control characters and surrounding whitespace are stripped from a chosen table name,
+    /// and a name that is too long (or has nothing left) is rejected
+    #[test]
+    fn table_name_sanitized() {
+        assert_eq!(
+            sanitize_table_name("  Friday\u{7}\nNight ", MAX_TABLE_NAME_LEN).ok(),
+            Some("FridayNight".to_string())
+        );
+        assert!(matches!(
+            sanitize_table_name(&"A".repeat(MAX_TABLE_NAME_LEN + 1), MAX_TABLE_NAME_LEN),
+            Err(CreateTableError::TableNameTooLong(MAX_TABLE_NAME_LEN))
+        ));
+        assert!(matches!(
+            sanitize_table_name("\u{1b}\t ", MAX_TABLE_NAME_LEN),
+            Err(CreateTableError::EmptyTableName)
+        ));
+    }
+
+    /// once the hub is at its session cap, a new connection is turned away,
+    /// but someone already connected can still reconnect
+    #[actix::test]
+    async fn max_sessions() {
+        let hub = TableHub::new().with_max_sessions(Some(2)).start();
+        let (addr, _messages) = start_collector();
+        let mut ids = vec![];
+        for _ in 0..2 {
+            let id = Uuid::new_v4();
+            let connected = hub.send(Connect { id, addr: addr.clone() }).await.unwrap();
+            assert_eq!(connected.unwrap(), id);
+            ids.push(id);
+        }
+
+        let rejected = hub.send(Connect { id: Uuid::new_v4(), addr: addr.clone() }).await.unwrap();
+        assert!(matches!(rejected, Err(ConnectError::ServerFull(2))));
+
+        let reconnected = hub.send(Connect { id: ids[0], addr }).await.unwrap();
+        assert_eq!(reconnected.unwrap(), ids[0]);
+    }
+}
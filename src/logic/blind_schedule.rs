@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use super::game_hand::AnteMode;
+
+/// For tournaments, the blinds (and ante) go up every level_duration,
+/// until the last level is reached, where they stay.
+/// Each level is a (small_blind, big_blind, ante)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlindSchedule {
+    levels: Vec<(u32, u32, u32)>,
+    level_duration: Duration,
+    current_level: usize,
+    #[serde(with = "elapsed")]
+    level_started: Instant,
+    big_blind_ante: bool, // does the big blind pay the ante for the table, rather than every player
+}
+
+impl BlindSchedule {
+    /// returns None if there are no levels, since there would be nothing to play at
+    pub fn new(levels: Vec<(u32, u32, u32)>, level_duration: Duration) -> Option<Self> {
+        if levels.is_empty() {
+            return None;
+        }
+        Some(Self {
+            levels,
+            level_duration,
+            current_level: 0,
+            level_started: Instant::now(),
+            big_blind_ante: false,
+        })
+    }
+
+    /// parse a schedule like "10 4/8/0,8/16/0,15/30/5",
+    /// i.e. the minutes per level, then each level as small_blind/big_blind/ante
+    pub fn parse(schedule: &str) -> Option<Self> {
+        let mut parts = schedule.split_whitespace();
+        let minutes = parts.next()?.parse::<u64>().ok()?;
+        let mut levels = vec![];
+        for level in parts.next()?.split(',') {
+            let amounts: Vec<u32> = level
+                .split('/')
+                .map(|amount| amount.parse::<u32>())
+                .collect::<Result<_, _>>()
+                .ok()?;
+            match amounts[..] {
+                [small_blind, big_blind, ante] => levels.push((small_blind, big_blind, ante)),
+                _ => return None,
+            }
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+        BlindSchedule::new(levels, Duration::from_secs(minutes.checked_mul(60)?))
+    }
+
+    /// the schedule takes effect on a table now, so the first level's time starts counting.
+    /// The antes are paid the same way as the table's ante mode
+    pub fn start(&mut self, ante_mode: AnteMode, now: Instant) {
+        self.current_level = 0;
+        self.level_started = now;
+        self.big_blind_ante = matches!(ante_mode, AnteMode::BigBlind(_));
+    }
+
+    /// how the ante of this level is paid
+    pub fn ante_mode(&self, level: usize) -> AnteMode {
+        match self.levels[level].2 {
+            0 => AnteMode::None,
+            ante if self.big_blind_ante => AnteMode::BigBlind(ante),
+            ante => AnteMode::PerPlayer(ante),
+        }
+    }
+
+    /// the (small_blind, big_blind, ante) of the current level
+    pub fn current(&self) -> (u32, u32, u32) {
+        self.levels[self.current_level]
+    }
+
+    /// every (small_blind, big_blind, ante) of the schedule
+    pub fn levels(&self) -> &[(u32, u32, u32)] {
+        &self.levels
+    }
+
+    pub fn current_level(&self) -> usize {
+        self.current_level
+    }
+
+    fn is_last_level(&self) -> bool {
+        self.current_level + 1 == self.levels.len()
+    }
+
+    /// how long until the next level, or None if we are at the last level
+    pub fn time_remaining(&self, now: Instant) -> Option<Duration> {
+        if self.is_last_level() {
+            return None;
+        }
+        Some(self.level_duration.saturating_sub(now.duration_since(self.level_started)))
+    }
+
+    /// move up a level if the current one is over.
+    /// Returns a bool indicating whether the level went up
+    pub fn advance_if_due(&mut self, now: Instant) -> bool {
+        if self.time_remaining(now) != Some(Duration::ZERO) {
+            return false;
+        }
+        self.current_level += 1;
+        self.level_started = now;
+        true
+    }
+}
+
+/// an Instant can't be saved, so when the level started is saved as how long ago that was,
+/// and a restored schedule picks up the level where it left off
+mod elapsed {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::{Duration, Instant};
+
+    pub fn serialize<S: Serializer>(started: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        started.elapsed().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        let elapsed = Duration::deserialize(deserializer)?;
+        let now = Instant::now();
+        Ok(now.checked_sub(elapsed).unwrap_or(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the levels go up once each level's time is over, and stop at the last level
+    #[test]
+    fn advance_through_levels() {
+        let mut schedule = BlindSchedule::parse("10 4/8/0,8/16/2").unwrap();
+        let start = schedule.level_started;
+        assert_eq!(schedule.current(), (4, 8, 0));
+        assert!(!schedule.advance_if_due(start + Duration::from_secs(599)));
+        assert_eq!(schedule.time_remaining(start + Duration::from_secs(599)), Some(Duration::from_secs(1)));
+
+        let level_up = start + Duration::from_secs(600);
+        assert!(schedule.advance_if_due(level_up));
+        assert_eq!(schedule.current(), (8, 16, 2));
+        assert_eq!(schedule.current_level(), 1);
+
+        // the last level lasts forever
+        assert_eq!(schedule.time_remaining(level_up + Duration::from_secs(600)), None);
+        assert!(!schedule.advance_if_due(level_up + Duration::from_secs(600)));
+        assert_eq!(schedule.current(), (8, 16, 2));
+    }
+
+    /// the level's time only starts once the schedule is started on a table,
+    /// and a big blind ante table keeps its antes on the big blind at every level
+    #[test]
+    fn start_keeps_ante_mode() {
+        let mut schedule = BlindSchedule::parse("10 4/8/0,8/16/2").unwrap();
+        let start = schedule.level_started + Duration::from_secs(300);
+        schedule.start(AnteMode::BigBlind(8), start);
+        assert_eq!(schedule.time_remaining(start), Some(Duration::from_secs(600)));
+        assert_eq!(schedule.ante_mode(0), AnteMode::None);
+        assert_eq!(schedule.ante_mode(1), AnteMode::BigBlind(2));
+
+        schedule.start(AnteMode::None, start);
+        assert_eq!(schedule.ante_mode(1), AnteMode::PerPlayer(2));
+    }
+
+    #[test]
+    fn invalid_schedules() {
+        assert!(BlindSchedule::parse("").is_none());
+        assert!(BlindSchedule::parse("10").is_none());
+        assert!(BlindSchedule::parse("ten 4/8/0").is_none());
+        assert!(BlindSchedule::parse("10 4/8").is_none());
+        assert!(BlindSchedule::parse("10 4/8/0 extra").is_none());
+        // too many minutes to count in seconds
+        assert!(BlindSchedule::parse(&format!("{} 4/8/0", u64::MAX / 60 + 1)).is_none());
+        assert!(BlindSchedule::parse(&format!("{} 4/8/0", u64::MAX / 60)).is_some());
+    }
+}
@@ -1,12 +1,15 @@
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 ///
 /// This file contains structs/enums/methods for defining, using, and comparing cards and hands of cards
 ///
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, EnumIter, Hash)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, EnumIter, Hash, Serialize, Deserialize)]
 pub enum Rank {
     Two = 2,
     Three = 3,
@@ -44,7 +47,38 @@ impl fmt::Display for Rank {
     }
 }
 
-#[derive(Eq, PartialEq, Debug, Copy, Clone, EnumIter)]
+impl Rank {
+    /// the rank spelled out, e.g. for describing a hand
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rank::Two => "Two",
+            Rank::Three => "Three",
+            Rank::Four => "Four",
+            Rank::Five => "Five",
+            Rank::Six => "Six",
+            Rank::Seven => "Seven",
+            Rank::Eight => "Eight",
+            Rank::Nine => "Nine",
+            Rank::Ten => "Ten",
+            Rank::Jack => "Jack",
+            Rank::Queen => "Queen",
+            Rank::King => "King",
+            Rank::Ace => "Ace",
+        }
+    }
+
+    /// e.g. "Kings", or "Sixes"
+    pub fn plural_name(&self) -> String {
+        match self {
+            Rank::Six => "Sixes".to_string(),
+            _ => format!("{}s", self.name()),
+        }
+    }
+}
+
+/// Note: the order of the variants is the standard suit order (see Suit::STANDARD_ORDER),
+/// so Suit::iter() goes through them in that order
+#[derive(Eq, PartialEq, Debug, Copy, Clone, EnumIter, Serialize, Deserialize)]
 pub enum Suit {
     Club,
     Diamond,
@@ -52,6 +86,12 @@ pub enum Suit {
     Spade,
 }
 
+impl Suit {
+    /// the order the suits are in within each rank of an unshuffled deck, lowest first (as in bridge).
+    /// This is part of the deck's stable order, which seeded shuffles start from, so it should not change
+    pub const STANDARD_ORDER: [Suit; 4] = [Suit::Club, Suit::Diamond, Suit::Heart, Suit::Spade];
+}
+
 impl fmt::Display for Suit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let string = match self {
@@ -64,7 +104,7 @@ impl fmt::Display for Suit {
     }
 }
 
-#[derive(Eq, Debug, Copy, Clone)]
+#[derive(Eq, Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Card {
     pub rank: Rank,
     pub suit: Suit,
@@ -76,6 +116,43 @@ impl fmt::Display for Card {
     }
 }
 
+/// parse a card as it is displayed, e.g. "As", "Td" or "2c"
+impl FromStr for Card {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let (rank, suit) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(rank), Some(suit), None) => (rank, suit),
+            _ => return Err(format!("{:?} is not a card, e.g. As or Td", s)),
+        };
+        let rank = match rank {
+            '2' => Rank::Two,
+            '3' => Rank::Three,
+            '4' => Rank::Four,
+            '5' => Rank::Five,
+            '6' => Rank::Six,
+            '7' => Rank::Seven,
+            '8' => Rank::Eight,
+            '9' => Rank::Nine,
+            'T' => Rank::Ten,
+            'J' => Rank::Jack,
+            'Q' => Rank::Queen,
+            'K' => Rank::King,
+            'A' => Rank::Ace,
+            _ => return Err(format!("{:?} is not a rank in {:?}", rank, s)),
+        };
+        let suit = match suit {
+            'c' => Suit::Club,
+            'd' => Suit::Diamond,
+            'h' => Suit::Heart,
+            's' => Suit::Spade,
+            _ => return Err(format!("{:?} is not a suit in {:?}", suit, s)),
+        };
+        Ok(Card { rank, suit })
+    }
+}
+
 /// We simply compare Cards based on their rank field.
 impl Ord for Card {
     fn cmp(&self, other: &Self) -> Ordering {
@@ -95,8 +172,8 @@ impl PartialEq for Card {
     }
 }
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, EnumIter)]
-enum HandRanking {
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, EnumIter, Serialize, Deserialize)]
+pub enum HandRanking {
     HighCard = 1,
     Pair = 2,
     TwoPair = 3,
@@ -109,6 +186,41 @@ enum HandRanking {
     RoyalFlush = 10,
 }
 
+impl HandRanking {
+    /// look up a hand ranking by its name, e.g. "FourOfAKind"
+    pub fn from_name(name: &str) -> Option<Self> {
+        HandRanking::iter().find(|ranking| format!("{:?}", ranking) == name)
+    }
+}
+
+/// which rules the hands are ranked by. Short-deck (6+) Hold'em plays without the 2s through 5s,
+/// which makes a flush rarer than a full house, so the flush beats it.
+/// The ace still plays low, in the straight A-6-7-8-9
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum HandRules {
+    Standard,
+    ShortDeck,
+}
+
+impl HandRules {
+    /// the lowest rank in the deck, which the ace wraps around to for the lowest straight
+    pub fn lowest_rank(&self) -> Rank {
+        match self {
+            HandRules::Standard => Rank::Two,
+            HandRules::ShortDeck => Rank::Six,
+        }
+    }
+
+    /// where the ranking places in the order of hands, the greater being the better hand
+    fn ranking_order(&self, hand_ranking: HandRanking) -> u32 {
+        match (self, hand_ranking) {
+            (HandRules::ShortDeck, HandRanking::Flush) => HandRanking::FullHouse as u32,
+            (HandRules::ShortDeck, HandRanking::FullHouse) => HandRanking::Flush as u32,
+            _ => hand_ranking as u32,
+        }
+    }
+}
+
 /// The hand result has the HandRanking, for quick comparisons, then the cads that make
 /// up that HandRanking, along with the remaining kicker cards for tie breaking (sorted)
 /// There is also a field "value", which gives a value of the hand that can be used to quickly
@@ -149,9 +261,10 @@ impl PartialEq for HandResult {
         self.value == other.value
     }
 }
-impl ToString for HandResult {
-    fn to_string(&self) -> String {
-        format!(
+impl fmt::Display for HandResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
             "{:?}: {}, {}",
             self.hand_ranking,
             self.constituent_cards
@@ -174,11 +287,12 @@ impl HandResult {
     /// note that the cards have been specially pre-sorted by analyze_hand,
     /// and hence this shouldn't be called otherwise
     fn score_hand(
+        rules: HandRules,
         hand_ranking: HandRanking,
-        constituent_cards: &Vec<Card>,
+        constituent_cards: &[Card],
         kickers: &[Card],
     ) -> u32 {
-        let mut value = hand_ranking as u32;
+        let mut value = rules.ranking_order(hand_ranking);
         value <<= 20; // shift it into the most significant area we need
 
         // add the values of the constituent cards and then kickers
@@ -210,7 +324,12 @@ impl HandResult {
 
     /// Given a hand of 5 cards, we return a HandResult, which tells
     /// us the hand ranking, the constituent cards, kickers, and hand score    
-    pub fn analyze_hand(mut five_cards: Vec<Card>) -> Self {
+    pub fn analyze_hand(five_cards: Vec<Card>) -> Self {
+        HandResult::analyze_hand_with(five_cards, HandRules::Standard)
+    }
+
+    /// the same as analyze_hand, but ranked by the given rules
+    pub fn analyze_hand_with(mut five_cards: Vec<Card>, rules: HandRules) -> Self {
         assert!(five_cards.len() == 5);
         five_cards.sort(); // first sort by Rank
 
@@ -228,8 +347,8 @@ impl HandResult {
             if card.suit != first_suit {
                 is_flush = false;
             }
-            if is_straight && i == 4 && card.rank == Rank::Ace && first_rank == 2 {
-                // completing the straight with an Ace on 2-->Ace
+            if is_straight && i == 4 && card.rank == Rank::Ace && first_rank == rules.lowest_rank() as usize {
+                // completing the straight with an Ace on 2-->Ace (or 6-->Ace in short-deck)
                 is_low_ace_straight = true;
             } else if card.rank as usize != first_rank + i {
                 is_straight = false;
@@ -240,7 +359,7 @@ impl HandResult {
         let mut kickers = Vec::new();
 
         if is_flush && is_straight {
-            if let Rank::Ace = five_cards[4].rank {
+            if five_cards[4].rank == Rank::Ace && !is_low_ace_straight {
                 hand_ranking = HandRanking::RoyalFlush;
             } else {
                 hand_ranking = HandRanking::StraightFlush;
@@ -334,7 +453,7 @@ impl HandResult {
         }
 
         kickers.sort();
-        let value = HandResult::score_hand(hand_ranking, &constituent_cards, &kickers);
+        let value = HandResult::score_hand(rules, hand_ranking, &constituent_cards, &kickers);
         Self {
             hand_ranking,
             constituent_cards,
@@ -343,6 +462,48 @@ impl HandResult {
         }
     }
 
+    /// Given any number of cards (at least 5), we look at every 5 card combination
+    /// and return the best HandResult among them.
+    /// Returns None if there are fewer than 5 cards
+    pub fn best_hand(cards: &[Card]) -> Option<Self> {
+        HandResult::best_hand_with(cards, HandRules::Standard)
+    }
+
+    /// the same as best_hand, but ranked by the given rules
+    pub fn best_hand_with(cards: &[Card], rules: HandRules) -> Option<Self> {
+        let mut best_result: Option<HandResult> = None;
+        let num_cards = cards.len();
+        let mut indices = [0, 1, 2, 3, 4];
+        if num_cards < 5 {
+            return None;
+        }
+        loop {
+            let possible_hand = indices.iter().map(|i| cards[*i]).collect();
+            let current_result = HandResult::analyze_hand_with(possible_hand, rules);
+            match best_result {
+                Some(ref result) if current_result <= *result => (),
+                _ => best_result = Some(current_result),
+            }
+            // move on to the next combination of indices, in lexicographic order
+            let mut pos = 5;
+            while pos > 0 && indices[pos - 1] == num_cards - 5 + pos - 1 {
+                pos -= 1;
+            }
+            if pos == 0 {
+                break;
+            }
+            indices[pos - 1] += 1;
+            for j in pos..5 {
+                indices[j] = indices[j - 1] + 1;
+            }
+        }
+        best_result
+    }
+
+    pub fn hand_ranking(&self) -> HandRanking {
+	self.hand_ranking
+    }
+
     pub fn hand_ranking_string(&self) -> String {
         format!(
             "{:?}",
@@ -359,6 +520,26 @@ impl HandResult {
 	    .to_string()
     }
 
+    /// a description of the hand for people, e.g. "Full House, Kings full of Threes".
+    /// The constituent cards are sorted by analyze_hand, so the deciding rank is at the end
+    /// (and for a full house the trips are at the end, while the pair is at the start)
+    pub fn description(&self) -> String {
+        let first = self.constituent_cards[0].rank;
+        let last = self.constituent_cards[self.constituent_cards.len() - 1].rank;
+        match self.hand_ranking {
+            HandRanking::HighCard => format!("High Card, {} high", last.name()),
+            HandRanking::Pair => format!("Pair, {}", last.plural_name()),
+            HandRanking::TwoPair => format!("Two Pair, {} and {}", last.plural_name(), first.plural_name()),
+            HandRanking::ThreeOfAKind => format!("Three of a Kind, {}", last.plural_name()),
+            HandRanking::Straight => format!("Straight, {} high", last.name()),
+            HandRanking::Flush => format!("Flush, {} high", last.name()),
+            HandRanking::FullHouse => format!("Full House, {} full of {}", last.plural_name(), first.plural_name()),
+            HandRanking::FourOfAKind => format!("Four of a Kind, {}", last.plural_name()),
+            HandRanking::StraightFlush => format!("Straight Flush, {} high", last.name()),
+            HandRanking::RoyalFlush => "Royal Flush".to_string(),
+        }
+    }
+
     pub fn kickers_string(&self) -> String {
         self.kickers
             .iter()
@@ -375,6 +556,27 @@ impl HandResult {
 mod tests {
     use super::*;
     
+    /// every card parses back from how it is displayed
+    #[test]
+    fn card_string_round_trip() {
+        for rank in Rank::iter() {
+            for suit in Suit::iter() {
+                let card = Card { rank, suit };
+                let parsed = card.to_string().parse::<Card>().unwrap();
+                // Card equality only compares ranks, so compare the suits too
+                assert_eq!((parsed.rank, parsed.suit), (rank, suit));
+            }
+        }
+        assert_eq!(Card::from_str("Td").unwrap().to_string(), "Td");
+    }
+
+    #[test]
+    fn card_string_rejects_bad_input() {
+        for bad in ["", "A", "Asd", "1s", "10s", "as", "AS", "Ax", " As"] {
+            assert!(bad.parse::<Card>().is_err(), "{:?} should not parse", bad);
+        }
+    }
+
     #[test]
     fn compare_high_card_and_pair() {
         let hand1 = vec![
@@ -1011,5 +1213,43 @@ mod tests {
         assert_eq!(result2.hand_ranking, HandRanking::Flush);
         assert!(result1 > result2);
     }
-    
+
+    fn parse_cards(text: &str) -> Vec<Card> {
+        text.split_whitespace().map(|card| card.parse().unwrap()).collect()
+    }
+
+    fn short_deck_hand(text: &str) -> HandResult {
+        HandResult::analyze_hand_with(parse_cards(text), HandRules::ShortDeck)
+    }
+
+    /// in short-deck a flush is harder to make than a full house, so it wins
+    #[test]
+    fn short_deck_flush_beats_full_house() {
+        let flush = short_deck_hand("6c 8c 9c Jc Kc");
+        let full_house = short_deck_hand("Ac Ad Ah Ks Kd");
+        assert_eq!(flush.hand_ranking, HandRanking::Flush);
+        assert_eq!(full_house.hand_ranking, HandRanking::FullHouse);
+        assert!(flush > full_house);
+        // the rest of the order is as usual
+        assert!(short_deck_hand("Ac Ad Ah As 6d") > flush);
+        assert!(flush > short_deck_hand("6d 7c 8h 9s Ts"));
+
+        // while with the regular rankings, the full house wins
+        assert!(HandResult::analyze_hand(parse_cards("6c 8c 9c Jc Kc")) < HandResult::analyze_hand(parse_cards("Ac Ad Ah Ks Kd")));
+    }
+
+    /// in short-deck the ace plays low in A-6-7-8-9, the lowest straight
+    #[test]
+    fn short_deck_wheel() {
+        let wheel = short_deck_hand("As 6d 7c 8h 9s");
+        assert_eq!(wheel.hand_ranking, HandRanking::Straight);
+        assert_eq!(wheel.constituent_cards[0].rank, Rank::Ace);
+        assert!(wheel < short_deck_hand("6d 7c 8h 9s Ts"));
+        assert!(wheel > short_deck_hand("Ts Ad Ah Kd Qc"));
+
+        let steel_wheel = short_deck_hand("As 6s 7s 8s 9s");
+        assert_eq!(steel_wheel.hand_ranking, HandRanking::StraightFlush);
+        // without the short-deck rules, it is just ace high
+        assert_eq!(HandResult::analyze_hand(parse_cards("As 6d 7c 8h 9s")).hand_ranking, HandRanking::HighCard);
+    }
 }
@@ -1,4 +1,10 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom; // for shuffling a vec
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::fmt;
+use std::str::FromStr;
 
 use super::card::{Card, Rank, Suit};
 
@@ -14,26 +20,156 @@ pub trait Deck: Send + std::fmt::Debug {
     /// shuffle the deck to randomize (possibly) the output of future cards
     fn shuffle(&mut self);
 
+    /// shuffle into the order given by the seed, so the same seed always gives the same deck.
+    /// Useful for reproducing a game when debugging, or for proving the deal was fair
+    fn shuffle_with_seed(&mut self, seed: u64);
+
+    /// shuffle with the seed, and commit to it with a hash that can be published before the deal.
+    /// Revealing the seed once the hand is over lets anyone check the deck order was fixed in advance
+    fn shuffle_with_commitment(&mut self, seed: u64) -> ShuffleCommitment {
+        self.shuffle_with_seed(seed);
+        ShuffleCommitment::new(seed)
+    }
+
     /// give us a single card. Optional, because the deck may be exhausted
     fn draw_card(&mut self) -> Option<Card>;
+
+    /// how many cards are left to be drawn before the deck is exhausted
+    fn remaining(&self) -> usize;
+
+    /// the cards left to be drawn, in the order they will be drawn
+    fn peek_remaining(&self) -> Vec<Card>;
+}
+
+/// when does the table reshuffle the deck
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum ReshufflePolicy {
+    EveryHand,
+    // keep dealing through the stub, and only reshuffle once it can't cover the next hand
+    WhenNeeded,
+}
+
+impl fmt::Display for ReshufflePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let output = match self {
+            ReshufflePolicy::EveryHand => "every_hand",
+            ReshufflePolicy::WhenNeeded => "when_needed",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// what happens when a hole card is exposed during the deal,
+/// e.g. if a protocol bug or a client reveals it before the betting starts
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum ExposurePolicy {
+    Continue, // everyone is told which card was exposed, and the hand plays on
+    VoidHand, // the hand is dead and is dealt again
+}
+
+impl fmt::Display for ExposurePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let output = match self {
+            ExposurePolicy::Continue => "continue",
+            ExposurePolicy::VoidHand => "void_hand",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// a shuffle seed along with the salted hash committing to it.
+/// The hash is published when the hand starts, and the seed and salt when it ends
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShuffleCommitment {
+    pub seed: u64,
+    pub salt: String, // random, so the seed can't be found by hashing every possible u64
+    pub hash: String,
+}
+
+impl ShuffleCommitment {
+    fn new(seed: u64) -> Self {
+        let salt: [u8; 16] = rand::thread_rng().gen();
+        let salt: String = salt.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let hash = commitment_hash(seed, &salt);
+        Self { seed, salt, hash }
+    }
 }
 
+/// the hex digest of the salt and seed, which is what gets published before the deal
+pub fn commitment_hash(seed: u64, salt: &str) -> String {
+    let digest = Sha1::digest(format!("{}:{}", salt, seed).as_bytes());
+    format!("{:x}", digest)
+}
+
+/// for clients to verify a hand: if the revealed seed and salt match the published commitment,
+/// rebuild the deck in the order it was dealt from, to check the hole cards, burns and board against
+#[allow(dead_code)]
+pub fn verify_shuffle(hash: &str, seed: u64, salt: &str) -> Option<Vec<Card>> {
+    if commitment_hash(seed, salt) != hash {
+        return None;
+    }
+    let mut deck = StandardDeck::new();
+    deck.shuffle_with_seed(seed);
+    Some(deck.peek_remaining())
+}
+
+/// A deck is built in a stable order before it is shuffled: by rank from the lowest up to the aces,
+/// and within each rank by suit, in Suit::STANDARD_ORDER unless another suit order is given.
+/// So an unshuffled standard deck starts with 2c and ends with As.
+/// A seeded shuffle always starts from this order, so the same seed deals the same cards
 #[derive(Debug)]
 pub struct StandardDeck {
     cards: Vec<Card>,
     top: usize, // index that we deal the next card from
+    lowest_rank: Rank, // a short deck has no cards below a six
+    suit_order: [Suit; 4],
 }
 
 impl StandardDeck {
     pub fn new() -> Self {
-        // returns a new unshuffled deck of 52 cards
+        // returns a new shuffled deck of 52 cards
+        let mut deck = StandardDeck::unshuffled();
+        deck.shuffle();
+        deck
+    }
+
+    /// a shuffled short deck of 36 cards, without the 2s through 5s, for short-deck (6+) Hold'em
+    pub fn short() -> Self {
+        let mut deck = StandardDeck::ordered(Rank::Six, Suit::STANDARD_ORDER);
+        deck.shuffle();
+        deck
+    }
+
+    /// all 52 cards in the stable order, e.g. for golden tests
+    pub fn unshuffled() -> Self {
+        StandardDeck::ordered(Rank::Two, Suit::STANDARD_ORDER)
+    }
+
+    /// all 52 cards in the stable order, but with the suits in the given order within each rank.
+    /// Seeded shuffles of this deck start from this order too
+    #[allow(dead_code)]
+    pub fn unshuffled_with_suit_order(suit_order: [Suit; 4]) -> Self {
+        StandardDeck::ordered(Rank::Two, suit_order)
+    }
+
+    fn ordered(lowest_rank: Rank, suit_order: [Suit; 4]) -> Self {
+        Self {
+            cards: StandardDeck::ordered_cards(lowest_rank, suit_order),
+            top: 0,
+            lowest_rank,
+            suit_order,
+        }
+    }
+
+    /// every card from the lowest rank up, in order of rank and then suit
+    fn ordered_cards(lowest_rank: Rank, suit_order: [Suit; 4]) -> Vec<Card> {
         let mut cards = Vec::<Card>::with_capacity(52);
-        for rank in Rank::iter() {
-            for suit in Suit::iter() {
+        for rank in Rank::iter().filter(|rank| *rank >= lowest_rank) {
+            for suit in suit_order {
                 cards.push(Card { rank, suit });
             }
         }
-        Self { cards, top: 0 }
+        cards
     }
 }
 
@@ -44,6 +180,13 @@ impl Deck for StandardDeck {
         self.top = 0;
     }
 
+    fn shuffle_with_seed(&mut self, seed: u64) {
+        // start from the ordered deck, so the order only depends on the seed and not on past shuffles
+        self.cards = StandardDeck::ordered_cards(self.lowest_rank, self.suit_order);
+        self.cards.shuffle(&mut StdRng::seed_from_u64(seed));
+        self.top = 0;
+    }
+
     fn draw_card(&mut self) -> Option<Card> {
         // take the top card from the deck and move the index of the top of the deck
         if self.top == self.cards.len() {
@@ -55,6 +198,14 @@ impl Deck for StandardDeck {
             Some(card)
         }
     }
+
+    fn remaining(&self) -> usize {
+        self.cards.len() - self.top
+    }
+
+    fn peek_remaining(&self) -> Vec<Card> {
+        self.cards[self.top..].to_vec()
+    }
 }
 
 /// the rigged deck is useful for unit testing
@@ -79,10 +230,23 @@ impl RiggedDeck {
     }
 }
 
+/// a rigged deck dealt in the order of the cards given, e.g. "As Ad Kc"
+impl FromStr for RiggedDeck {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = s.split_whitespace().map(Card::from_str).collect::<Result<Vec<Card>, String>>()?;
+        Ok(Self { cards, top: 0 })
+    }
+}
+
 impl Deck for RiggedDeck {
     /// shuffle does nothing
     fn shuffle(&mut self) {}
 
+    /// nor does a seeded shuffle
+    fn shuffle_with_seed(&mut self, _seed: u64) {}
+
     fn draw_card(&mut self) -> Option<Card> {
         // take the top card from the deck and move the index of the top of the deck
         if self.top == self.cards.len() {
@@ -94,4 +258,89 @@ impl Deck for RiggedDeck {
             Some(card)
         }
     }
+
+    fn remaining(&self) -> usize {
+        self.cards.len() - self.top
+    }
+
+    fn peek_remaining(&self) -> Vec<Card> {
+        self.cards[self.top..].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw_all(deck: &mut dyn Deck) -> Vec<Card> {
+        std::iter::from_fn(|| deck.draw_card()).collect()
+    }
+
+    /// the same seed deals the same cards, even after the decks were shuffled differently before
+    #[test]
+    fn seeded_shuffle_is_reproducible() {
+        let mut deck1 = StandardDeck::new();
+        let mut deck2 = StandardDeck::new();
+        deck1.draw_card();
+        deck1.shuffle_with_seed(42);
+        deck2.shuffle_with_seed(42);
+        let cards1 = draw_all(&mut deck1);
+        let cards2 = draw_all(&mut deck2);
+        assert_eq!(cards1.len(), 52);
+        // Card equality only compares ranks, so compare the suits too
+        assert!(cards1.iter().zip(cards2.iter()).all(|(a, b)| a.rank == b.rank && a.suit == b.suit));
+
+        // and a different seed deals a different order
+        deck2.shuffle_with_seed(43);
+        let cards3 = draw_all(&mut deck2);
+        assert!(!cards1.iter().zip(cards3.iter()).all(|(a, b)| a.rank == b.rank && a.suit == b.suit));
+    }
+
+    /// an unshuffled deck is in the documented order, from the lowest club to the highest spade
+    #[test]
+    fn unshuffled_deck_order() {
+        let cards = draw_all(&mut StandardDeck::unshuffled());
+        assert_eq!(cards.len(), 52);
+        let first_and_last = [cards[0], cards[1], cards[50], cards[51]].map(|card| card.to_string());
+        assert_eq!(first_and_last, ["2c", "2d", "Ah", "As"]);
+
+        let cards = draw_all(&mut StandardDeck::unshuffled_with_suit_order([Suit::Spade, Suit::Heart, Suit::Diamond, Suit::Club]));
+        let first_and_last = [cards[0], cards[1], cards[50], cards[51]].map(|card| card.to_string());
+        assert_eq!(first_and_last, ["2s", "2h", "Ad", "Ac"]);
+    }
+
+    /// a short deck has the 36 cards from the sixes up, even after a seeded shuffle
+    #[test]
+    fn short_deck_has_36_cards() {
+        let mut deck = StandardDeck::short();
+        deck.shuffle_with_seed(7);
+        let cards = draw_all(&mut deck);
+        assert_eq!(cards.len(), 36);
+        assert!(cards.iter().all(|card| card.rank >= Rank::Six));
+    }
+
+    #[test]
+    fn rigged_deck_from_str() {
+        let mut deck = RiggedDeck::from_str("As Ad  Kc\n2h").unwrap();
+        let cards: Vec<String> = draw_all(&mut deck).iter().map(|card| card.to_string()).collect();
+        assert_eq!(cards, ["As", "Ad", "Kc", "2h"]);
+        assert!(RiggedDeck::from_str("As Xd").is_err());
+    }
+
+    /// the revealed seed checks out against the commitment and rebuilds the dealt order, and a wrong seed doesn't
+    #[test]
+    fn shuffle_commitment_round_trip() {
+        let mut deck = StandardDeck::new();
+        let commitment = deck.shuffle_with_commitment(1234);
+        assert_eq!(commitment.hash.len(), 40);
+        assert_ne!(commitment.hash, commitment_hash(1234, ""));
+        let dealt = draw_all(&mut deck);
+
+        let rebuilt = verify_shuffle(&commitment.hash, commitment.seed, &commitment.salt).unwrap();
+        assert_eq!(rebuilt.len(), 52);
+        assert!(dealt.iter().zip(rebuilt.iter()).all(|(a, b)| a.rank == b.rank && a.suit == b.suit));
+
+        assert!(verify_shuffle(&commitment.hash, 1235, &commitment.salt).is_none());
+        assert!(verify_shuffle(&commitment.hash, commitment.seed, "00").is_none());
+    }
 }
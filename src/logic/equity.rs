@@ -0,0 +1,147 @@
+use rand::seq::SliceRandom;
+
+use super::card::{Card, HandResult, Rank, Suit};
+use super::game_hand::GameVariant;
+use super::hand_eval;
+
+use strum::IntoEnumIterator;
+
+/// when more than this many board cards are still to come, there are too many
+/// run-outs to look at them all, so we sample random run-outs instead
+const MAX_EXACT_MISSING: usize = 2;
+
+/// how many random run-outs we look at when sampling
+const NUM_SAMPLES: usize = 10_000;
+
+/// Given the hole cards of each player still in the hand and the board so far,
+/// returns each player's share of the pot (between 0 and 1) if the hand were played out.
+/// A tied run-out is shared equally among the tied players.
+/// With at most two board cards to come, every run-out is looked at, so the equities are exact.
+/// Otherwise, they are estimated from random run-outs.
+pub fn equities(variant: GameVariant, hands: &[Vec<Card>], board: &[Card]) -> Vec<f64> {
+    let remaining = remaining_cards(variant, hands, board);
+    let num_missing = 5 - board.len();
+    let mut shares = vec![0.0; hands.len()];
+    let mut num_runouts = 0;
+    if num_missing <= MAX_EXACT_MISSING {
+        let mut runout = Vec::with_capacity(num_missing);
+        for_each_runout(&remaining, num_missing, 0, &mut runout, &mut |runout| {
+            add_shares(variant, hands, board, runout, &mut shares);
+            num_runouts += 1;
+        });
+    } else {
+        let mut rng = rand::thread_rng();
+        for _ in 0..NUM_SAMPLES {
+            let runout: Vec<Card> = remaining
+                .choose_multiple(&mut rng, num_missing)
+                .copied()
+                .collect();
+            add_shares(variant, hands, board, &runout, &mut shares);
+            num_runouts += 1;
+        }
+    }
+    shares.iter().map(|share| share / num_runouts as f64).collect()
+}
+
+/// every card in the variant's deck that is not in someone's hand or already on the board.
+/// Note: Card equality only compares ranks, so we need to compare the suits as well
+fn remaining_cards(variant: GameVariant, hands: &[Vec<Card>], board: &[Card]) -> Vec<Card> {
+    let mut remaining = Vec::with_capacity(52);
+    let lowest_rank = variant.hand_rules().lowest_rank();
+    for rank in Rank::iter().filter(|rank| *rank >= lowest_rank) {
+        for suit in Suit::iter() {
+            let is_dealt = hands
+                .iter()
+                .flatten()
+                .chain(board.iter())
+                .any(|card| card.rank == rank && card.suit == suit);
+            if !is_dealt {
+                remaining.push(Card { rank, suit });
+            }
+        }
+    }
+    remaining
+}
+
+/// call the given function with every combination of num_missing cards from the remaining cards
+fn for_each_runout<F: FnMut(&[Card])>(
+    remaining: &[Card],
+    num_missing: usize,
+    start: usize,
+    runout: &mut Vec<Card>,
+    f: &mut F,
+) {
+    if runout.len() == num_missing {
+        f(runout);
+        return;
+    }
+    for i in start..remaining.len() {
+        runout.push(remaining[i]);
+        for_each_runout(remaining, num_missing, i + 1, runout, f);
+        runout.pop();
+    }
+}
+
+/// find the best hand(s) for the given run-out, and split a single share between them
+fn add_shares(variant: GameVariant, hands: &[Vec<Card>], board: &[Card], runout: &[Card], shares: &mut [f64]) {
+    let full_board: Vec<Card> = board.iter().chain(runout.iter()).copied().collect();
+    let results: Vec<HandResult> = hands
+        .iter()
+        .map(|hand| hand_eval::best_hand_for(variant, hand, &full_board).unwrap())
+        .collect();
+    let best = results.iter().max().unwrap();
+    let num_best = results.iter().filter(|result| *result == best).count();
+    for (share, result) in shares.iter_mut().zip(results.iter()) {
+        if result == best {
+            *share += 1.0 / num_best as f64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// kings are behind aces on a dry flop, and can only win by hitting one of the
+    /// two remaining kings without an ace also coming: 83 of the 990 run-outs
+    #[test]
+    fn kings_against_aces_on_the_flop() {
+        let aces = vec![
+            Card {
+                rank: Rank::Ace,
+                suit: Suit::Heart,
+            },
+            Card {
+                rank: Rank::Ace,
+                suit: Suit::Spade,
+            },
+        ];
+        let kings = vec![
+            Card {
+                rank: Rank::King,
+                suit: Suit::Heart,
+            },
+            Card {
+                rank: Rank::King,
+                suit: Suit::Diamond,
+            },
+        ];
+        let flop = vec![
+            Card {
+                rank: Rank::Two,
+                suit: Suit::Club,
+            },
+            Card {
+                rank: Rank::Seven,
+                suit: Suit::Diamond,
+            },
+            Card {
+                rank: Rank::Nine,
+                suit: Suit::Spade,
+            },
+        ];
+        let result = equities(GameVariant::Holdem, &[aces, kings], &flop);
+        assert!((result[0] - 907.0 / 990.0).abs() < 1e-9);
+        assert!((result[1] - 83.0 / 990.0).abs() < 1e-9);
+    }
+}
@@ -1,11 +1,15 @@
+use std::cmp;
 use std::fmt;
 use std::collections::{HashMap, HashSet};
 
-use super::card::{Card, HandResult};
+use super::card::{Card, HandRanking, HandResult, HandRules};
+use super::equity;
 use super::player::{Player, PlayerConfig, PlayerAction};
 use super::pots::PotManager;
+use super::game_state::GameStateDto;
 
 use json::object;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
@@ -14,6 +18,7 @@ pub enum Street {
     Flop,
     Turn,
     River,
+    Seventh, // the last betting round of Seven-Card Stud, with the final card dealt down
     ShowDown,
 }
 
@@ -25,35 +30,321 @@ impl fmt::Display for Street {
 	    Street::Flop => "flop".to_owned(),
 	    Street::Turn => "turn".to_owned(),
 	    Street::River => "river".to_owned(),
+	    Street::Seventh => "seventh".to_owned(),
 	    Street::ShowDown => "showdown".to_owned(),
 	};
         write!(f, "{}", output)
     }
 }
 			
+/// There are two conventions for how big a raise must be.
+/// Most rooms require a raise to be at least as large as the last raise increment
+/// (e.g. after a bet of 40 and a raise to 100, the next raise must be to at least 160),
+/// but some require it to be at least the size of the last full bet (so at least to 200).
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum MinRaiseRule {
+    LastRaiseIncrement,
+    LastFullBet,
+}
+
+impl fmt::Display for MinRaiseRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+	let output = match self {
+	    MinRaiseRule::LastRaiseIncrement => "raise_increment",
+	    MinRaiseRule::LastFullBet => "full_bet",
+	};
+        write!(f, "{}", output)
+    }
+}
+
+/// how much a player is allowed to bet or raise
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+#[allow(clippy::enum_variant_names)]
+pub enum BettingStructure {
+    NoLimit, // anything up to your whole stack
+    PotLimit, // at most the size of the pot, after calling
+    FixedLimit, // exactly one small bet (preflop and flop) or big bet (turn and river), with a cap on raises per street
+}
+
+impl fmt::Display for BettingStructure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+	let output = match self {
+	    BettingStructure::NoLimit => "no_limit",
+	    BettingStructure::PotLimit => "pot_limit",
+	    BettingStructure::FixedLimit => "fixed_limit",
+	};
+        write!(f, "{}", output)
+    }
+}
+
+/// which game is dealt. In Omaha, a hand must use exactly two of the hole cards and three of the board.
+/// Seven-Card Stud has no board: each player is dealt their own seven cards, four of them face up.
+/// Short-deck is Hold'em with the 2s through 5s taken out of the deck (see HandRules)
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum GameVariant {
+    Holdem,
+    Omaha,
+    SevenStud,
+    ShortDeck,
+}
+
+impl GameVariant {
+    /// how many cards each player holds by the end of the hand
+    pub fn num_hole_cards(&self) -> usize {
+	match self {
+	    GameVariant::Holdem | GameVariant::ShortDeck => 2,
+	    GameVariant::Omaha => 4,
+	    GameVariant::SevenStud => 7,
+	}
+    }
+
+    /// how many cards each player is dealt before the first betting round.
+    /// In Stud, that is two down and one up, and the rest come one street at a time
+    pub fn num_starting_cards(&self) -> usize {
+	match self {
+	    GameVariant::SevenStud => 3,
+	    _ => self.num_hole_cards(),
+	}
+    }
+
+    /// which rules the hands are ranked by, which also decides which cards are in the deck
+    pub fn hand_rules(&self) -> HandRules {
+	match self {
+	    GameVariant::ShortDeck => HandRules::ShortDeck,
+	    _ => HandRules::Standard,
+	}
+    }
+
+    /// whether the hand is played out on a shared board
+    pub fn has_board(&self) -> bool {
+	*self != GameVariant::SevenStud
+    }
+
+    /// the last street with a betting round, after which comes the showdown
+    pub fn last_street(&self) -> Street {
+	match self {
+	    GameVariant::SevenStud => Street::Seventh,
+	    _ => Street::River,
+	}
+    }
+
+    /// whether the card dealt to a player at the given position (in the order they were dealt)
+    /// is face up for everyone to see. In Stud, the third through sixth cards are
+    pub fn is_up_card(&self, position: usize) -> bool {
+	*self == GameVariant::SevenStud && (2..6).contains(&position)
+    }
+}
+
+impl fmt::Display for GameVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+	let output = match self {
+	    GameVariant::Holdem => "holdem",
+	    GameVariant::Omaha => "omaha",
+	    GameVariant::SevenStud => "seven_stud",
+	    GameVariant::ShortDeck => "short_deck",
+	};
+        write!(f, "{}", output)
+    }
+}
+
+/// optionally, the action clock can grow for the bigger decisions
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum ClockScaling {
+    Off, // everyone always gets the action_timeout
+    PotSize, // an extra second for every CLOCK_SCALING_POT_BIG_BLINDS big blinds in the pot
+    PlayersInHand, // an extra CLOCK_SCALING_SECONDS_PER_PLAYER seconds for each active player past heads-up
+}
+
+impl fmt::Display for ClockScaling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+	let output = match self {
+	    ClockScaling::Off => "off",
+	    ClockScaling::PotSize => "pot_size",
+	    ClockScaling::PlayersInHand => "players_in_hand",
+	};
+        write!(f, "{}", output)
+    }
+}
+
+/// who pays the ante each hand, and how much
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum AnteMode {
+    None,
+    PerPlayer(u32), // everyone dealt in antes this much (before or after the blinds, see AnteOrder)
+    BigBlind(u32), // the big blind posts this much for the whole table along with their blind
+}
+
+impl fmt::Display for AnteMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+	let output = match self {
+	    AnteMode::None => "none".to_owned(),
+	    AnteMode::PerPlayer(amount) => format!("{} per player", amount),
+	    AnteMode::BigBlind(amount) => format!("{} from the big blind", amount),
+	};
+        write!(f, "{}", output)
+    }
+}
+
+/// once the betting is closed with a player all-in, which hand is turned face up first.
+/// Either the last player to bet or raise shows first, as at a showdown,
+/// or the hands go in position order, starting left of the button
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum RevealOrder {
+    LastAggressor,
+    Position,
+}
+
+impl fmt::Display for RevealOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+	let output = match self {
+	    RevealOrder::LastAggressor => "last_aggressor",
+	    RevealOrder::Position => "position",
+	};
+        write!(f, "{}", output)
+    }
+}
+
+/// whether a per player ante is collected before or after the blinds.
+/// This decides whether a short-stacked blind is all-in on the ante or on the blind
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum AnteOrder {
+    BeforeBlinds,
+    AfterBlinds,
+}
+
+impl fmt::Display for AnteOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+	let output = match self {
+	    AnteOrder::BeforeBlinds => "before_blinds",
+	    AnteOrder::AfterBlinds => "after_blinds",
+	};
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum Blind {
+    Small,
+    Big,
+}
+
+/// Which blinds are posted depends on how many players are dealt into the hand.
+/// The blinds in the list are posted in order by the players after the button,
+/// and the action starts with the player after the last blind.
+/// e.g. some formats only use a single big blind when heads-up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlindConfig {
+    pub heads_up: Vec<Blind>,
+    pub three_handed: Vec<Blind>,
+    pub full_ring: Vec<Blind>, // four or more players
+}
+
+impl Default for BlindConfig {
+    fn default() -> Self {
+	Self {
+	    heads_up: vec![Blind::Small, Blind::Big],
+	    three_handed: vec![Blind::Small, Blind::Big],
+	    full_ring: vec![Blind::Small, Blind::Big],
+	}
+    }
+}
+
+impl BlindConfig {
+    pub fn blinds_for(&self, num_players: usize) -> &[Blind] {
+	match num_players {
+	    0..=2 => &self.heads_up,
+	    3 => &self.three_handed,
+	    _ => &self.full_ring,
+	}
+    }
+}
+
+/// what one player got out of one pot at the end of a hand.
+/// The amounts are structured so clients can move the chips of each pot,
+/// while the summary has the human readable details, e.g. the player's name and the hand they showed
+#[derive(Debug, Clone)]
+pub struct Settlement {
+    pub player_id: Uuid,
+    pub seat: usize,
+    pub amount_won: u32,
+    pub pot_index: usize,
+    pub winner: bool,
+    pub run: Option<usize>, // which run of the board this is for, when it was run twice
+    pub summary: json::JsonValue,
+}
+
+impl Settlement {
+    /// the structured payout to send to clients. The player id stays private, since it is their session id
+    pub fn payout_json(&self) -> json::JsonValue {
+	object! {
+	    seat: self.seat,
+	    pot_index: self.pot_index,
+	    amount_won: self.amount_won,
+	    run: self.run,
+	}
+    }
+}
+
+/// the most seats a table can have, which bounds the per-seat contribution tracking
+pub const MAX_SEATS: usize = 9;
+
 #[derive(Debug)]
 pub struct GameHand {
+    pub variant: GameVariant, // decides how the hole cards and board make a hand
     pub street: Street,
     pot_manager: PotManager,
-    pub street_contributions: HashMap<Street, [u32; 9]>, // how much a player contributed to the pot during each street
+    pub street_contributions: HashMap<Street, [u32; MAX_SEATS]>, // how much a player contributed to the pot during each street
+    pub ante_contributions: [u32; MAX_SEATS], // antes are dead money, so they don't count towards the preflop street bet
     pub current_bet: u32, // the current street bet at any moment
+    pub last_full_raise_size: u32, // how much the last full bet/raise of the street went up by
+    pub last_full_bet: u32, // the street bet as of the last full bet/raise
+    pub acted_since_full_raise: HashSet<Uuid>, // players who can't raise again unless there is a full raise
+    pub num_raises: u32, // how many raises there have been this street, for the fixed limit cap
     pub flop: Option<Vec<Card>>,
     pub turn: Option<Card>,
     pub river: Option<Card>,
+    pub second_board: Option<Vec<Card>>, // the full board of the second run, when the board is run twice
+    pub burned_cards: Vec<Card>, // the card burned before each deal of the board, in order
     pub index_to_act: Option<usize>,
+    pub num_players_dealt: usize, // how many players were dealt into the hand
+    pub num_blinds_posted: usize, // how many of the blinds have been posted so far this hand
+    pub num_straddles: usize, // how many straddles have been posted on top of the blinds
+    pub straddling_closed: bool, // once anyone acts preflop without straddling, nobody else can straddle
+    pub misdeal: Option<String>, // the reason the hand had to be cancelled, if it was
+    pub vpip_ids: HashSet<Uuid>, // players who voluntarily put money in preflop
+    pub pfr_ids: HashSet<Uuid>, // players who raised preflop
+    pub action_log: Vec<(usize, PlayerAction, Street)>, // every action of the hand in order, with the seat and street
+    pub starting_stacks: [u32; MAX_SEATS], // what each player in the hand had before the antes and blinds
 }
 
 impl GameHand {
     pub fn default() -> Self {
         GameHand {
+            variant: GameVariant::Holdem,
             street: Street::Preflop,
             pot_manager: PotManager::new(),
             street_contributions: HashMap::new(),
+            ante_contributions: [0; MAX_SEATS],
 	    current_bet: 0,
+	    last_full_raise_size: 0,
+	    last_full_bet: 0,
+	    acted_since_full_raise: HashSet::new(),
+	    num_raises: 0,
             flop: None,
             turn: None,
             river: None,
+	    second_board: None,
+	    burned_cards: vec![],
 	    index_to_act: None,
+	    num_players_dealt: 0,
+	    num_blinds_posted: 0,
+	    num_straddles: 0,
+	    straddling_closed: false,
+	    misdeal: None,
+	    vpip_ids: HashSet::new(),
+	    pfr_ids: HashSet::new(),
+	    action_log: vec![],
+	    starting_stacks: [0; MAX_SEATS],
         }
     }
 
@@ -65,80 +356,544 @@ impl GameHand {
 	Street::ShowDown == self.street
     }
 
+    /// reset the betting at the start of a street.
+    /// The smallest opening bet is the big blind
+    pub fn new_betting_round(&mut self, big_blind: u32) {
+	self.current_bet = 0;
+	self.last_full_raise_size = big_blind;
+	self.last_full_bet = 0;
+	self.acted_since_full_raise.clear();
+	self.num_raises = 0;
+    }
+
+    /// the smallest amount that the street bet can be raised to, given the convention being used.
+    /// (A player going all-in for less is always allowed, but this is up to the caller to check)
+    pub fn min_raise_to(&self, rule: MinRaiseRule) -> u32 {
+	let increment = match rule {
+	    MinRaiseRule::LastRaiseIncrement => self.last_full_raise_size,
+	    MinRaiseRule::LastFullBet => cmp::max(self.last_full_raise_size, self.last_full_bet),
+	};
+	self.current_bet + increment
+    }
+
+    /// the street bet goes up to new_bet.
+    /// If this is a full raise, then it also sets the size that the next raise is based on,
+    /// and reopens the betting for everyone who already acted.
+    /// Returns a bool indicating whether it was a full raise (i.e. not a short all-in)
+    pub fn raise_to(&mut self, new_bet: u32, rule: MinRaiseRule) -> bool {
+	let is_full_raise = new_bet >= self.min_raise_to(rule);
+	if is_full_raise {
+	    self.last_full_raise_size = new_bet - self.current_bet;
+	    self.last_full_bet = new_bet;
+	    self.acted_since_full_raise.clear();
+	}
+	self.current_bet = new_bet;
+	is_full_raise
+    }
+
+    /// the house takes its rake from the pots before they are paid out.
+    /// A hand that ended before the flop isn't raked ("no flop, no drop").
+    /// Returns how much was taken
+    pub fn take_rake(&mut self, percent: u32, cap: Option<u32>) -> u32 {
+	if self.street == Street::Preflop {
+	    return 0;
+	}
+	self.pot_manager.take_rake(percent, cap)
+    }
+
+    /// all the money in all the pots, including the bets on the current street
+    pub fn total_pot(&self) -> u32 {
+	self.pot_manager.iter().map(|pot| pot.get_money()).sum()
+    }
+
+    /// what the player in this seat has bet so far on the current street
+    pub fn street_contribution(&self, index: usize) -> u32 {
+	self.street_contributions.get(&self.street).map_or(0, |amounts| amounts[index])
+    }
+
+    /// how much more the player in this seat has to put in to match the street bet
+    pub fn amount_to_call(&self, index: usize) -> u32 {
+	self.current_bet.saturating_sub(self.street_contribution(index))
+    }
+
+    /// everything the player in this seat has put in this hand, across every street and including antes
+    pub fn invested_this_hand(&self, index: usize) -> u32 {
+	self.street_contributions.values().map(|amounts| amounts[index]).sum::<u32>() + self.ante_contributions[index]
+    }
+
+    /// in pot limit, the most the street bet can go up to is the current bet
+    /// plus the size of the pot after the player calls
+    pub fn max_pot_limit_raise_to(&self, player_cumulative: u32) -> u32 {
+	let to_call = self.current_bet.saturating_sub(player_cumulative);
+	self.current_bet + self.total_pot() + to_call
+    }
+
+    /// the hand is being cancelled, so give every player back everything they put in.
+    /// (A player who already left the table can't be refunded)
+    pub fn refund_contributions(&mut self, players: &mut [Option<Player>]) {
+	for (i, player_spot) in players.iter_mut().enumerate() {
+	    if let Some(player) = player_spot {
+		player.pay(self.invested_this_hand(i));
+	    }
+	}
+	self.street_contributions.clear();
+	self.ante_contributions = [0; MAX_SEATS];
+	self.pot_manager = PotManager::new();
+    }
+
+    /// an ante goes into the pot without counting towards the preflop bet
+    pub fn contribute_ante(&mut self, index: usize, player_id: Uuid, amount: u32, all_in: bool) {
+	self.ante_contributions[index] += amount;
+	self.pot_manager.contribute(player_id, amount, all_in);
+    }
+
+    /// a big blind ante is dead money for the whole table, so it goes into the main pot
+    /// without counting towards what the other players need to match
+    pub fn contribute_dead_ante(&mut self, index: usize, player_id: Uuid, amount: u32) {
+	self.ante_contributions[index] += amount;
+	self.pot_manager.add_dead_money(player_id, amount);
+    }
+
     pub fn contribute(&mut self, index: usize, player_id: Uuid, amount: u32, all_in: bool) {
 	let current_contributions = self.street_contributions.get_mut(&self.street).unwrap();	
         current_contributions[index] += amount;	
         self.pot_manager.contribute(player_id, amount, all_in);	    
     }
 	
+    /// at showdown, if the best losing hand is at least as good as the threshold ranking,
+    /// then that is a bad beat. Returns a bad_beat message with both the losing and winning hands
+    pub fn find_bad_beat(
+	&self,
+	players: &[Option<Player>],
+	player_ids_to_configs: &HashMap::<Uuid, PlayerConfig>,
+	threshold: HandRanking,
+    ) -> Option<json::JsonValue> {
+	if !self.is_showdown() {
+	    return None;
+	}
+	let hand_results: Vec<(usize, HandResult)> = players
+	    .iter()
+	    .enumerate()
+	    .filter_map(|(i, spot)| spot.as_ref().map(|player| (i, player)))
+	    .filter(|(_, player)| player_ids_to_configs.contains_key(&player.id)) // make sure still in the game
+	    .filter_map(|(i, player)| player.determine_best_hand(self).map(|result| (i, result)))
+	    .collect();
+	let (winning_idx, winning_hand) = hand_results.iter().max_by(|a, b| a.1.cmp(&b.1))?;
+	let (losing_idx, losing_hand) = hand_results
+	    .iter()
+	    .filter(|(_, result)| result < winning_hand && result.hand_ranking() >= threshold)
+	    .max_by(|a, b| a.1.cmp(&b.1))?;
+	log::info!("bad beat! {} lost to {}", losing_hand, winning_hand);
+	Some(json::object! {
+	    msg_type: "bad_beat".to_owned(),
+	    losing_index: *losing_idx,
+	    losing_hand: losing_hand.hand_ranking_string(),
+	    losing_constituent_cards: losing_hand.constituent_cards_string(),
+	    winning_index: *winning_idx,
+	    winning_hand: winning_hand.hand_ranking_string(),
+	    winning_constituent_cards: winning_hand.constituent_cards_string(),
+	})
+    }
+
+    /// the action log as json, e.g. for the finish_hand message or a hand history
+    pub fn action_log_json(&self) -> json::JsonValue {
+	GameHand::actions_json(&self.action_log)
+    }
+
+    /// the last few actions of the hand as json, e.g. to catch up a player who joins mid-hand
+    pub fn recent_actions_json(&self, count: usize) -> json::JsonValue {
+	let start = self.action_log.len().saturating_sub(count);
+	GameHand::actions_json(&self.action_log[start..])
+    }
+
+    fn actions_json(actions: &[(usize, PlayerAction, Street)]) -> json::JsonValue {
+	actions
+	    .iter()
+	    .map(|(seat, action, street)| object! {
+		seat: *seat,
+		action: action.to_string(),
+		street: street.to_string(),
+	    })
+	    .collect::<Vec<_>>()
+	    .into()
+    }
+
+    /// the hand written out in the text format of the online poker rooms, which hand history
+    /// trackers can import. This is meant for a finished hand: the pots have been paid out,
+    /// but the players still have their hole cards.
+    /// The table's game state gives the stakes and the seat names, and the settlements
+    /// say who collected what, and whose cards were shown (so a mucked hand stays hidden)
+    pub fn to_hand_history(&self, state: &GameStateDto, players: &[Option<Player>], settlements: &[Settlement]) -> String {
+	let seat_name = |seat: usize| match &state.players[seat] {
+	    Some(info) => info.player_name.clone(),
+	    None => format!("Seat {}", seat + 1),
+	};
+	let game = match self.variant {
+	    GameVariant::Holdem => "Hold'em",
+	    GameVariant::Omaha => "Omaha",
+	    GameVariant::SevenStud => "7 Card Stud",
+	    GameVariant::ShortDeck => "Short Deck Hold'em",
+	};
+	let structure = match state.betting_structure.as_str() {
+	    "pot_limit" => "Pot Limit",
+	    "fixed_limit" => "Limit",
+	    _ => "No Limit",
+	};
+	let mut lines = vec![
+	    format!("Hand #{}: {} {} ({}/{})", state.hand_num, game, structure, state.small_blind, state.big_blind),
+	    format!("Table '{}' {}-max Seat #{} is the button", state.name, state.max_players, state.button_idx + 1),
+	];
+	let seats: Vec<usize> = (0..MAX_SEATS).filter(|seat| self.starting_stacks[*seat] > 0).collect();
+	for seat in seats.iter() {
+	    lines.push(format!("Seat {}: {} ({} in chips)", seat + 1, seat_name(*seat), self.starting_stacks[*seat]));
+	}
+
+	// replay the action log to put the amounts on the calls, and to know who went all-in
+	let mut stacks = self.starting_stacks;
+	let mut contributions = [0; MAX_SEATS];
+	let mut current_bet = 0;
+	let mut street = Street::Preflop;
+	let mut forced_bets_done = false;
+	for (seat, action, action_street) in self.action_log.iter() {
+	    if *action_street != street {
+		street = *action_street;
+		contributions = [0; MAX_SEATS];
+		current_bet = 0;
+	    }
+	    let forced = matches!(
+		action,
+		PlayerAction::PostAnte(_) | PlayerAction::PostSmallBlind(_) | PlayerAction::PostBigBlind(_) | PlayerAction::PostStraddle(_)
+	    );
+	    if !forced && !forced_bets_done {
+		forced_bets_done = true;
+		lines.push(self.history_street_header(Street::Preflop));
+	    }
+	    if street != Street::Preflop && !lines.contains(&self.history_street_header(street)) {
+		lines.push(self.history_street_header(street));
+	    }
+	    let (text, paid) = match *action {
+		PlayerAction::PostAnte(amount) => (format!("posts the ante {}", amount), 0),
+		PlayerAction::PostSmallBlind(amount) => {
+		    current_bet = state.small_blind;
+		    (format!("posts small blind {}", amount), amount)
+		}
+		PlayerAction::PostBigBlind(amount) => {
+		    current_bet = state.big_blind;
+		    (format!("posts big blind {}", amount), amount)
+		}
+		PlayerAction::PostBringIn(amount) => {
+		    current_bet = state.small_blind;
+		    (format!("brings in for {}", amount), amount)
+		}
+		PlayerAction::PostStraddle(amount) => {
+		    current_bet = amount;
+		    (format!("posts straddle {}", amount), amount)
+		}
+		PlayerAction::Fold => ("folds".to_owned(), 0),
+		PlayerAction::SitOut => ("sits out".to_owned(), 0),
+		PlayerAction::Check => ("checks".to_owned(), 0),
+		PlayerAction::Call => {
+		    let amount = cmp::min(current_bet.saturating_sub(contributions[*seat]), stacks[*seat]);
+		    (format!("calls {}", amount), amount)
+		}
+		PlayerAction::Bet(new_bet) => {
+		    current_bet = new_bet;
+		    let amount = new_bet.saturating_sub(contributions[*seat]);
+		    (format!("bets {}", amount), amount)
+		}
+		PlayerAction::Raise(amount) | PlayerAction::RaiseBy(amount) => {
+		    current_bet += amount;
+		    (format!("raises {} to {}", amount, current_bet), current_bet.saturating_sub(contributions[*seat]))
+		}
+		PlayerAction::RaiseTo(new_bet) => {
+		    let amount = new_bet.saturating_sub(current_bet);
+		    current_bet = new_bet;
+		    (format!("raises {} to {}", amount, new_bet), new_bet.saturating_sub(contributions[*seat]))
+		}
+	    };
+	    // the ante doesn't count towards the street bet, but it still comes out of the stack
+	    let ante = match *action {
+		PlayerAction::PostAnte(amount) => amount,
+		_ => 0,
+	    };
+	    contributions[*seat] += paid;
+	    stacks[*seat] = stacks[*seat].saturating_sub(paid + ante);
+	    let all_in = if stacks[*seat] == 0 && (paid + ante) > 0 { " and is all-in" } else { "" };
+	    lines.push(format!("{}: {}{}", seat_name(*seat), text, all_in));
+	}
+	if !forced_bets_done {
+	    // everyone was all-in from the blinds
+	    lines.push(self.history_street_header(Street::Preflop));
+	}
+	// when everyone is all-in, the rest of the board is dealt without any more action
+	for street in [Street::Flop, Street::Turn, Street::River, Street::Seventh] {
+	    let dealt = match street {
+		Street::Flop => self.flop.is_some(),
+		Street::Turn => self.turn.is_some(),
+		Street::River => self.river.is_some(),
+		_ => false,
+	    };
+	    let header = self.history_street_header(street);
+	    let reached = dealt || (!self.variant.has_board() && self.is_showdown() && street != Street::Preflop);
+	    if reached && !lines.contains(&header) {
+		lines.push(header);
+	    }
+	}
+
+	if self.is_showdown() {
+	    lines.push("*** SHOW DOWN ***".to_owned());
+	    let shown: HashSet<usize> = settlements
+		.iter()
+		.filter(|settlement| settlement.summary.has_key("hole_cards"))
+		.map(|settlement| settlement.seat)
+		.collect();
+	    for seat in seats.iter().filter(|seat| shown.contains(seat)) {
+		if let Some(player) = &players[*seat] {
+		    lines.push(format!("{}: shows [{}]", seat_name(*seat), Self::cards_text(&player.hole_cards)));
+		}
+	    }
+	}
+	let mut total_won = 0;
+	for seat in seats.iter() {
+	    let won: u32 = settlements
+		.iter()
+		.filter(|settlement| settlement.seat == *seat)
+		.map(|settlement| settlement.amount_won)
+		.sum();
+	    if won > 0 {
+		total_won += won;
+		lines.push(format!("{} collected {} from pot", seat_name(*seat), won));
+	    }
+	}
+	let total_pot: u32 = seats.iter().map(|seat| self.invested_this_hand(*seat)).sum();
+	lines.push("*** SUMMARY ***".to_owned());
+	lines.push(format!("Total pot {} | Rake {}", total_pot, total_pot.saturating_sub(total_won)));
+	if !self.board().is_empty() {
+	    lines.push(format!("Board [{}]", Self::cards_text(&self.board())));
+	}
+	if let Some(second_board) = &self.second_board {
+	    lines.push(format!("Second board [{}]", Self::cards_text(second_board)));
+	}
+	lines.join("\n")
+    }
+
+    /// the line that starts each street in a hand history. The board streets show
+    /// the cards that were already out, and then the new ones
+    fn history_street_header(&self, street: Street) -> String {
+	let flop = self.flop.as_deref().unwrap_or(&[]);
+	let turn: Vec<Card> = self.turn.into_iter().collect();
+	match (self.variant.has_board(), street) {
+	    (true, Street::Preflop) => "*** HOLE CARDS ***".to_owned(),
+	    (true, Street::Flop) => format!("*** FLOP *** [{}]", Self::cards_text(flop)),
+	    (true, Street::Turn) => format!("*** TURN *** [{}] [{}]", Self::cards_text(flop), Self::cards_text(&turn)),
+	    (true, Street::River) => {
+		let river: Vec<Card> = self.river.into_iter().collect();
+		let before = [flop, &turn].concat();
+		format!("*** RIVER *** [{}] [{}]", Self::cards_text(&before), Self::cards_text(&river))
+	    }
+	    (false, Street::Preflop) => "*** 3rd STREET ***".to_owned(),
+	    (false, Street::Flop) => "*** 4th STREET ***".to_owned(),
+	    (false, Street::Turn) => "*** 5th STREET ***".to_owned(),
+	    (false, Street::River) => "*** 6th STREET ***".to_owned(),
+	    (false, Street::Seventh) => "*** RIVER ***".to_owned(),
+	    (_, _) => "*** SHOW DOWN ***".to_owned(),
+	}
+    }
+
+    /// cards separated by spaces, e.g. "As Kd 2c"
+    fn cards_text(cards: &[Card]) -> String {
+	cards.iter().map(|card| card.to_string()).collect::<Vec<_>>().join(" ")
+    }
+
+    /// the flop, turn and river, as far as they have been dealt
+    pub fn board(&self) -> Vec<Card> {
+	self.flop
+	    .iter()
+	    .flatten()
+	    .chain(self.turn.iter())
+	    .chain(self.river.iter())
+	    .copied()
+	    .collect()
+    }
+
+    /// instead of running out the board, the players agreed to chop each pot according to
+    /// their equity in it. Any chip left over from rounding goes to the player with the most equity.
+    /// Returns the settlements, similar to divvy_pots
+    pub fn chop_pots(
+	&self,
+	players: &mut [Option<Player>],
+	player_ids_to_configs: &HashMap::<Uuid, PlayerConfig>,
+    ) -> Vec<Settlement> {
+	let board = self.board();
+        let mut settlements: Vec<Settlement> = vec![];
+	for (pot_idx, pot) in self.pot_manager.iter().enumerate().filter(|(_, pot)| pot.money > 0) {
+	    let elligible_idxs: Vec<usize> = players
+		.iter()
+		.enumerate()
+		.filter(|(_, spot)| spot.as_ref().is_some_and(|player| {
+		    player.is_active && pot.is_elligible(&player.id)
+		}))
+		.map(|(i, _)| i)
+		.collect();
+	    let hands: Vec<Vec<Card>> = elligible_idxs
+		.iter()
+		.map(|i| players[*i].as_ref().unwrap().hole_cards.clone())
+		.collect();
+	    let equities = equity::equities(self.variant, &hands, &board);
+	    let mut payouts: Vec<u32> = equities
+		.iter()
+		.map(|equity| (pot.get_money() as f64 * equity) as u32)
+		.collect();
+	    let leftover = pot.get_money() - payouts.iter().sum::<u32>();
+	    let (favorite, _) = equities
+		.iter()
+		.enumerate()
+		.max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+		.unwrap();
+	    payouts[favorite] += leftover;
+	    for ((i, equity), payout) in elligible_idxs.iter().zip(equities.iter()).zip(payouts.iter()) {
+		let player = players[*i].as_mut().unwrap();
+		let name: String = if let Some(config) = &player_ids_to_configs.get(&player.id) {
+		    config.name.as_ref().unwrap().clone()
+		} else {
+		    "Player who left".to_string()
+		};
+		log::debug!("chop paying out {:?} to {:?}, with equity = {}", payout, player.id, equity);
+		player.pay(*payout);
+		settlements.push(Settlement {
+		    player_id: player.id,
+		    seat: *i,
+		    amount_won: *payout,
+		    pot_index: pot_idx,
+		    winner: *payout > 0,
+		    run: None,
+		    summary: object! {
+			index: *i,
+			player_name: name,
+			is_showdown: false,
+			pot_index: pot_idx,
+			winner: *payout > 0,
+			payout: *payout,
+			equity: *equity,
+			chop: true,
+			hole_cards: player.hole_cards_string(),
+		    },
+		});
+	    }
+	}
+	settlements
+    }
+
     /// The hand is over, so give all money within each pot to the player who deserves it
     /// If we did not get to show down, then there is one active player who deserves all the money.
     /// Otherwise, we need to figure out who has the best hand.
     /// Each pot needs its own calculation
     /// Returns a list of settlements of the paid (or active at showdown) players.
-    /// A settlement shows the payout and hole cards of winning players, OR possibly the hole cards
+    /// The summary of a settlement shows the payout and hole cards of winning players, OR possibly the hole cards
     /// of losing players (if they had to show in the final reveal order of cards - starting with most aggression)
+    /// If the board was run twice, then each run is worth half of every pot,
+    /// and the settlements of each run are tagged with the run's index
     pub fn divvy_pots(
 	&self,
-	players: &mut [Option<Player>; 9],
+	players: &mut [Option<Player>],
 	player_ids_to_configs: &HashMap::<Uuid, PlayerConfig>,
 	starting_idx: usize
     )
-    -> Vec<json::JsonValue> {
+    -> Vec<Settlement> {
+	match &self.second_board {
+	    None => self.divvy_run(players, player_ids_to_configs, starting_idx, &self.board(), None),
+	    Some(second_board) => {
+		let mut settlements = self.divvy_run(players, player_ids_to_configs, starting_idx, &self.board(), Some(0));
+		settlements.extend(self.divvy_run(players, player_ids_to_configs, starting_idx, second_board, Some(1)));
+		settlements
+	    }
+	}
+    }
+
+    /// how much of a pot is paid out on the given run of the board.
+    /// When run twice, any odd chip goes to the first run
+    fn run_share(money: u32, run: Option<usize>) -> u32 {
+	match run {
+	    None => money,
+	    Some(0) => money - money / 2,
+	    Some(_) => money / 2,
+	}
+    }
+
+    /// divvy the pots (or their share for this run) according to the hands made on the given board
+    fn divvy_run(
+	&self,
+	players: &mut [Option<Player>],
+	player_ids_to_configs: &HashMap::<Uuid, PlayerConfig>,
+	starting_idx: usize,
+	board: &[Card],
+	run: Option<usize>,
+    )
+    -> Vec<Settlement> {
         let hand_results: HashMap<Uuid, Option<HandResult>> = players
             .iter()
             .flatten()
             .filter(|player| player_ids_to_configs.contains_key(&player.id)) // make sure still in the game
-            .map(|player| (player.id, player.determine_best_hand(self)))
+            .map(|player| (player.id, player.determine_best_hand_on(self, board)))
             .collect();
 	
         let is_showdown = self.is_showdown();
-        let mut settlements: Vec<json::JsonValue> = vec![];	
-        println!("hand results = {:?}", hand_results);
+        let mut settlements: Vec<Settlement> = vec![];	
+        log::debug!("hand results = {:?}", hand_results);
 	let showdown_starting_idx = GameHand::get_showdown_starting_idx(players, starting_idx);
 	for (pot_idx, pot) in self.pot_manager.iter().enumerate().filter(|(_, pot)| pot.money > 0) {
 	    // for each pot, we determine who should get paid out
 	    // a player can only get paid for a pot that they contributed to
 	    // so each pot has its own best_hand calculation
-            let (best_ids, best_hand, amount, showing_ids, elligible_ids) = if is_showdown {
+            let (best_ids, best_hand, amount, odd_chip_ids, showing_ids, elligible_ids) = if is_showdown {
 		// if we made it to show down, there are multiple players left, so we need to see who
 		// has the best hand.
-		println!("Multiple active players made it to showdown!");
-		println!("Looking at pot {:?}", pot);
+		log::debug!("Multiple active players made it to showdown!");
+		log::debug!("Looking at pot {:?}", pot);
 		let mut best_ids = HashSet::<Uuid>::new(); // who is a winner of the pot
 		let mut showing_ids = HashSet::<Uuid>::new(); // who needs to show their cards
 		let mut elligible_ids = HashSet::<Uuid>::new(); // who was even in the pot (and should get a settlement)
 		let mut best_hand: Option<&HandResult> = None;
-		for i in (showdown_starting_idx..9).chain(0..showdown_starting_idx) {
+		for i in (showdown_starting_idx..players.len()).chain(0..showdown_starting_idx) {
 		    if let Some(player) = &mut players[i]  {
-			if pot.is_elligible(&player.id) && hand_results.get(&player.id).is_some() {
+			if pot.is_elligible(&player.id) && hand_results.contains_key(&player.id) {
 			    let current_opt = hand_results.get(&player.id).unwrap();
 			    if current_opt.is_none() {
 				continue;
 			    }
 			    elligible_ids.insert(player.id); // indicates we looked at them even for this pot
 			    let current_result = current_opt.as_ref().unwrap();
-			    if best_hand.is_none() || current_result > best_hand.unwrap() {
-				println!("new best hand for id {:?}", player.id);
-				best_hand = Some(&current_result);
+			    if best_hand.is_none_or(|best| current_result > best) {
+				log::debug!("new best hand for id {:?}", player.id);
+				best_hand = Some(current_result);
 				best_ids.clear();
 				best_ids.insert(player.id); // only one best hand now
 				showing_ids.insert(player.id); // they need to show since a potential winner at this point
-			    } else if current_result == best_hand.unwrap() {
-				println!("equally good hand for id {:?}", player.id);
+			    } else if best_hand == Some(current_result) {
+				log::debug!("equally good hand for id {:?}", player.id);
 				best_ids.insert(player.id); // another index that also has the best hand
 				showing_ids.insert(player.id); // they need to show since a potential winner at this point
 			    } else {
-				println!("hand worse for id {:?}", player.id);
+				log::debug!("hand worse for id {:?}", player.id);
 				continue;
 			    }
 			}
 		    }
 		}
 		// divy the pot to all the winners
-		let num_winners = best_ids.len();
-		let amount = (pot.get_money() as f64 / num_winners as f64) as u32;
-		(best_ids, best_hand, amount, showing_ids, elligible_ids)
+		// any chips that can't be split evenly go one each to the winners closest to the left of the button
+		let num_winners = best_ids.len() as u32;
+		let pot_money = GameHand::run_share(pot.get_money(), run);
+		let amount = pot_money / num_winners;
+		let num_odd_chips = (pot_money % num_winners) as usize;
+		let odd_chip_ids: HashSet<Uuid> = (starting_idx..players.len())
+		    .chain(0..starting_idx)
+		    .filter_map(|i| players[i].as_ref())
+		    .filter(|player| best_ids.contains(&player.id))
+		    .take(num_odd_chips)
+		    .map(|player| player.id)
+		    .collect();
+		(best_ids, best_hand, amount, odd_chip_ids, showing_ids, elligible_ids)
             } else {
 		// the hand ended before Showdown, so we simple find the one active player remaining
 		let best_ids:  HashSet::<Uuid> = players
@@ -152,34 +907,43 @@ impl GameHand {
 		    let amount = self.pot_manager.iter().next().unwrap().get_money();
 		let showing_ids = best_ids.clone();
 		let elligible_ids = best_ids.clone();		
-		(best_ids, best_hand, amount, showing_ids, elligible_ids)
+		(best_ids, best_hand, amount, HashSet::new(), showing_ids, elligible_ids)
             };
 	    self.settle_players(&mut settlements, players, player_ids_to_configs, &hand_results, pot_idx,
-				     best_ids, best_hand, amount, showing_ids, elligible_ids, showdown_starting_idx);
+				     best_ids, best_hand, amount, odd_chip_ids, showing_ids, elligible_ids, showdown_starting_idx);
 	    
 	}
+	if let Some(run) = run {
+	    for settlement in settlements.iter_mut() {
+		settlement.run = Some(run);
+		settlement.summary["run"] = run.into();
+	    }
+	}
 	settlements
     }
 
     /// iterate through the players, and any with an id in best_ids gets their money increased by amount.
+    /// The winners in odd_chip_ids get one more chip each, for a pot that didn't split evenly.
     /// Moreover, construct a json settlement message for each one of these payouts,
     /// and add it to the given settlements vec (if they need to show)
+    #[allow(clippy::too_many_arguments)]
     fn settle_players(
 	&self, 
-	settlements: &mut Vec<json::JsonValue>,
-	players: &mut [Option<Player>; 9],
+	settlements: &mut Vec<Settlement>,
+	players: &mut [Option<Player>],
 	player_ids_to_configs: &HashMap::<Uuid, PlayerConfig>,
 	hand_results: &HashMap<Uuid, Option<HandResult>>,	
 	pot_idx: usize,
         best_ids: HashSet<Uuid>,
         best_hand: Option<&HandResult>,
         amount: u32,
+	odd_chip_ids: HashSet<Uuid>,
 	showing_ids: HashSet<Uuid>,
 	elligible_ids: HashSet<Uuid>,	
 	showdown_starting_idx: usize,
     ) {
         let is_showdown = self.is_showdown();
-        for i in (showdown_starting_idx..9).chain(0..showdown_starting_idx) {
+        for i in (showdown_starting_idx..players.len()).chain(0..showdown_starting_idx) {
 	    if let Some(player) = &mut players[i]  {
 		if !elligible_ids.contains(&player.id) {
                     continue;
@@ -199,28 +963,48 @@ impl GameHand {
 		    pot_index: pot_idx,
 		};
 		
+		let mut amount_won = 0;
 		if best_ids.contains(&player.id) {
 		    message["winner"] = true.into();		    
-		    message["payout"] = amount.into();
-		    println!(
+		    let payout = if odd_chip_ids.contains(&player.id) {
+			amount + 1
+		    } else {
+			amount
+		    };
+		    message["payout"] = payout.into();
+		    log::debug!(
 			"paying out {:?} to {:?}, with hand result = {:?}",
-			amount, player.id, best_hand
+			payout, player.id, best_hand
 		    );
-		    player.pay(amount);		    
+		    player.pay(payout);		    
+		    amount_won = payout;
 		} else {
 		    message["winner"] = false.into();
 		}
-		if is_showdown && showing_ids.contains(&player.id) {		    
-		    let hole_string = format!("{}{}", player.hole_cards[0], player.hole_cards[1]);
-		    message["hole_cards"] = hole_string.into();
+		// only players who made it to showdown can reveal, a player who folded never shows.
+		// A player who asked to auto muck only shows if they have to, i.e. they win or are called
+		let must_show = best_ids.contains(&player.id) || i == showdown_starting_idx;
+		if is_showdown && player.is_active && showing_ids.contains(&player.id) && (must_show || !player.auto_muck) {		    
+		    message["hole_cards"] = player.hole_cards_string().into();
 		    if let Some(hand_result) = hand_results.get(&player.id).unwrap() {
 			message["hand_result"] = hand_result.hand_ranking_string().into();			
 			message["constituent_cards"] = hand_result.constituent_cards_string().into();
 			message["kickers"] = hand_result.kickers_string().into();
+			if best_ids.contains(&player.id) {
+			    message["winning_hand"] = hand_result.description().into();
+			}
 		    }
 		    
 		}
-		settlements.push(message);
+		settlements.push(Settlement {
+		    player_id: player.id,
+		    seat: i,
+		    amount_won,
+		    pot_index: pot_idx,
+		    winner: best_ids.contains(&player.id),
+		    run: None,
+		    summary: message,
+		});
             }
         }
     }
@@ -229,15 +1013,15 @@ impl GameHand {
     // if there is a last-aggressor, then it starts with them,
     // otherwise, it defaults to the street starting idx    
     fn get_showdown_starting_idx(
-	players: &mut [Option<Player>; 9],
+	players: &mut [Option<Player>],
 	starting_idx: usize,
     ) -> usize {
 	let mut showdown_starting_idx = starting_idx;
         for (i, player_spot) in players.iter_mut().enumerate() {
 	    if player_spot.is_some() {
                 let player = player_spot.as_mut().unwrap();
-		if let Some(PlayerAction::Bet(_)) = player.last_action {
-		    // if the player's last action was a bet, then they we the last aggressor,
+		if let Some(PlayerAction::Bet(_) | PlayerAction::Raise(_)) = player.last_action {
+		    // if the player's last action was a bet or a raise, then they we the last aggressor,
 		    // and hence has to show first
 		    showdown_starting_idx = i;
 		    break;
@@ -249,3 +1033,333 @@ impl GameHand {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::card::{Rank, Suit};
+
+    /// every player still in makes the same ace-high straight on this board, so they split the pot
+    const BROADWAY_BOARD: &str = "Ah Ks Qh Js Td";
+
+    /// seat a player for each pair of hole cards, in order from seat 0, all active with the same stack
+    fn seat_players(hole_cards: &[[Card; 2]], stack: u32) -> ([Option<Player>; 9], HashMap<Uuid, PlayerConfig>) {
+	let mut players: [Option<Player>; 9] = Default::default();
+	let mut player_ids_to_configs = HashMap::<Uuid, PlayerConfig>::new();
+	for (i, cards) in hole_cards.iter().enumerate() {
+	    let id = Uuid::new_v4();
+	    let mut player = Player::new(id, true, stack);
+	    player.is_active = true;
+	    player.hole_cards = cards.to_vec();
+	    players[i] = Some(player);
+	    player_ids_to_configs.insert(id, PlayerConfig::new(id, Some(format!("Player{}", i)), None));
+	}
+	(players, player_ids_to_configs)
+    }
+
+    /// deal the whole board, e.g. "2h 7s 9h 4s Qh", and go to the showdown
+    fn deal_board(gamehand: &mut GameHand, board: &str) {
+	let cards: Vec<Card> = board.split_whitespace().map(|card| card.parse().unwrap()).collect();
+	gamehand.flop = Some(cards[..3].to_vec());
+	gamehand.turn = Some(cards[3]);
+	gamehand.river = Some(cards[4]);
+	gamehand.street = Street::ShowDown;
+    }
+
+    /// after a bet of 40 and a raise to 100, the two conventions disagree on the next minimum raise
+    #[test]
+    fn min_raise_conventions() {
+	let mut gamehand = GameHand::default();
+	gamehand.new_betting_round(8);
+	for rule in [MinRaiseRule::LastRaiseIncrement, MinRaiseRule::LastFullBet].iter() {
+	    // the smallest opening bet is the big blind
+	    assert_eq!(gamehand.min_raise_to(*rule), 8);
+	}
+
+	assert!(gamehand.raise_to(40, MinRaiseRule::LastRaiseIncrement));
+	assert_eq!(gamehand.min_raise_to(MinRaiseRule::LastRaiseIncrement), 80);
+	assert_eq!(gamehand.min_raise_to(MinRaiseRule::LastFullBet), 80);
+
+	assert!(gamehand.raise_to(100, MinRaiseRule::LastRaiseIncrement));
+	assert_eq!(gamehand.min_raise_to(MinRaiseRule::LastRaiseIncrement), 160);
+	assert_eq!(gamehand.min_raise_to(MinRaiseRule::LastFullBet), 200);
+    }
+
+    /// an all-in for less than a full raise doesn't reopen the betting
+    /// to the player who already acted, but a full raise does
+    #[test]
+    fn short_all_in_does_not_reopen_betting() {
+	let mut gamehand = GameHand::default();
+	gamehand.new_betting_round(8);
+	let opener = Uuid::new_v4();
+	assert!(gamehand.raise_to(40, MinRaiseRule::LastRaiseIncrement));
+	gamehand.acted_since_full_raise.insert(opener);
+
+	// all-in for 60 is short of the minimum raise to 80
+	assert!(!gamehand.raise_to(60, MinRaiseRule::LastRaiseIncrement));
+	assert!(gamehand.acted_since_full_raise.contains(&opener));
+	// the next raise is still measured from the last full raise
+	assert_eq!(gamehand.min_raise_to(MinRaiseRule::LastRaiseIncrement), 100);
+
+	assert!(gamehand.raise_to(100, MinRaiseRule::LastRaiseIncrement));
+	assert!(gamehand.acted_since_full_raise.is_empty());
+    }
+
+    /// after a bet, a full raise, and then a short all-in, the original bettor faced a full raise
+    /// so can still re-raise, while the raiser can only call or fold
+    #[test]
+    fn short_all_in_after_raise() {
+	let mut gamehand = GameHand::default();
+	gamehand.new_betting_round(8);
+	let bettor = Uuid::new_v4();
+	let raiser = Uuid::new_v4();
+	let all_in = Uuid::new_v4();
+	for (id, new_bet) in [(bettor, 10), (raiser, 30), (all_in, 40)].iter() {
+	    gamehand.raise_to(*new_bet, MinRaiseRule::LastRaiseIncrement);
+	    gamehand.acted_since_full_raise.insert(*id);
+	}
+	assert!(!gamehand.acted_since_full_raise.contains(&bettor));
+	assert!(gamehand.acted_since_full_raise.contains(&raiser));
+    }
+
+    /// the seat 0 big blind and seat 1 button see a flop after a raise to 24, with a 1 ante each.
+    /// On the flop the button bets 30, so the big blind owes all of it while the button owes nothing
+    #[test]
+    fn bet_helpers_across_streets() {
+	let mut gamehand = GameHand::default();
+	let ids = [Uuid::new_v4(), Uuid::new_v4()];
+	gamehand.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+	gamehand.new_betting_round(8);
+	for (i, id) in ids.iter().enumerate() {
+	    gamehand.contribute_ante(i, *id, 1, false);
+	}
+	gamehand.contribute(1, ids[1], 4, false);
+	gamehand.contribute(0, ids[0], 8, false);
+	gamehand.raise_to(8, MinRaiseRule::LastRaiseIncrement);
+	gamehand.contribute(1, ids[1], 20, false);
+	gamehand.raise_to(24, MinRaiseRule::LastRaiseIncrement);
+	assert_eq!(gamehand.amount_to_call(0), 16);
+	assert_eq!(gamehand.amount_to_call(1), 0);
+	gamehand.contribute(0, ids[0], 16, false);
+	assert_eq!(gamehand.total_pot(), 2 + 48);
+
+	gamehand.street = Street::Flop;
+	gamehand.street_contributions.insert(Street::Flop, [0; MAX_SEATS]);
+	gamehand.new_betting_round(8);
+	assert_eq!(gamehand.amount_to_call(0), 0);
+	gamehand.contribute(1, ids[1], 30, false);
+	gamehand.raise_to(30, MinRaiseRule::LastRaiseIncrement);
+	assert_eq!(gamehand.amount_to_call(0), 30);
+	assert_eq!(gamehand.amount_to_call(1), 0);
+	assert_eq!(gamehand.street_contribution(1), 30);
+	assert_eq!(gamehand.invested_this_hand(0), 1 + 24);
+	assert_eq!(gamehand.invested_this_hand(1), 1 + 24 + 30);
+	assert_eq!(gamehand.total_pot(), 2 + 48 + 30);
+	// an empty seat has put nothing in
+	assert_eq!(gamehand.invested_this_hand(5), 0);
+    }
+
+    /// three players see the flop, and one folds on the turn.
+    /// At showdown, the folder gets no settlement and their cards are never revealed,
+    /// while both players who made it to showdown show their cards
+    #[test]
+    fn folded_player_not_revealed() {
+	let mut gamehand = GameHand::default();
+	let hole_cards = [
+	    [Card { rank: Rank::Ace, suit: Suit::Club }, Card { rank: Rank::Ace, suit: Suit::Diamond }],
+	    [Card { rank: Rank::King, suit: Suit::Club }, Card { rank: Rank::King, suit: Suit::Diamond }],
+	    [Card { rank: Rank::Queen, suit: Suit::Club }, Card { rank: Rank::Queen, suit: Suit::Diamond }],
+	];
+	let (mut players, player_ids_to_configs) = seat_players(&hole_cards, 1000);
+
+	// everyone puts in 10 on the preflop and flop, then the aces fold on the turn
+	for street in [Street::Preflop, Street::Flop, Street::Turn].iter() {
+	    gamehand.street = *street;
+	    gamehand.street_contributions.insert(*street, [0; 9]);
+	    for (i, player) in players.iter_mut().flatten().enumerate() {
+		if *street == Street::Turn && i == 0 {
+		    player.deactivate();
+		    continue;
+		}
+		player.money -= 10;
+		gamehand.contribute(i, player.id, 10, false);
+	    }
+	}
+	deal_board(&mut gamehand, "2h 7s 9h 4s Qh");
+
+	// the kings are first to show, then the queens (with a set) beat them
+	let settlements = gamehand.divvy_pots(&mut players, &player_ids_to_configs, 1);
+	assert_eq!(settlements.len(), 2);
+	assert!(settlements.iter().all(|settlement| settlement.seat != 0));
+	assert!(settlements.iter().all(|settlement| settlement.summary["hole_cards"].is_string()));
+	let summaries: Vec<json::JsonValue> = settlements.iter().map(|settlement| settlement.summary.clone()).collect();
+	assert!(!json::stringify(summaries).contains(&hole_cards[0][0].to_string()));
+	let winner = settlements.iter().find(|settlement| settlement.winner).unwrap();
+	assert_eq!(winner.seat, 2);
+	assert_eq!(winner.amount_won, 80);
+	assert_eq!(winner.summary["payout"], 80);
+    }
+
+    /// three players split a pot of 100 with the same straight on the board.
+    /// The odd chip goes to the first of them left of the button, so no chips are lost
+    #[test]
+    fn odd_chip_split() {
+	let mut gamehand = GameHand::default();
+	let hole_cards = [
+	    [Card { rank: Rank::Two, suit: Suit::Club }, Card { rank: Rank::Three, suit: Suit::Diamond }],
+	    [Card { rank: Rank::Two, suit: Suit::Diamond }, Card { rank: Rank::Four, suit: Suit::Club }],
+	    [Card { rank: Rank::Two, suit: Suit::Heart }, Card { rank: Rank::Five, suit: Suit::Club }],
+	    [Card { rank: Rank::Two, suit: Suit::Spade }, Card { rank: Rank::Six, suit: Suit::Club }],
+	];
+	let (mut players, player_ids_to_configs) = seat_players(&hole_cards, 1000);
+
+	// the first player puts in 1 and folds, and the other three put in 33 each
+	gamehand.street_contributions.insert(Street::Preflop, [0; 9]);
+	for (i, player) in players.iter_mut().flatten().enumerate() {
+	    let amount = if i == 0 { 1 } else { 33 };
+	    player.money -= amount;
+	    gamehand.contribute(i, player.id, amount, false);
+	    if i == 0 {
+		player.deactivate();
+	    }
+	}
+	deal_board(&mut gamehand, BROADWAY_BOARD);
+
+	let settlements = gamehand.divvy_pots(&mut players, &player_ids_to_configs, 1);
+	let payouts: Vec<u32> = settlements
+	    .iter()
+	    .filter(|settlement| settlement.winner)
+	    .map(|settlement| settlement.amount_won)
+	    .collect();
+	assert_eq!(payouts, vec![34, 33, 33]);
+	assert_eq!(players[1].as_ref().unwrap().money, 1001);
+	assert_eq!(players[2].as_ref().unwrap().money, 1000);
+	assert_eq!(players[3].as_ref().unwrap().money, 1000);
+    }
+
+    /// two players tie for a pot of 21. The button was the last aggressor, so shows first,
+    /// but the odd chip goes to the small blind, the first winner left of the button
+    #[test]
+    fn odd_chip_left_of_button() {
+	let mut gamehand = GameHand::default();
+	let hole_cards = [
+	    [Card { rank: Rank::Two, suit: Suit::Club }, Card { rank: Rank::Three, suit: Suit::Diamond }],
+	    [Card { rank: Rank::Two, suit: Suit::Diamond }, Card { rank: Rank::Four, suit: Suit::Club }],
+	    [Card { rank: Rank::Two, suit: Suit::Heart }, Card { rank: Rank::Five, suit: Suit::Club }],
+	];
+	let (mut players, player_ids_to_configs) = seat_players(&hole_cards, 1000);
+
+	// the button bets 10 and the small blind calls, while the big blind folds after putting in 1
+	gamehand.street_contributions.insert(Street::Preflop, [0; 9]);
+	for (i, player) in players.iter_mut().flatten().enumerate() {
+	    let amount = if i == 2 { 1 } else { 10 };
+	    player.money -= amount;
+	    gamehand.contribute(i, player.id, amount, false);
+	    if i == 2 {
+		player.deactivate();
+	    }
+	}
+	players[0].as_mut().unwrap().last_action = Some(PlayerAction::Bet(10));
+	deal_board(&mut gamehand, BROADWAY_BOARD);
+
+	// the button is at index 0, so the small blind at index 1 is first left of the button
+	let settlements = gamehand.divvy_pots(&mut players, &player_ids_to_configs, 1);
+	assert_eq!(settlements[0].seat, 0);
+	assert_eq!(settlements[0].amount_won, 10);
+	assert_eq!(settlements[1].seat, 1);
+	assert_eq!(settlements[1].amount_won, 11);
+	let total: u32 = players.iter().flatten().map(|player| player.money).sum();
+	assert_eq!(total, 3000);
+    }
+
+    /// the pot limit max counts the pot, the amount to call, and the call itself
+    #[test]
+    fn pot_limit_max() {
+	let mut gamehand = GameHand::default();
+	gamehand.street_contributions.insert(Street::Preflop, [0; 9]);
+	let ids: Vec<Uuid> = (0..2).map(|_| Uuid::new_v4()).collect();
+	// blinds of 4/8
+	gamehand.contribute(0, ids[0], 4, false);
+	gamehand.contribute(1, ids[1], 8, false);
+	gamehand.current_bet = 8;
+	assert_eq!(gamehand.total_pot(), 12);
+	// the small blind calls 4 (the pot is then 16), and can raise by 16, to 24
+	assert_eq!(gamehand.max_pot_limit_raise_to(4), 24);
+	// the big blind has nothing to call, so can raise by the pot of 12, to 20
+	assert_eq!(gamehand.max_pot_limit_raise_to(8), 20);
+    }
+
+    /// chips are whole numbers, so splitting pots never drifts. Over many hands of odd-sized
+    /// split pots (with a folder's dead money), the total at the table stays exactly the same
+    #[test]
+    fn split_pots_conserve_chips() {
+	let hole_cards = [
+	    [Card { rank: Rank::Two, suit: Suit::Club }, Card { rank: Rank::Three, suit: Suit::Diamond }],
+	    [Card { rank: Rank::Two, suit: Suit::Diamond }, Card { rank: Rank::Four, suit: Suit::Club }],
+	    [Card { rank: Rank::Two, suit: Suit::Heart }, Card { rank: Rank::Five, suit: Suit::Club }],
+	    [Card { rank: Rank::Two, suit: Suit::Spade }, Card { rank: Rank::Six, suit: Suit::Club }],
+	];
+	let (mut players, player_ids_to_configs) = seat_players(&hole_cards, 10000);
+
+	for hand in 0..1000 {
+	    let mut gamehand = GameHand::default();
+	    gamehand.street_contributions.insert(Street::Preflop, [0; 9]);
+	    // the first player puts in a chip or two and folds, the rest split the pot on the board
+	    for (i, player) in players.iter_mut().flatten().enumerate() {
+		player.is_active = true;
+		let amount = if i == 0 { 1 + hand % 2 } else { 1 + hand % 37 };
+		player.money -= amount;
+		gamehand.contribute(i, player.id, amount, false);
+		if i == 0 {
+		    player.deactivate();
+		}
+	    }
+	    deal_board(&mut gamehand, BROADWAY_BOARD);
+	    gamehand.divvy_pots(&mut players, &player_ids_to_configs, (hand % 4) as usize);
+
+	    let total: u32 = players.iter().flatten().map(|player| player.money).sum();
+	    assert_eq!(total, 40000);
+	}
+	// the folder paid 1500 in total, all of which went to the other three
+	assert_eq!(players[0].as_ref().unwrap().money, 8500);
+	let winnings: u32 = players[1..4].iter().flatten().map(|player| player.money - 10000).sum();
+	assert_eq!(winnings, 1500);
+    }
+
+    /// the queens are called and show first, then the kings show since they beat the queens.
+    /// But if the kings asked to auto muck, they lose without showing. The queens still have to show
+    #[test]
+    fn auto_muck_losing_caller() {
+	for auto_muck in [false, true] {
+	    let mut gamehand = GameHand::default();
+	    let hole_cards = [
+		[Card { rank: Rank::Ace, suit: Suit::Club }, Card { rank: Rank::Ace, suit: Suit::Diamond }],
+		[Card { rank: Rank::Queen, suit: Suit::Club }, Card { rank: Rank::Queen, suit: Suit::Diamond }],
+		[Card { rank: Rank::King, suit: Suit::Club }, Card { rank: Rank::King, suit: Suit::Diamond }],
+	    ];
+	    let (mut players, player_ids_to_configs) = seat_players(&hole_cards, 1000);
+	    gamehand.street_contributions.insert(Street::Preflop, [0; 9]);
+	    for (i, player) in players.iter_mut().flatten().enumerate() {
+		player.auto_muck = auto_muck && i != 0;
+		player.money -= 10;
+		gamehand.contribute(i, player.id, 10, false);
+	    }
+	    deal_board(&mut gamehand, "2h 7s 9h 4s Jd");
+
+	    let settlements = gamehand.divvy_pots(&mut players, &player_ids_to_configs, 1);
+	    let shown = |index: usize| {
+		settlements
+		    .iter()
+		    .find(|settlement| settlement.seat == index)
+		    .is_some_and(|settlement| settlement.summary["hole_cards"].is_string())
+	    };
+	    assert!(shown(0)); // the winner
+	    assert!(shown(1)); // called, so first to show
+	    assert_eq!(shown(2), !auto_muck);
+	    if auto_muck {
+		let summaries: Vec<json::JsonValue> = settlements.iter().map(|settlement| settlement.summary.clone()).collect();
+		assert!(!json::stringify(summaries).contains(&hole_cards[2][0].to_string()));
+	    }
+	}
+    }
+}
@@ -0,0 +1,103 @@
+///
+/// The typed schema of the game_state message that is sent to the front-end.
+/// Fields that are only sometimes sent are skipped when they are None (or false), so the
+/// wire format is the same as when the message was assembled by hand
+///
+use serde::{Deserialize, Serialize};
+
+/// the state of the table, and of the hand if one is being played.
+/// Each player gets their own copy, with their seat, hole cards and made hand filled in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameStateDto {
+    pub msg_type: String,
+    pub name: String,
+    pub max_players: u8,
+    pub small_blind: u32,
+    pub big_blind: u32,
+    pub ante_mode: String,
+    pub betting_structure: String,
+    pub variant: String,
+    pub buy_in: u32,
+    pub password: Option<String>,
+    pub button_idx: usize,
+    pub hand_num: u32,
+    pub game_suspended: bool,
+    pub clocks_frozen: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deck_remaining: Option<usize>, // only when the table shows the deck count, for training
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blind_level: Option<usize>, // only with a blind schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level_time_remaining: Option<Option<u64>>, // only with a blind schedule, and null at the last level
+    pub players: Vec<Option<PlayerInfoDto>>,
+    pub available_seats: Vec<usize>,
+
+    // the rest of the table fields are only sent during a hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub street: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_bet: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flop: Option<String>, // the three cards run together, e.g. "AsKdQh"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub river: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pots: Option<Vec<u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_pot: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_to_act: Option<usize>,
+
+    // personal to the recipient, and null for spectators
+    #[serde(default)]
+    pub your_index: Option<usize>,
+    #[serde(default)]
+    pub hole_cards: Option<String>, // both cards run together, e.g. "AsAd"
+    #[serde(default)]
+    pub made_hand: Option<String>,
+}
+
+/// a seated player, as everyone at the table sees them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInfoDto {
+    pub index: usize,
+    pub player_name: String,
+    pub money: u32,
+    pub is_active: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_sitting_out: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_all_in: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_action: Option<String>,
+
+    // what they have bet on each street so far, only during a hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preflop_cont: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flop_cont: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_cont: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub river_cont: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seventh_cont: Option<u32>, // only in Stud
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invested: Option<u32>,
+    // in Stud, the cards they were dealt face up, run together like the hole cards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up_cards: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl GameStateDto {
+    /// the state as a json value, for the places that still pass messages around that way
+    pub fn to_json(&self) -> json::JsonValue {
+        json::parse(&serde_json::to_string(self).unwrap()).unwrap()
+    }
+}
@@ -0,0 +1,203 @@
+//! Evaluating hands outside of the showdown, e.g. for telling a player "you have a flush".
+//! The ranking itself lives with the cards (see HandResult), this is the public way in
+
+use super::card::{Card, HandRanking, HandResult, HandRules, Rank};
+use super::game_hand::GameVariant;
+
+/// the best five card hand among the given cards (e.g. two hole cards and the board).
+/// HandResults compare by ranking and then kickers, so the better hand is the greater one.
+/// Returns None if there are fewer than 5 cards
+pub fn best_hand(cards: &[Card]) -> Option<HandResult> {
+    HandResult::best_hand(cards)
+}
+
+/// the best hand a player can make with their hole cards and the board, under the rules of the variant.
+/// In Hold'em any five of the cards play, while in Omaha exactly two hole cards and three board cards must.
+/// In Stud there is no board, and any five of the player's own seven cards play.
+/// Short-deck plays like Hold'em, but under the short-deck rankings.
+/// Returns None if there isn't a full hand yet (i.e. before the flop)
+pub fn best_hand_for(variant: GameVariant, hole_cards: &[Card], board: &[Card]) -> Option<HandResult> {
+    match variant {
+        GameVariant::Holdem | GameVariant::SevenStud => {
+            let cards: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+            best_hand(&cards)
+        }
+        GameVariant::ShortDeck => {
+            let cards: Vec<Card> = hole_cards.iter().chain(board.iter()).copied().collect();
+            HandResult::best_hand_with(&cards, HandRules::ShortDeck)
+        }
+        GameVariant::Omaha => {
+            let mut best: Option<HandResult> = None;
+            for hole in combinations(hole_cards, 2) {
+                for shared in combinations(board, 3) {
+                    let cards: Vec<Card> = hole.iter().chain(shared.iter()).copied().collect();
+                    let result = HandResult::analyze_hand(cards);
+                    match best {
+                        Some(ref best) if result <= *best => (),
+                        _ => best = Some(result),
+                    }
+                }
+            }
+            best
+        }
+    }
+}
+
+/// every way of choosing k of the cards, in order
+fn combinations(cards: &[Card], k: usize) -> Vec<Vec<Card>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    let mut combos = vec![];
+    for i in 0..cards.len() {
+        for mut rest in combinations(&cards[i + 1..], k - 1) {
+            rest.insert(0, cards[i]);
+            combos.push(rest);
+        }
+    }
+    combos
+}
+
+/// how strong a Stud player's up-cards look, which decides who acts first after third street.
+/// The most of a kind counts first, then the higher rank, and so on down. Straights and flushes
+/// showing don't count. The greater value is the stronger showing
+pub fn showing_strength(up_cards: &[Card]) -> Vec<(usize, Rank)> {
+    let mut groups: Vec<(usize, Rank)> = vec![];
+    for card in up_cards {
+        match groups.iter_mut().find(|(_, rank)| *rank == card.rank) {
+            Some(group) => group.0 += 1,
+            None => groups.push((1, card.rank)),
+        }
+    }
+    groups.sort_by(|a, b| b.cmp(a));
+    groups
+}
+
+/// what a player has made so far with their hole cards and the board,
+/// or None if there isn't a full hand yet (i.e. before the flop)
+pub fn made_hand(variant: GameVariant, hole_cards: &[Card], board: &[Card]) -> Option<HandRanking> {
+    best_hand_for(variant, hole_cards, board).map(|result| result.hand_ranking())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::card::{Rank, Suit};
+    use strum::IntoEnumIterator;
+
+    /// parse cards written like "AsKd9c"
+    fn cards(text: &str) -> Vec<Card> {
+        let chars: Vec<char> = text.chars().collect();
+        chars
+            .chunks(2)
+            .map(|pair| Card {
+                rank: Rank::iter().find(|rank| rank.to_string() == pair[0].to_string()).unwrap(),
+                suit: Suit::iter().find(|suit| suit.to_string() == pair[1].to_string()).unwrap(),
+            })
+            .collect()
+    }
+
+    fn ranking(text: &str) -> HandRanking {
+        best_hand(&cards(text)).unwrap().hand_ranking()
+    }
+
+    /// the best five of seven cards, for every ranking
+    #[test]
+    fn every_ranking() {
+        assert_eq!(ranking("As9d7c5h3s2dJc"), HandRanking::HighCard);
+        assert_eq!(ranking("AsAd7c5h3s2dJc"), HandRanking::Pair);
+        assert_eq!(ranking("AsAd7c7h3s2dJc"), HandRanking::TwoPair);
+        assert_eq!(ranking("AsAdAc7h3s2dJc"), HandRanking::ThreeOfAKind);
+        assert_eq!(ranking("9s8d7c6h5sAdAc"), HandRanking::Straight);
+        assert_eq!(ranking("As9s7s5s3sAdAc"), HandRanking::Flush);
+        assert_eq!(ranking("AsAdAc7h7s2d2c"), HandRanking::FullHouse);
+        assert_eq!(ranking("AsAdAcAh7s7d7c"), HandRanking::FourOfAKind);
+        assert_eq!(ranking("9s8s7s6s5sAdAc"), HandRanking::StraightFlush);
+        assert_eq!(ranking("AsKsQsJsTsAdAc"), HandRanking::RoyalFlush);
+        assert!(best_hand(&cards("AsAdAcAh")).is_none());
+    }
+
+    /// the wheel is a five-high straight: it beats trips, but loses to a six-high straight
+    #[test]
+    fn wheel() {
+        let wheel = best_hand(&cards("As2d3c4h5sKdKc")).unwrap();
+        assert_eq!(wheel.hand_ranking(), HandRanking::Straight);
+        assert!(wheel > best_hand(&cards("KsKdKc4h9s2dJc")).unwrap());
+        assert!(wheel < best_hand(&cards("6s2d3c4h5sKdKc")).unwrap());
+
+        let steel_wheel = best_hand(&cards("As2s3s4s5sKdKc")).unwrap();
+        assert_eq!(steel_wheel.hand_ranking(), HandRanking::StraightFlush);
+        assert!(steel_wheel < best_hand(&cards("6s2s3s4s5sKdKc")).unwrap());
+    }
+
+    #[test]
+    fn kickers() {
+        // the same pair, decided by the third kicker
+        assert!(best_hand(&cards("AsAdKc9h7s3d2c")).unwrap() > best_hand(&cards("AhAcKd9s6h3c2d")).unwrap());
+        // the same two pair, decided by the kicker
+        assert!(best_hand(&cards("KsKd8c8h2sQd3c")).unwrap() > best_hand(&cards("KhKc8d8s2dJh3d")).unwrap());
+        // only five cards play, so the sixth and seventh cards don't break the tie
+        assert_eq!(best_hand(&cards("AsAdKcQhJs4d3c")).unwrap(), best_hand(&cards("AhAcKdQsJh5c2d")).unwrap());
+        // the flushes are decided by the last card
+        assert!(best_hand(&cards("AsJs9s7s4sKd2c")).unwrap() > best_hand(&cards("AhJh9h7h3hKc2d")).unwrap());
+        // with three pairs, only the top two count, and the best card left over is the kicker
+        let three_pairs = best_hand(&cards("KsKd8c8h6s6dAc")).unwrap();
+        assert_eq!(three_pairs.hand_ranking(), HandRanking::TwoPair);
+        assert!(three_pairs > best_hand(&cards("KhKc8d8s6h6cQd")).unwrap());
+        // a full house is decided by the trips before the pair
+        assert!(best_hand(&cards("3s3d3c2h2sAdKc")).unwrap() > best_hand(&cards("2d2c2sAhAsKdQc")).unwrap());
+    }
+
+    /// before the flop there is no hand made yet
+    #[test]
+    fn made_hand_needs_a_board() {
+        let hole_cards = cards("AsKs");
+        assert_eq!(made_hand(GameVariant::Holdem, &hole_cards, &[]), None);
+        assert_eq!(made_hand(GameVariant::Holdem, &hole_cards, &cards("Qs7s2s")), Some(HandRanking::Flush));
+    }
+
+    /// in Omaha a lone ace of the flush suit or a board straight doesn't play, as exactly two hole cards must
+    #[test]
+    fn omaha_uses_two_hole_cards() {
+        let board = cards("KsQs7s2s3d");
+        let one_spade = cards("As9c8c4h");
+        assert_eq!(best_hand_for(GameVariant::Holdem, &one_spade, &board).unwrap().hand_ranking(), HandRanking::Flush);
+        assert_eq!(best_hand_for(GameVariant::Omaha, &one_spade, &board).unwrap().hand_ranking(), HandRanking::HighCard);
+        let two_spades = cards("As9s8c4h");
+        assert_eq!(best_hand_for(GameVariant::Omaha, &two_spades, &board).unwrap().hand_ranking(), HandRanking::Flush);
+
+        // four aces in the hand are only a pair, and the straight on the board doesn't play either
+        let straight_board = cards("9h8d7c6sTd");
+        let quads = cards("AsAdAcAh");
+        assert_eq!(best_hand_for(GameVariant::Holdem, &quads, &straight_board).unwrap().hand_ranking(), HandRanking::FourOfAKind);
+        assert_eq!(best_hand_for(GameVariant::Omaha, &quads, &straight_board).unwrap().hand_ranking(), HandRanking::Pair);
+        assert!(best_hand_for(GameVariant::Omaha, &quads, &cards("9h8d")).is_none());
+    }
+
+    /// a pair showing beats any high cards, and otherwise the highest card (then the next) decides
+    #[test]
+    fn stud_showing_strength() {
+        assert!(showing_strength(&cards("2s2d")) > showing_strength(&cards("AsKd")));
+        assert!(showing_strength(&cards("AsKd")) > showing_strength(&cards("AsQd")));
+        assert!(showing_strength(&cards("Qs7h2d")) > showing_strength(&cards("Jc9h8d")));
+        assert_eq!(showing_strength(&cards("9s9d")), showing_strength(&cards("9h9c")));
+        // the four cards to a flush showing are just queen high
+        assert!(showing_strength(&cards("3c3d4h5h")) > showing_strength(&cards("Qs9s7s5s")));
+    }
+
+    #[test]
+    fn descriptions() {
+        let description = |text: &str| best_hand(&cards(text)).unwrap().description();
+        assert_eq!(description("As9d7c5h3s2dJc"), "High Card, Ace high");
+        assert_eq!(description("6s6d7c5h3s2dJc"), "Pair, Sixes");
+        assert_eq!(description("AsAd7c7h3s3dJc"), "Two Pair, Aces and Sevens");
+        assert_eq!(description("AsAdAc7h3s2dJc"), "Three of a Kind, Aces");
+        assert_eq!(description("As2d3c4h5sKdKc"), "Straight, Five high");
+        assert_eq!(description("As9s7s5s3sAdAc"), "Flush, Ace high");
+        assert_eq!(description("3s3d3cKhKs2dJc"), "Full House, Threes full of Kings");
+        assert_eq!(description("KsKdKc3h3s2dJc"), "Full House, Kings full of Threes");
+        assert_eq!(description("AsAdAcAh7s7d7c"), "Four of a Kind, Aces");
+        assert_eq!(description("9s8s7s6s5sAdAc"), "Straight Flush, Nine high");
+        assert_eq!(description("AsKsQsJsTsAdAc"), "Royal Flush");
+    }
+}
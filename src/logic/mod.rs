@@ -1,12 +1,27 @@
 mod card;
 mod pots;
 mod game_hand;
+mod equity;
+mod blind_schedule;
 
 pub mod player;
 pub mod deck;
+pub mod game_state;
+pub mod hand_eval;
 pub mod table;
 
 pub use table::Table;
 pub use player::PlayerAction;
 pub use player::PlayerConfig;
 pub use player::PLAYER_TIMEOUT;
+pub use game_hand::MinRaiseRule;
+pub use game_hand::AnteMode;
+pub use game_hand::AnteOrder;
+pub use game_hand::RevealOrder;
+pub use game_hand::BettingStructure;
+pub use game_hand::GameVariant;
+pub use game_hand::ClockScaling;
+pub use card::HandRanking;
+pub use deck::ReshufflePolicy;
+pub use deck::ExposurePolicy;
+pub use blind_schedule::BlindSchedule;
@@ -1,9 +1,10 @@
 use super::card::{Card, HandResult};
-use super::game_hand::GameHand;
+use super::game_hand::{GameHand, GameVariant};
+use super::hand_eval;
 use crate::messages::WsMessage;
 use actix::prelude::Recipient;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::iter;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 use std::fmt;
@@ -12,38 +13,104 @@ use std::fmt;
 /// before we remove them from any game AND the hub.
 pub const PLAYER_TIMEOUT: Duration = Duration::from_secs(1800);
 
-#[derive(Debug, Copy, Clone)]
+/// the longest a player can ask the table to wait before mucking their losing hand
+pub const MAX_AUTO_MUCK_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum PlayerAction {
+    PostAnte(u32),
     PostSmallBlind(u32),
     PostBigBlind(u32),
+    PostBringIn(u32), // in Stud, the lowest card showing opens third street with a forced bet
+    PostStraddle(u32), // a blind for double the current bet, posted before anyone has acted preflop
     Fold,
     SitOut,    
     Check,
     Bet(u32),
     Call,
-    //Raise(u32), // i guess a raise is just a bet really?
+    // a Bet opens the betting on a street, while a Raise is the increase on top of the current bet
+    Raise(u32),
+    // clients can be explicit about whether an amount is the new total for the street or the increase,
+    // but these are normalized into a Bet or a Raise before they reach the table (see normalize())
+    RaiseTo(u32),
+    RaiseBy(u32),
 }
 impl fmt::Display for PlayerAction {
     // This trait requires `fmt` with this exact signature.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 	let output = match self {
+	    Self::PostAnte(amount) => format!("ante:{}", amount),
 	    Self::PostSmallBlind(amount) => format!("small_blind:{}", amount),
 	    Self::PostBigBlind(amount) => format!("big_blind:{}", amount),
+	    Self::PostBringIn(amount) => format!("bring_in:{}", amount),
+	    Self::PostStraddle(amount) => format!("straddle:{}", amount),
 	    Self::Fold => "fold".to_owned(),
 	    Self::SitOut => "sit out".to_owned(),	    
 	    Self::Check => "check".to_owned(),
 	    Self::Bet(amount) => format!("bet:{}", amount),
 	    Self::Call => "call".to_owned(),
+	    Self::Raise(amount) => format!("raise:{}", amount),
+	    Self::RaiseTo(amount) => format!("raise_to:{}", amount),
+	    Self::RaiseBy(amount) => format!("raise_by:{}", amount),
 	};
         write!(f, "{}", output)
     }
 }
 
-/// this struct holds the player name and recipient address
-#[derive(Debug, Clone)]
+impl PlayerAction {
+    /// turn an explicit raise into a Bet if nobody has bet yet on this street,
+    /// or into a Raise of the increase over the current street bet otherwise.
+    /// Any other action is returned as is
+    pub fn normalize(self, current_bet: u32) -> Self {
+	match self {
+	    Self::RaiseTo(amount) | Self::RaiseBy(amount) if current_bet == 0 => Self::Bet(amount),
+	    Self::RaiseTo(amount) => Self::Raise(amount.saturating_sub(current_bet)),
+	    Self::RaiseBy(amount) => Self::Raise(amount),
+	    other => other,
+	}
+    }
+}
+
+/// running totals over a player's time at a table,
+/// which are summarized for the player when they leave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub hands_played: u32,
+    pub vpip_hands: u32, // hands where they voluntarily put money in preflop (i.e. not just the blinds)
+    pub pfr_hands: u32, // hands where they raised preflop
+    pub biggest_pot_won: u32,
+    pub total_buy_in: u32, // all the money the player has been given at the table
+}
+
+impl SessionStats {
+    pub fn new(buy_in: u32) -> Self {
+	Self {
+	    hands_played: 0,
+	    vpip_hands: 0,
+	    pfr_hands: 0,
+	    biggest_pot_won: 0,
+	    total_buy_in: buy_in,
+	}
+    }
+
+    /// the percent of hands played where the count was incremented
+    fn percent_of_hands(&self, count: u32) -> f64 {
+	if self.hands_played == 0 {
+	    0.0
+	} else {
+	    100.0 * count as f64 / self.hands_played as f64
+	}
+    }
+}
+
+/// this struct holds the player name and recipient address.
+/// When saved, the address and heartbeat are left out: a restored player is disconnected
+/// until they reconnect, and their heartbeat starts over
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerConfig {
     pub id: Uuid,
     pub name: Option<String>,
+    #[serde(skip)]
     pub player_addr: Option<Recipient<WsMessage>>,
     // the heart_beat indicates the last time the player was "active"
     // inside the game, any player action updates the heartbeat, as well as text messages or ImBack
@@ -51,6 +118,7 @@ pub struct PlayerConfig {
     // The game hub checks on an interval for failed-heart-beat configs in the lobby, and removes them
     // Moreover, the WsPlayerSession also maintains a heartbeat, and on time out, stops itself
     // This should remove all memory of this player and session from the system (unless I missed something lol)
+    #[serde(skip, default = "Instant::now")]
     pub heart_beat: Instant, 
 }
 
@@ -119,7 +187,7 @@ impl PlayerConfig {
 	let gap = Instant::now().duration_since(self.heart_beat);
 	if gap > PLAYER_TIMEOUT {
             // heartbeat timed out
-            println!("player timed out!");
+            log::debug!("player timed out!");
 	    false
 	} else {
 	    true
@@ -128,7 +196,7 @@ impl PlayerConfig {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub id: Uuid,
     pub human_controlled: bool, // do we need user input or let the computer control it
@@ -137,6 +205,23 @@ pub struct Player {
     pub is_sitting_out: bool, // if sitting out, then they are not active for any future hand
     pub hole_cards: Vec<Card>,
     pub last_action: Option<PlayerAction>, // the last thing they did (or None)
+    // how long after the showdown a losing hand is kept before it is mucked.
+    // None means the cards are simply taken along with everyone else's at the end of the hand
+    pub auto_muck_delay: Option<Duration>,
+    // whether to muck a losing hand at showdown rather than show it, when not required to show
+    pub auto_muck: bool,
+    // a fold queued for the current hand, or for the next one when queued between hands.
+    // Any blind is still posted, then the player folds without a prompt when the action reaches them
+    pub quick_fold: bool,
+    // whether to post a straddle whenever the table allows one when it is their turn preflop
+    pub straddle: bool,
+    // whether to top back up to the buy-in between hands, if the table allows rebuys
+    pub auto_rebuy: bool,
+    // the stack to top back up to instead of the buy-in, up to the table's max stack
+    pub rebuy_target: Option<u32>,
+    // extra seconds the player can spend once their action timeout runs out
+    pub time_bank_seconds: u32,
+    pub stats: SessionStats,
 }
 
 impl Player {
@@ -149,9 +234,40 @@ impl Player {
             is_sitting_out: false,
             hole_cards: Vec::<Card>::with_capacity(2),
 	    last_action: None,
+	    auto_muck_delay: None,
+	    auto_muck: false,
+	    quick_fold: false,
+	    straddle: false,
+	    auto_rebuy: false,
+	    rebuy_target: None,
+	    time_bank_seconds: 0,
+	    stats: SessionStats::new(money),
         }
     }
 
+    /// a summary of the player's session at the table, e.g. for when they leave
+    pub fn session_summary(&self) -> json::JsonValue {
+	json::object! {
+	    msg_type: "session_summary".to_owned(),
+	    hands_played: self.stats.hands_played,
+	    vpip: self.stats.percent_of_hands(self.stats.vpip_hands),
+	    pfr: self.stats.percent_of_hands(self.stats.pfr_hands),
+	    biggest_pot_won: self.stats.biggest_pot_won,
+	    net_result: self.money as i64 - self.stats.total_buy_in as i64,
+	}
+    }
+
+    /// the player's line in the end-of-session reconciliation report,
+    /// i.e. what they put in versus what they have now
+    pub fn reconciliation_entry(&self, name: Option<String>) -> json::JsonValue {
+	json::object! {
+	    player_name: name,
+	    total_buy_in: self.stats.total_buy_in,
+	    final_stack: self.money,
+	    net: self.money as i64 - self.stats.total_buy_in as i64,
+	}
+    }
+
     /// create a new bot from scratch
     pub fn new_bot(money: u32) -> Self {
         let bot_id = Uuid::new_v4(); // can just gen a new arbitrary id for the bot
@@ -177,6 +293,12 @@ impl Player {
     /// If the player is not active, or if the hand never made it to showdown, then we simply
     /// return None as the optional best hand.
     pub fn determine_best_hand(&self, gamehand: &GameHand) -> Option<HandResult> {
+	self.determine_best_hand_on(gamehand, &gamehand.board())
+    }
+
+    /// the same as determine_best_hand, but against the given board,
+    /// e.g. the second run when the board is run twice
+    pub fn determine_best_hand_on(&self, gamehand: &GameHand, board: &[Card]) -> Option<HandResult> {
         if !self.is_active {
             // if the player isn't active, then can't have a best hand
             return None;
@@ -185,39 +307,38 @@ impl Player {
 	    // there is no "best hand" if we didn't even make it to showdown
 	    return None;
 	}
-	// we look at all possible 7 choose 5 (21) hands from the hole cards, flop, turn, river
-	let mut best_result: Option<HandResult> = None;
-	let mut hand_count = 0;
-	for exclude_idx1 in 0..7 {
-	    for exclude_idx2 in exclude_idx1 + 1..7 {
-		let mut possible_hand = Vec::with_capacity(5);
-		hand_count += 1;
-		for (idx, card) in self
-		    .hole_cards
-		    .iter()
-		    .chain(gamehand.flop.as_ref().unwrap().iter())
-		    .chain(iter::once(&gamehand.turn.unwrap()))
-		    .chain(iter::once(&gamehand.river.unwrap()))
-		    .enumerate()
-		{
-		    if idx != exclude_idx1 && idx != exclude_idx2 {
-			//println!("pushing!");
-			possible_hand.push(*card);
-		    }
-		}
-		// we have built a hand of five cards, now evaluate it
-		let current_result = HandResult::analyze_hand(possible_hand);
-		match best_result {
-		    None => best_result = Some(current_result),
-		    Some(result) if current_result > result => {
-			best_result = Some(current_result)
-		    }
-		    _ => (),
-		}
-	    }
-	}
-	assert!(hand_count == 21); // 7 choose 5
-	best_result
+	hand_eval::best_hand_for(gamehand.variant, &self.hole_cards, board)
+    }
+
+    /// the hole cards run together, e.g. "AsKd"
+    pub fn hole_cards_string(&self) -> String {
+	self.hole_cards.iter().map(|card| card.to_string()).collect()
+    }
+
+    /// the cards dealt face up, which everyone at the table can see (only in Stud)
+    pub fn up_cards(&self, variant: GameVariant) -> Vec<Card> {
+	self.hole_cards
+	    .iter()
+	    .enumerate()
+	    .filter(|(position, _)| variant.is_up_card(*position))
+	    .map(|(_, card)| *card)
+	    .collect()
     }
     
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// raising by 20 over a bet of 40 is the same as raising to 60
+    #[test]
+    fn raise_to_and_by_normalize() {
+	let current_bet = 40;
+	assert!(matches!(PlayerAction::RaiseBy(20).normalize(current_bet), PlayerAction::Raise(20)));
+	assert!(matches!(PlayerAction::RaiseTo(60).normalize(current_bet), PlayerAction::Raise(20)));
+	// with no bet yet on the street, either form opens the betting
+	assert!(matches!(PlayerAction::RaiseTo(60).normalize(0), PlayerAction::Bet(60)));
+	assert!(matches!(PlayerAction::Call.normalize(current_bet), PlayerAction::Call));
+    }
+}
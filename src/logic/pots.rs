@@ -1,3 +1,4 @@
+use std::cmp;
 use std::collections::HashMap;
 use std::ops::Deref;
 
@@ -64,11 +65,35 @@ impl PotManager {
         self.pots.iter().filter(|x| x.money > 0).map(|x| x.money).collect()
     }
 
+    /// dead money (such as a big blind ante) goes straight into the main pot,
+    /// without counting towards the player's contribution when matching bets and all-ins
+    pub fn add_dead_money(&mut self, player_id: Uuid, amount: u32) {
+        let main_pot = &mut self.pots[0];
+        main_pot.contributions.entry(player_id).or_insert(0);
+        main_pot.money += amount;
+    }
+
+    /// skim the rake off each pot, main pot first, before the pots are paid out.
+    /// The cap is for the whole hand, so once it is reached the later pots are left alone.
+    /// Returns the total amount raked
+    pub fn take_rake(&mut self, percent: u32, cap: Option<u32>) -> u32 {
+        let mut raked = 0;
+        for pot in self.pots.iter_mut() {
+            let mut rake = (u64::from(pot.money) * u64::from(percent) / 100) as u32;
+            if let Some(cap) = cap {
+                rake = cmp::min(rake, cap - raked);
+            }
+            pot.money -= rake;
+            raked += rake;
+        }
+        raked
+    }
+
     /// given a player id and an amount they need to contribute to the pot
     /// and whether this is putting them all-in), this method puts the proper
     /// amount into the proper pot(s), and possibly create and redistribute into a new side pot
     pub fn contribute(&mut self, player_id: Uuid, amount: u32, all_in: bool) {
-        println!(
+        log::debug!(
             "inside contribute: {:?}, {:?}, all_in={:?}",
             player_id, amount, all_in
         );
@@ -79,7 +104,7 @@ impl PotManager {
         for (i, pot) in self.pots.iter_mut().enumerate() {
             let so_far = pot.contributions.entry(player_id).or_insert(0);
             if let Some(cap) = pot.cap {
-                println!("cap of {}", cap);
+                log::debug!("cap of {}", cap);
                 if *so_far > cap {
                     panic!(
                         "somehow player {} put in more than the cap for \
@@ -87,37 +112,39 @@ impl PotManager {
                         player_id, i
                     );
                 } else if *so_far == cap {
-                    println!("we have already filled up this pot");
+                    log::debug!("we have already filled up this pot");
                     continue;
                 }
                 // else, we need to put more into the pot
                 let remaining = cap - *so_far; // amount left before the cap
                 if remaining >= to_contribute {
-                    println!(
+                    log::debug!(
                         "the new contribution fits since {} > {}",
                         remaining, to_contribute
                     );
                     *so_far += to_contribute;
                     pot.money += to_contribute;
-                    if all_in {
+                    if all_in && *so_far < cap {
                         // our all-in is smaller than the previous all-in
-                        println!("our all-in is smaller than the previous all-in");
+                        // Note: an all-in for exactly the cap shares this pot as is,
+                        // so there is no new layer to create
+                        log::debug!("our all-in is smaller than the previous all-in");
                         //pot.cap = Some(pot.contributions[&player_id]);
                         insert_pot = Some((i, pot.contributions[&player_id]));
                     }
                     break;
                 } else {
                     // we need to contribute to the cap, then put more in the next pot
-                    println!("we need to contribute to the cap, then put more in the next pot");
+                    log::debug!("we need to contribute to the cap, then put more in the next pot");
                     *so_far += remaining;
                     pot.money += remaining;
                     assert!(*so_far == cap);
                     to_contribute -= remaining;
-                    println!("still need to contribute {}", to_contribute)
+                    log::debug!("still need to contribute {}", to_contribute)
                 }
             } else {
                 // there is not cap on this pot, so simply put the new money in for this player
-                println!("no cap");
+                log::debug!("no cap");
                 *so_far += to_contribute;
                 pot.money += to_contribute;
                 if all_in {
@@ -128,7 +155,7 @@ impl PotManager {
             }
         }
         if let Some((index, new_cap)) = insert_pot {
-            println!(
+            log::debug!(
                 "inserting a pot at index {} and capping the previous pot at {}",
                 index + 1,
                 new_cap
@@ -146,7 +173,7 @@ impl PotManager {
     /// will need to be transfered into the new pot at index+1
     fn transfer_excess(&mut self, index: usize, new_cap: u32) {
         let prev_pot = self.pots.get_mut(index).unwrap();
-        println!("prev_pot = {:?}", prev_pot);
+        log::debug!("prev_pot = {:?}", prev_pot);
         let mut transfers = HashMap::<Uuid, u32>::new();
         let prev_cap_opt = prev_pot.cap; // move the previous cap to the new pot (if needed)
         prev_pot.cap = Some(new_cap);
@@ -160,9 +187,9 @@ impl PotManager {
                 prev_pot.money -= excess;
             }
         }
-        println!("after taking = {:?}", prev_pot);
-        println!("transfers = {:?}", transfers);
-        let mut new_pot = self.pots.get_mut(index + 1).unwrap();
+        log::debug!("after taking = {:?}", prev_pot);
+        log::debug!("transfers = {:?}", transfers);
+        let new_pot = self.pots.get_mut(index + 1).unwrap();
         new_pot.money = transfers.values().sum();
         new_pot.contributions = transfers;
 
@@ -175,3 +202,62 @@ impl PotManager {
     }    
     
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// two players go all-in for the exact same amount behind a bigger bet.
+    /// They should share the main pot, and the excess of the bettor is in a single side pot
+    #[test]
+    fn equal_all_ins_share_pot() {
+        let mut manager = PotManager::new();
+        let bettor = Uuid::new_v4();
+        let short1 = Uuid::new_v4();
+        let short2 = Uuid::new_v4();
+
+        manager.contribute(bettor, 300, false);
+        manager.contribute(short1, 100, true);
+        manager.contribute(short2, 100, true);
+
+        // no redundant empty pot layers
+        assert_eq!(manager.len(), 2);
+        assert_eq!(manager.simple_repr(), vec![300, 200]);
+
+        let main_pot = &manager[0];
+        assert_eq!(main_pot.cap, Some(100));
+        assert!(main_pot.is_elligible(&bettor));
+        assert!(main_pot.is_elligible(&short1));
+        assert!(main_pot.is_elligible(&short2));
+
+        let side_pot = &manager[1];
+        assert!(side_pot.is_elligible(&bettor));
+        assert!(!side_pot.is_elligible(&short1));
+        assert!(!side_pot.is_elligible(&short2));
+    }
+
+    /// each pot gives up its percentage, rounded down
+    #[test]
+    fn rake() {
+        let mut manager = PotManager::new();
+        manager.contribute(Uuid::new_v4(), 300, false);
+        manager.contribute(Uuid::new_v4(), 100, true);
+        manager.contribute(Uuid::new_v4(), 300, false);
+        assert_eq!(manager.simple_repr(), vec![300, 400]);
+
+        assert_eq!(manager.take_rake(5, None), 15 + 20);
+        assert_eq!(manager.simple_repr(), vec![285, 380]);
+    }
+
+    /// the cap is for the whole hand, so it stops the rake partway through the side pots
+    #[test]
+    fn rake_cap() {
+        let mut manager = PotManager::new();
+        manager.contribute(Uuid::new_v4(), 300, false);
+        manager.contribute(Uuid::new_v4(), 100, true);
+        manager.contribute(Uuid::new_v4(), 300, false);
+
+        assert_eq!(manager.take_rake(5, Some(25)), 25);
+        assert_eq!(manager.simple_repr(), vec![285, 390]);
+    }
+}
@@ -1,18 +1,26 @@
 use actix::Addr;
 use json::object;
-use rand::Rng;
-use std::collections::{HashMap, VecDeque};
+use serde::{Deserialize, Serialize};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::convert::TryInto;
 
-use super::card::Card;
-use super::deck::{Deck, StandardDeck};
-use super::game_hand::{GameHand, Street};
+use super::blind_schedule::BlindSchedule;
+use super::card::{Card, HandRanking, HandRules};
+use super::deck::{Deck, ExposurePolicy, ReshufflePolicy, ShuffleCommitment, StandardDeck};
+use super::equity;
+use super::hand_eval;
+use super::game_state::{GameStateDto, PlayerInfoDto};
+use super::game_hand::{AnteMode, AnteOrder, BettingStructure, Blind, BlindConfig, ClockScaling, GameHand, GameVariant, MinRaiseRule, RevealOrder, Settlement, Street, MAX_SEATS};
 
-use super::player::{Player, PlayerAction, PlayerConfig};
+use super::player::{Player, PlayerAction, PlayerConfig, SessionStats, MAX_AUTO_MUCK_DELAY};
 use crate::hub::TableHub;
 
-use crate::messages::{AdminCommand, GameOver, JoinTableError, MetaAction, Returned, ReturnedReason, WsMessage};
+use crate::messages::{AdminCommand, ConfigError, GameEvent, GameOver, JoinTableError, MetaAction, Returned, ReturnedReason, WsMessage};
 
 use std::{cmp, sync::Arc, thread, time};
 
@@ -21,21 +29,174 @@ use uuid::Uuid;
 // any game that runs for too long without a human will end, rather than looping indefinitely
 const NON_HUMAN_HANDS_LIMIT: u32 = 3;
 
+// how long the all-in players have to agree to a chop before the board is run out
+const CHOP_WAIT: time::Duration = time::Duration::from_secs(15);
+
+// when scaling the clock with the pot, every this many big blinds in the pot is worth an extra second
+const CLOCK_SCALING_POT_BIG_BLINDS: u32 = 10;
+
+// when scaling the clock with the players in the hand, each player past heads-up is worth this many seconds
+const CLOCK_SCALING_SECONDS_PER_PLAYER: u32 = 5;
+
+// when scaling the pause after a hand with the pot, every this many big blinds in the pot is worth an extra second
+const FINISH_DELAY_POT_BIG_BLINDS: u32 = 25;
+
+// with only bots seated nobody needs time to follow along, so no pause is longer than this by default
+const BOTS_ONLY_MAX_PAUSE: time::Duration = time::Duration::from_millis(10);
+
+// while waiting, whether on a player or for a pause to run out, how often to look for actions and meta actions,
+// so they are picked up as soon as they arrive
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(10);
+
+// a player who keeps sending actions that aren't allowed is folded (or checked) after this many,
+// rather than waiting out their whole clock
+const MAX_INVALID_ACTIONS: u32 = 5;
+
+// how many of the hand's latest actions a player who joins or reconnects mid-hand is caught up with
+const RECENT_ACTIONS: usize = 8;
+
+// in fixed limit, a street is capped after this many raises
+const FIXED_LIMIT_RAISE_CAP: u32 = 4;
+
+// once a table is this close to its closing time (or hand limit), the players are warned before each hand
+const CLOSING_WARNING: time::Duration = time::Duration::from_secs(5 * 60);
+const CLOSING_WARNING_HANDS: u32 = 2;
+
+/// what is saved of a table, so it can be restored if the server restarts.
+/// The seats, stacks and every setting are kept, but not a hand in progress or anything about the connections
+#[derive(Debug, Serialize, Deserialize)]
+struct TableSnapshot {
+    name: String,
+    max_players: u8,
+    small_blind: u32,
+    big_blind: u32,
+    buy_in: u32,
+    password: Option<String>,
+    admin_id: Uuid,
+    button_idx: usize,
+    hand_num: u32,
+    min_players_to_start: usize,
+    ante_mode: AnteMode,
+    ante_order: AnteOrder,
+    blind_schedule: Option<BlindSchedule>,
+    small_blind_idx: Option<usize>,
+    big_blind_idx: Option<usize>,
+    min_raise_rule: MinRaiseRule,
+    betting_structure: BettingStructure,
+    max_straddles: u32,
+    variant: GameVariant,
+    bad_beat_threshold: HandRanking,
+    action_timeout: u32,
+    clock_scaling: ClockScaling,
+    clocks_frozen: bool,
+    time_bank: u32,
+    time_bank_replenish: u32,
+    finish_delay: u32,
+    max_finish_delay: u32,
+    finish_delay_pot_scaling: bool,
+    allow_chop: bool,
+    run_it_twice: bool,
+    show_runout_equities: bool,
+    all_in_reveal_order: RevealOrder,
+    all_in_reveal_delay: time::Duration,
+    anonymous: bool,
+    allow_rebuys: bool,
+    max_stack: Option<u32>,
+    min_stack_to_play: u32,
+    rake_percent: u32,
+    rake_cap: Option<u32>,
+    total_raked: u32,
+    rebuys_at_orbit_start: bool,
+    remove_busted: bool,
+    rematch_window: Option<time::Duration>,
+    muted_ids: HashSet<Uuid>,
+    check_it_down: bool,
+    blind_config: BlindConfig,
+    reshuffle_policy: ReshufflePolicy,
+    exposure_policy: ExposurePolicy,
+    expose_bot_decisions: bool,
+    audit_deck: bool,
+    commit_shuffle: bool,
+    show_deck_count: bool,
+    players: Vec<Option<Player>>,
+    player_configs: Vec<PlayerConfig>,
+}
+
 #[derive(Debug)]
 pub struct Table {
     hub_addr: Option<Addr<TableHub>>, // needs to be able to communicate back to the hub sometimes
     pub name: String,
     deck: Box<dyn Deck>,
-    players: [Option<Player>; 9], // 9 spots where players can sit
+    players: Vec<Option<Player>>, // the seats, one per max_players (up to MAX_SEATS)
     player_ids_to_configs: HashMap<Uuid, PlayerConfig>,
+    spectators: HashMap<Uuid, PlayerConfig>, // connected to the table, but without a seat
     max_players: u8, // how many will we let in the game
+    min_players_to_start: usize, // a hand isn't dealt until this many players can be dealt in
     small_blind: u32,
     big_blind: u32,
+    ante_mode: AnteMode, // who pays the ante each hand
+    ante_order: AnteOrder, // whether a per player ante is collected before or after the blinds
+    blind_schedule: Option<BlindSchedule>, // for tournaments, the blinds go up over time
+    closing_at: Option<time::Instant>, // when a scheduled table ends, after the hand in progress
     buy_in: u32,
     password: Option<String>,
     admin_id: Uuid,
     button_idx: usize, // index of the player with the button
+    // with three or more players, the seats of the blinds for the current hand, so the big blind can
+    // advance exactly one seat each hand (the dead button rule). None when the blinds go by the button
+    small_blind_idx: Option<usize>,
+    big_blind_idx: Option<usize>,
     hand_num: u32, // keeps track of the current hand number
+    min_raise_rule: MinRaiseRule, // which convention decides how big a raise must be
+    betting_structure: BettingStructure, // no limit, pot limit, or fixed limit
+    max_straddles: u32, // how many straddles can be chained on top of the blinds, each doubling the bet. 0 for none
+    variant: GameVariant, // Hold'em or Omaha
+    bad_beat_threshold: HandRanking, // a losing hand at least this good at showdown is a bad beat
+    action_timeout: u32, // how many seconds a human has to act before being sat out
+    clock_scaling: ClockScaling, // whether bigger decisions get more than the action_timeout
+    clocks_frozen: bool, // while frozen, no player's action clock counts down
+    time_bank: u32, // how many seconds of time bank each player starts with
+    time_bank_replenish: u32, // how many seconds are added to each player's time bank every orbit
+    finish_delay: u32, // how many seconds the table pauses after a hand, for each settlement shown
+    max_finish_delay: u32, // the longest the table pauses after a hand, however many settlements or big the pot
+    bots_only_max_pause: time::Duration, // the longest any pause can be when no human is seated, so bot tables wind down quickly
+    max_pause: Option<time::Duration>, // the longest any pause can be, no matter who is seated. None lets every pause run in full
+    finish_delay_pot_scaling: bool, // whether bigger pots get a longer pause after the hand
+    allow_chop: bool, // can all-in players agree to split the pots by equity instead of running out the board
+    run_it_twice: bool, // once everyone is all-in before the river, the rest of the board is dealt twice for half the pot each
+    show_runout_equities: bool, // while an all-in board is run out, everyone sees the updated equities after each street
+    all_in_reveal_order: RevealOrder, // whose hand is turned face up first once the betting is closed with a player all-in
+    all_in_reveal_delay: time::Duration, // the pause between turning each hand face up. Zero shows them all at once
+    anonymous: bool, // are the players shown to the table as "Seat N" instead of by name
+    chop_agreements: Option<HashSet<Uuid>>, // while a chop is on offer, which players have agreed to it
+    allow_rebuys: bool, // can players with auto rebuy on be topped back up to the buy-in between hands
+    max_stack: Option<u32>, // the biggest stack a player can rebuy to with a target. None means only up to the buy-in
+    min_stack_to_play: u32, // a player below this between hands is sat out until they rebuy. 0 means no minimum
+    rake_percent: u32, // the percentage of each pot the house takes, once the flop has been seen
+    rake_cap: Option<u32>, // the most the house takes from a single hand. None means no cap
+    total_raked: u32, // the running total taken by the house at this table
+    rebuys_at_orbit_start: bool, // hold rebuys until the button comes back around, so nobody reloads to dodge the blinds
+    remove_busted: bool, // for tournaments, a player who runs out of chips loses their seat. Cash games keep it for a rebuy
+    rematch_window: Option<time::Duration>, // how long the two players have to agree to a rematch once a heads-up match is over
+    rematch_agreements: Option<HashSet<Uuid>>, // while a rematch is on offer, which players have agreed to it
+    muted_ids: HashSet<Uuid>, // players and spectators whose chat the admin has muted
+    check_it_down: bool, // once a player is all-in, the others can't open the betting against each other
+    blind_config: BlindConfig, // which blinds get posted for each number of players
+    reshuffle_policy: ReshufflePolicy,
+    exposure_policy: ExposurePolicy, // whether a hand with an exposed hole card is voided or played on
+    exposed_cards: Vec<Card>, // cards that have been revealed before they should have been, e.g. by a client
+    expose_bot_decisions: bool, // for debugging, tell the admin what each bot decided and why
+    audit_deck: bool, // for debugging, record the deck order of every hand. This leaks the cards, so off in production
+    audited_deck_order: Option<Vec<Card>>, // when auditing, the order the cards of the current hand come off the deck
+    shuffle_seeds: Option<StdRng>, // for a reproducible session, every shuffle is seeded from this generator
+    shuffle_seed: Option<u64>, // the seed of the last shuffle, which the current hand is dealt from
+    commit_shuffle: bool, // for trust, publish a hash of the shuffle seed before each hand and reveal the seed after it
+    shuffle_commitment: Option<ShuffleCommitment>, // the commitment to the shuffle the current hand is dealt from
+    show_deck_count: bool, // for training, show how many cards are left in the deck. Normally hidden as meta information
+    departed_results: Vec<json::JsonValue>, // the reconciliation entries of players who left before the table ended
+    hand_events: RefCell<Vec<json::JsonValue>>, // the events published so far in the current hand
+    last_hand_events: Option<Vec<json::JsonValue>>, // the events of the last completed hand, to replay to anyone who asks
+    save_path: Option<PathBuf>, // where the table is saved after every hand, so it can be restored after a restart
 }
 
 /// useful for unit tests, for example
@@ -45,16 +206,74 @@ impl Default for Table {
             hub_addr: None,
             name: "Table".to_owned(),
             deck: Box::new(StandardDeck::new()),
-            players: Default::default(),
+            players: vec![None; MAX_SEATS],
             player_ids_to_configs: HashMap::<Uuid, PlayerConfig>::new(),
+            spectators: HashMap::<Uuid, PlayerConfig>::new(),
             max_players: 9,
+            min_players_to_start: 2,
             small_blind: 4,
             big_blind: 8,
+            ante_mode: AnteMode::None,
+            ante_order: AnteOrder::BeforeBlinds,
+            blind_schedule: None,
+            closing_at: None,
             buy_in: 1000,
             password: None,
 	    admin_id: uuid::Uuid::new_v4(), // an arbitrary/random admin id
             button_idx: 0,
+            small_blind_idx: None,
+            big_blind_idx: None,
             hand_num: 1,
+            min_raise_rule: MinRaiseRule::LastRaiseIncrement,
+            betting_structure: BettingStructure::NoLimit,
+            max_straddles: 0,
+            variant: GameVariant::Holdem,
+            bad_beat_threshold: HandRanking::FourOfAKind,
+            action_timeout: 45,
+            clock_scaling: ClockScaling::Off,
+            clocks_frozen: false,
+            time_bank: 0,
+            time_bank_replenish: 0,
+            finish_delay: 3,
+            max_finish_delay: 30,
+            bots_only_max_pause: BOTS_ONLY_MAX_PAUSE,
+            max_pause: None,
+            finish_delay_pot_scaling: false,
+            allow_chop: false,
+            run_it_twice: false,
+            show_runout_equities: false,
+            all_in_reveal_order: RevealOrder::LastAggressor,
+            all_in_reveal_delay: time::Duration::ZERO,
+            anonymous: false,
+            chop_agreements: None,
+            rematch_window: None,
+            rematch_agreements: None,
+            muted_ids: HashSet::new(),
+            allow_rebuys: true,
+            max_stack: None,
+            rake_percent: 0,
+            rake_cap: None,
+            total_raked: 0,
+            min_stack_to_play: 0,
+            rebuys_at_orbit_start: false,
+            remove_busted: false,
+            check_it_down: false,
+            blind_config: BlindConfig::default(),
+            reshuffle_policy: ReshufflePolicy::EveryHand,
+            exposure_policy: ExposurePolicy::Continue,
+            exposed_cards: vec![],
+            expose_bot_decisions: false,
+            audit_deck: false,
+            audited_deck_order: None,
+            shuffle_seeds: None,
+            shuffle_seed: None,
+            commit_shuffle: false,
+            shuffle_commitment: None,
+            show_deck_count: false,
+            departed_results: vec![],
+            hand_events: RefCell::new(vec![]),
+            last_hand_events: None,
+            save_path: None,
         }
     }
 }
@@ -62,10 +281,12 @@ impl Default for Table {
 impl Table {
     /// the address of the TableHub is optional so that unit tests need not worry about it
     /// We can pass in a custom Deck object, but if not, we will just construct a StandardDeck
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         hub_addr: Addr<TableHub>,
         name: String,
         deck_opt: Option<Box<dyn Deck>>,
+        deck_seed: Option<u64>, // for debugging, the same seed deals the same cards for the whole session
         max_players: u8, // how many will we let in the game
         small_blind: u32,
         big_blind: u32,
@@ -78,150 +299,457 @@ impl Table {
         } else {
             Box::new(StandardDeck::new())
         };
-        Table {
+        // one seat per player we let in, up to the most a table can have
+        let max_players = cmp::min(max_players, MAX_SEATS as u8);
+        let mut table = Table {
             hub_addr: Some(hub_addr),
             name,
             deck,
-            players: Default::default(),
+            players: vec![None; max_players.into()],
             player_ids_to_configs: HashMap::<Uuid, PlayerConfig>::new(),
+            spectators: HashMap::<Uuid, PlayerConfig>::new(),
             max_players,
+            min_players_to_start: 2,
             small_blind,
             big_blind,
+            ante_mode: AnteMode::None,
+            ante_order: AnteOrder::BeforeBlinds,
+            blind_schedule: None,
+            closing_at: None,
             buy_in,
             password,
 	    admin_id,
             button_idx: 0,
+            small_blind_idx: None,
+            big_blind_idx: None,
             hand_num: 1,
+            min_raise_rule: MinRaiseRule::LastRaiseIncrement,
+            betting_structure: BettingStructure::NoLimit,
+            max_straddles: 0,
+            variant: GameVariant::Holdem,
+            bad_beat_threshold: HandRanking::FourOfAKind,
+            action_timeout: 45,
+            clock_scaling: ClockScaling::Off,
+            clocks_frozen: false,
+            time_bank: 0,
+            time_bank_replenish: 0,
+            finish_delay: 3,
+            max_finish_delay: 30,
+            bots_only_max_pause: BOTS_ONLY_MAX_PAUSE,
+            max_pause: None,
+            finish_delay_pot_scaling: false,
+            allow_chop: false,
+            run_it_twice: false,
+            show_runout_equities: false,
+            all_in_reveal_order: RevealOrder::LastAggressor,
+            all_in_reveal_delay: time::Duration::ZERO,
+            anonymous: false,
+            chop_agreements: None,
+            rematch_window: None,
+            rematch_agreements: None,
+            muted_ids: HashSet::new(),
+            allow_rebuys: true,
+            max_stack: None,
+            rake_percent: 0,
+            rake_cap: None,
+            total_raked: 0,
+            min_stack_to_play: 0,
+            rebuys_at_orbit_start: false,
+            remove_busted: false,
+            check_it_down: false,
+            blind_config: BlindConfig::default(),
+            reshuffle_policy: ReshufflePolicy::EveryHand,
+            exposure_policy: ExposurePolicy::Continue,
+            exposed_cards: vec![],
+            expose_bot_decisions: false,
+            audit_deck: false,
+            audited_deck_order: None,
+            shuffle_seeds: None,
+            shuffle_seed: None,
+            commit_shuffle: false,
+            shuffle_commitment: None,
+            show_deck_count: false,
+            departed_results: vec![],
+            hand_events: RefCell::new(vec![]),
+            last_hand_events: None,
+            save_path: None,
+        };
+        if let Some(seed) = deck_seed {
+            // shuffle right away, so that even the first hand is dealt from a seeded deck
+            table.shuffle_seeds = Some(StdRng::seed_from_u64(seed));
+            table.shuffle_deck();
         }
+        table
+    }
+
+    /// turn on recording the order of the deck for every hand, to be able to reproduce an odd deal
+    pub fn set_audit_deck(&mut self, audit_deck: bool) {
+	self.audit_deck = audit_deck;
+    }
+
+    /// save the table to this file after every hand
+    pub fn set_save_path(&mut self, save_path: Option<PathBuf>) {
+	self.save_path = save_path;
+    }
+
+    /// the ids of the seated humans
+    pub fn human_ids(&self) -> Vec<Uuid> {
+	self.players.iter().flatten().filter(|player| player.human_controlled).map(|player| player.id).collect()
+    }
+
+    /// a table with a password doesn't show up in the lobby
+    pub fn has_password(&self) -> bool {
+	self.password.is_some()
+    }
+
+    /// turn on showing the number of cards left in the deck in the game state, for training
+    pub fn set_show_deck_count(&mut self, show_deck_count: bool) {
+	self.show_deck_count = show_deck_count;
+    }
+
+    /// write the seats, stacks, button, hand number and settings to the file at the path as json
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+	let snapshot = TableSnapshot {
+	    name: self.name.clone(),
+	    max_players: self.max_players,
+	    small_blind: self.small_blind,
+	    big_blind: self.big_blind,
+	    buy_in: self.buy_in,
+	    password: self.password.clone(),
+	    admin_id: self.admin_id,
+	    button_idx: self.button_idx,
+	    hand_num: self.hand_num,
+	    min_players_to_start: self.min_players_to_start,
+	    ante_mode: self.ante_mode,
+	    ante_order: self.ante_order,
+	    blind_schedule: self.blind_schedule.clone(),
+	    small_blind_idx: self.small_blind_idx,
+	    big_blind_idx: self.big_blind_idx,
+	    min_raise_rule: self.min_raise_rule,
+	    betting_structure: self.betting_structure,
+	    max_straddles: self.max_straddles,
+	    variant: self.variant,
+	    bad_beat_threshold: self.bad_beat_threshold,
+	    action_timeout: self.action_timeout,
+	    clock_scaling: self.clock_scaling,
+	    clocks_frozen: self.clocks_frozen,
+	    time_bank: self.time_bank,
+	    time_bank_replenish: self.time_bank_replenish,
+	    finish_delay: self.finish_delay,
+	    max_finish_delay: self.max_finish_delay,
+	    finish_delay_pot_scaling: self.finish_delay_pot_scaling,
+	    allow_chop: self.allow_chop,
+	    run_it_twice: self.run_it_twice,
+	    show_runout_equities: self.show_runout_equities,
+	    all_in_reveal_order: self.all_in_reveal_order,
+	    all_in_reveal_delay: self.all_in_reveal_delay,
+	    anonymous: self.anonymous,
+	    allow_rebuys: self.allow_rebuys,
+	    max_stack: self.max_stack,
+	    min_stack_to_play: self.min_stack_to_play,
+	    rake_percent: self.rake_percent,
+	    rake_cap: self.rake_cap,
+	    total_raked: self.total_raked,
+	    rebuys_at_orbit_start: self.rebuys_at_orbit_start,
+	    remove_busted: self.remove_busted,
+	    rematch_window: self.rematch_window,
+	    muted_ids: self.muted_ids.clone(),
+	    check_it_down: self.check_it_down,
+	    blind_config: self.blind_config.clone(),
+	    reshuffle_policy: self.reshuffle_policy,
+	    exposure_policy: self.exposure_policy,
+	    expose_bot_decisions: self.expose_bot_decisions,
+	    audit_deck: self.audit_deck,
+	    commit_shuffle: self.commit_shuffle,
+	    show_deck_count: self.show_deck_count,
+	    players: self.players.clone(),
+	    player_configs: self.player_ids_to_configs.values().cloned().collect(),
+	};
+	let contents = serde_json::to_string(&snapshot).map_err(|e| e.to_string())?;
+	fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    /// restore a table saved with save().
+    /// Every player is disconnected until they reconnect with their id (see MetaAction::UpdateAddress),
+    /// and their heartbeat starts over, so they have the usual time to come back
+    pub fn load(path: &Path, hub_addr: Addr<TableHub>) -> Result<Self, String> {
+	let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+	let snapshot: TableSnapshot = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+	let mut table = Table::new(
+	    hub_addr,
+	    snapshot.name,
+	    None,
+	    None,
+	    snapshot.max_players,
+	    snapshot.small_blind,
+	    snapshot.big_blind,
+	    snapshot.buy_in,
+	    snapshot.password,
+	    snapshot.admin_id,
+	);
+	if snapshot.players.len() != table.players.len() {
+	    return Err(format!("The saved table has {} seats but allows {} players.", snapshot.players.len(), table.players.len()));
+	}
+	table.players = snapshot.players;
+	for player in table.players.iter_mut().flatten() {
+	    // no hand is in progress after a restart
+	    player.is_active = false;
+	    player.hole_cards.clear();
+	}
+	table.player_ids_to_configs = snapshot
+	    .player_configs
+	    .into_iter()
+	    .map(|config| (config.id, config))
+	    .collect();
+	table.button_idx = snapshot.button_idx;
+	table.hand_num = snapshot.hand_num;
+	table.min_players_to_start = snapshot.min_players_to_start;
+	table.ante_mode = snapshot.ante_mode;
+	table.ante_order = snapshot.ante_order;
+	table.blind_schedule = snapshot.blind_schedule;
+	table.small_blind_idx = snapshot.small_blind_idx;
+	table.big_blind_idx = snapshot.big_blind_idx;
+	table.min_raise_rule = snapshot.min_raise_rule;
+	table.betting_structure = snapshot.betting_structure;
+	table.max_straddles = snapshot.max_straddles;
+	table.variant = snapshot.variant;
+	if table.variant.hand_rules() == HandRules::ShortDeck {
+	    table.deck = Box::new(StandardDeck::short());
+	}
+	table.bad_beat_threshold = snapshot.bad_beat_threshold;
+	table.action_timeout = snapshot.action_timeout;
+	table.clock_scaling = snapshot.clock_scaling;
+	table.clocks_frozen = snapshot.clocks_frozen;
+	table.time_bank = snapshot.time_bank;
+	table.time_bank_replenish = snapshot.time_bank_replenish;
+	table.finish_delay = snapshot.finish_delay;
+	table.max_finish_delay = snapshot.max_finish_delay;
+	table.finish_delay_pot_scaling = snapshot.finish_delay_pot_scaling;
+	table.allow_chop = snapshot.allow_chop;
+	table.run_it_twice = snapshot.run_it_twice;
+	table.show_runout_equities = snapshot.show_runout_equities;
+	table.all_in_reveal_order = snapshot.all_in_reveal_order;
+	table.all_in_reveal_delay = snapshot.all_in_reveal_delay;
+	table.anonymous = snapshot.anonymous;
+	table.allow_rebuys = snapshot.allow_rebuys;
+	table.max_stack = snapshot.max_stack;
+	table.min_stack_to_play = snapshot.min_stack_to_play;
+	table.rake_percent = snapshot.rake_percent;
+	table.rake_cap = snapshot.rake_cap;
+	table.total_raked = snapshot.total_raked;
+	table.rebuys_at_orbit_start = snapshot.rebuys_at_orbit_start;
+	table.remove_busted = snapshot.remove_busted;
+	table.rematch_window = snapshot.rematch_window;
+	table.muted_ids = snapshot.muted_ids;
+	table.check_it_down = snapshot.check_it_down;
+	table.blind_config = snapshot.blind_config;
+	table.reshuffle_policy = snapshot.reshuffle_policy;
+	table.exposure_policy = snapshot.exposure_policy;
+	table.expose_bot_decisions = snapshot.expose_bot_decisions;
+	table.audit_deck = snapshot.audit_deck;
+	table.commit_shuffle = snapshot.commit_shuffle;
+	table.show_deck_count = snapshot.show_deck_count;
+	Ok(table)
     }
 
     fn send_game_state(&self, gamehand_opt: Option<&GameHand>, game_suspended: bool) {
-	let mut state_message = self.get_game_state_json(gamehand_opt, game_suspended);
+	let mut state = self.game_state(gamehand_opt, game_suspended);
+	// the event subscribers aren't at the table, so they don't get its password
+	let mut public_state = state.clone();
+	public_state.password = None;
+	self.publish_event(&public_state.to_json());
+	let board = gamehand_opt.map(|gamehand| gamehand.board()).unwrap_or_default();
 	// go through each player, and update the personal information for their message
-	// (i.e. hole cards, player index)
+	// (i.e. hole cards, what they have made with the board, player index)
         for (i, player_spot) in self.players.iter().enumerate() {
             if let Some(player) = player_spot {
-		state_message["your_index"] = i.into();
-		if player.hole_cards.len() == 2 {
-		    state_message["hole_cards"] = format!("{}{}",
-							  player.hole_cards[0],
-							  player.hole_cards[1])
-			.into();
-		} else {
-		    state_message["hole_cards"] = json::Null;
-		}
-		
+		self.personalize_game_state(&mut state, i, player, &board);
 		PlayerConfig::send_specific_message(
-		    &state_message.dump(),
+		    &serde_json::to_string(&state).unwrap(),
 		    player.id,
                     &self.player_ids_to_configs,
 		);
 		
             }
 	}
+	// spectators get the public state, never anybody's hole cards
+	state.your_index = None;
+	state.hole_cards = None;
+	state.made_hand = None;
+	PlayerConfig::send_group_message(&serde_json::to_string(&state).unwrap(), &self.spectators);
     }
-    
-    /// returns the game state as a json-String, for sending to the front-end
-    fn get_game_state_json(
-	&self,
-	gamehand_opt: Option<&GameHand>,
-	game_suspended: bool,
-    ) -> json::JsonValue {
-        let mut state_message = object! {
-            msg_type: "game_state".to_owned(),
-            name: self.name.to_owned(),
-            max_players: self.max_players,
-            small_blind: self.small_blind,
-            big_blind: self.big_blind,
-            buy_in: self.buy_in,
-            password: self.password.to_owned(),	    
-            button_idx: self.button_idx,
-            hand_num: self.hand_num,
-	    game_suspended: game_suspended,
+
+    /// send the game state to just this one player (or spectator), e.g. to catch them up
+    /// on the board, the pots and whose turn it is as soon as they reconnect
+    fn send_game_state_to(&self, gamehand_opt: Option<&GameHand>, id: Uuid) {
+	let mut state = self.game_state(gamehand_opt, false);
+	let board = gamehand_opt.map(|gamehand| gamehand.board()).unwrap_or_default();
+	let seated = self
+	    .players
+	    .iter()
+	    .enumerate()
+	    .find_map(|(i, player_spot)| player_spot.as_ref().filter(|player| player.id == id).map(|player| (i, player)));
+	if let Some((i, player)) = seated {
+	    self.personalize_game_state(&mut state, i, player, &board);
+	    PlayerConfig::send_specific_message(&serde_json::to_string(&state).unwrap(), id, &self.player_ids_to_configs);
+	} else {
+	    PlayerConfig::send_specific_message(&serde_json::to_string(&state).unwrap(), id, &self.spectators);
+	}
+    }
+
+    /// the game state only shows where the hand is now, so someone who joins or reconnects mid-hand
+    /// is also sent the last few actions that got it there
+    fn send_recent_actions(&self, gamehand_opt: Option<&GameHand>, id: Uuid) {
+	let Some(gamehand) = gamehand_opt else {
+	    return;
+	};
+	let message = object! {
+	    msg_type: "recent_actions".to_owned(),
+	    actions: gamehand.recent_actions_json(RECENT_ACTIONS),
+	};
+	PlayerConfig::send_specific_message(&message.dump(), id, &self.player_ids_to_configs);
+	PlayerConfig::send_specific_message(&message.dump(), id, &self.spectators);
+    }
+
+    /// fill in what is personal to the player in this seat: their index, hole cards, and what they have made with the board
+    fn personalize_game_state(&self, state: &mut GameStateDto, i: usize, player: &Player, board: &[Card]) {
+	state.your_index = Some(i);
+	state.hole_cards = if player.hole_cards.is_empty() {
+	    None
+	} else {
+	    Some(player.hole_cards_string())
 	};
+	state.made_hand = hand_eval::made_hand(self.variant, &player.hole_cards, board)
+	    .map(|ranking| format!("{:?}", ranking));
+    }
+    
+    /// publish a public message (i.e. nothing private like hole cards) to the hub,
+    /// so that it can be relayed to any external event subscribers.
+    /// Nothing from a private table is published, since anyone can subscribe to the events
+    fn publish_event(&self, message: &json::JsonValue) {
+	self.hand_events.borrow_mut().push(message.clone());
+	if self.password.is_some() {
+	    return;
+	}
+        if let Some(hub_addr) = &self.hub_addr {
+            hub_addr.do_send(GameEvent {
+                table_name: self.name.clone(),
+                event: message.dump(),
+            });
+        }
+    }
 
+    /// the public game state for sending to the front-end, without anything personal to the player it is sent to
+    fn game_state(&self, gamehand_opt: Option<&GameHand>, game_suspended: bool) -> GameStateDto {
 	// add a list of player infos
 	let mut player_infos = vec![];
         for (i, player_spot) in self.players.iter().enumerate() {
             // display the play positions for the front end to consume
             if let Some(player) = player_spot {
-		if !self.player_ids_to_configs.contains_key(&player.id) {
+		let config = match self.player_ids_to_configs.get(&player.id) {
+		    Some(config) => config,
 		    // be safe, double check if config still exists
-		    continue;
-		}
-                let config = self.player_ids_to_configs.get(&player.id).unwrap();		
-                let mut player_info = object! {
+		    None => continue,
+		};
+                let mut player_info = PlayerInfoDto {
                     index: i,
+                    // a player without a name is still shown, by their seat
+                    player_name: self.display_name(player.id, config.name.clone()).unwrap_or_else(|| Table::seat_label(i)),
+                    money: player.money,
+                    is_active: player.is_active,
+                    is_sitting_out: player.is_sitting_out,
+                    is_all_in: player.is_all_in(),
+                    last_action: player.last_action.map(|last_action| last_action.to_string()),
+                    preflop_cont: None,
+                    flop_cont: None,
+                    turn_cont: None,
+                    river_cont: None,
+                    seventh_cont: None,
+                    invested: None,
+                    up_cards: None,
                 };
-                let name = config.name.as_ref().unwrap().clone();
-                player_info["player_name"] = name.into();
-                player_info["money"] = player.money.into();
-                player_info["is_active"] = player.is_active.into();
-		if player.is_sitting_out {
-                    player_info["is_sitting_out"] = true.into();		    
-		}
-		if player.is_all_in() {
-                    player_info["is_all_in"] = true.into();
-		}
-		if let Some(last_action) = player.last_action {
-                    player_info["last_action"] = last_action.to_string().into();
-		}
 		if let Some(gamehand) = gamehand_opt {
 		    for (street, contributions) in gamehand.street_contributions.iter() {
 			match street {			    
-			    Street::Preflop => {player_info["preflop_cont"] = contributions[i].into()}
-			    Street::Flop => {player_info["flop_cont"] = contributions[i].into()}
-			    Street::Turn => {player_info["turn_cont"] = contributions[i].into()}
-			    Street::River => {player_info["river_cont"] = contributions[i].into()}
+			    Street::Preflop => player_info.preflop_cont = Some(contributions[i]),
+			    Street::Flop => player_info.flop_cont = Some(contributions[i]),
+			    Street::Turn => player_info.turn_cont = Some(contributions[i]),
+			    Street::River => player_info.river_cont = Some(contributions[i]),
+			    Street::Seventh => player_info.seventh_cont = Some(contributions[i]),
 			    Street::ShowDown => (),
 			}
 		    }
+		    player_info.invested = Some(gamehand.invested_this_hand(i));
+		    if !gamehand.variant.has_board() && player.is_active {
+			// in Stud, everyone sees the face up cards of each player still in the hand
+			let up_cards = player.up_cards(gamehand.variant);
+			player_info.up_cards = Some(up_cards.iter().map(|card| card.to_string()).collect());
+		    }
 		}
 		player_infos.push(Some(player_info));
             } else {
 		player_infos.push(None);
 	    }
         }
-	state_message["players"] = player_infos.into();
 
-	if let Some(gamehand) = gamehand_opt {
-	    state_message["street"] = gamehand.street.to_string().into();
-	    state_message["current_bet"] = gamehand.current_bet.into();
-	    
-	    if let Some(flop) = &gamehand.flop {
-		state_message["flop"] = format!(
-		    "{}{}{}",
-		    flop[0],
-		    flop[1],
-		    flop[2]
-		)
-		    .into();
-	    }
-	    if let Some(turn) = &gamehand.turn {
-		state_message["turn"] = format!("{}", turn).into();
-            }	
-	    if let Some(river) = &gamehand.river {
-            state_message["river"] = format!("{}", river).into();
-            }
-            state_message["pots"] = gamehand.pot_repr().into();
-
-	    if let Some(index_to_act) = gamehand.index_to_act {
-		state_message["index_to_act"] = index_to_act.into();
-	    }
+        GameStateDto {
+            msg_type: "game_state".to_owned(),
+            name: self.name.to_owned(),
+            max_players: self.max_players,
+            small_blind: self.small_blind,
+            big_blind: self.big_blind,
+            ante_mode: self.ante_mode.to_string(),
+            betting_structure: self.betting_structure.to_string(),
+            variant: self.variant.to_string(),
+            buy_in: self.buy_in,
+            password: self.password.to_owned(),	    
+            button_idx: self.button_idx,
+            hand_num: self.hand_num,
+	    game_suspended,
+	    clocks_frozen: self.clocks_frozen,
+	    deck_remaining: if self.show_deck_count { Some(self.deck.remaining()) } else { None },
+	    blind_level: self.blind_schedule.as_ref().map(|schedule| schedule.current_level()),
+	    level_time_remaining: self.blind_schedule.as_ref().map(|schedule| {
+		schedule
+		    .time_remaining(time::Instant::now())
+		    .map(|remaining| remaining.as_secs())
+	    }),
+	    players: player_infos,
+	    available_seats: self.available_seats(),
+	    street: gamehand_opt.map(|gamehand| gamehand.street.to_string()),
+	    current_bet: gamehand_opt.map(|gamehand| gamehand.current_bet),
+	    flop: gamehand_opt
+		.and_then(|gamehand| gamehand.flop.as_ref())
+		.map(|flop| format!("{}{}{}", flop[0], flop[1], flop[2])),
+	    turn: gamehand_opt.and_then(|gamehand| gamehand.turn).map(|turn| turn.to_string()),
+	    river: gamehand_opt.and_then(|gamehand| gamehand.river).map(|river| river.to_string()),
+	    pots: gamehand_opt.map(|gamehand| gamehand.pot_repr()),
+	    total_pot: gamehand_opt.map(|gamehand| gamehand.total_pot()),
+	    index_to_act: gamehand_opt.and_then(|gamehand| gamehand.index_to_act),
+	    your_index: None,
+	    hole_cards: None,
+	    made_hand: None,
 	}
-
-	state_message
     }
 	
-    /// add a given playerconfig to an empty seat
+    /// add a given playerconfig to an empty seat, or to the seat they chose if it is open
     /// if the game requires a password, then a matching password must be provided for the user to be added
-    /// TODO: eventually we wanmt the player to select an open seat I guess
     /// returns the index of the seat that they joined (if they were able to join)
     fn add_human(
         &mut self,
         player_config: PlayerConfig,
         password: Option<String>,
+        seat_index: Option<usize>,
     ) -> Result<usize, JoinTableError> {
+        self.check_password(password)?;
+        let id = player_config.id; // copy so that we can send the messsage later
+        let mut new_player = Player::new(id, true, self.buy_in);
+        new_player.time_bank_seconds = self.time_bank;
+        self.add_player(player_config, new_player, seat_index)
+    }
+
+    /// if the game requires a password, the given one must match it
+    fn check_password(&self, password: Option<String>) -> Result<(), JoinTableError> {
         if let Some(game_password) = &self.password {
             if let Some(given_password) = password {
                 if game_password.ne(&given_password) {
@@ -233,22 +761,46 @@ impl Table {
                 return Err(JoinTableError::MissingPassword);
             }
         }
-        let id = player_config.id; // copy so that we can send the messsage later
-        let new_player = Player::new(id, true, self.buy_in);
-        let result = self.add_player(player_config, new_player);
-        result
+        Ok(())
+    }
+
+    /// watch the table without taking a seat. A private table still needs its password
+    fn add_spectator(
+        &mut self,
+        player_config: PlayerConfig,
+        password: Option<String>,
+    ) -> Result<(), JoinTableError> {
+        self.check_password(password)?;
+        self.spectators.insert(player_config.id, player_config);
+        Ok(())
     }
 
     pub fn add_bot(&mut self, name: String) -> Result<usize, JoinTableError> {
         let new_bot = Player::new_bot(self.buy_in);
         let new_config = PlayerConfig::new(new_bot.id, Some(name), None);
-        self.add_player(new_config, new_bot)
+        self.add_player(new_config, new_bot, None)
+    }
+
+    /// the indices of the empty seats that someone could sit in,
+    /// which is none of them if we already have max_players
+    fn available_seats(&self) -> Vec<usize> {
+        if self.players.iter().flatten().count() >= self.max_players.into() {
+            return vec![];
+        }
+        self.players
+            .iter()
+            .enumerate()
+            .take(self.max_players.into())
+            .filter(|(_, player_spot)| player_spot.is_none())
+            .map(|(i, _)| i)
+            .collect()
     }
 
     fn add_player(
         &mut self,
         player_config: PlayerConfig,
         player: Player,
+        seat_index: Option<usize>,
     ) -> Result<usize, JoinTableError> {
         // Kinda weird, but first check if the player is already at the table
         // Could happen if their Leave wasn't completed yet
@@ -256,7 +808,7 @@ impl Table {
         for (i, player_spot) in self.players.iter_mut().enumerate() {
             if let Some(existing) = player_spot {
                 if existing.id == player.id {
-                    println!("the player was ALREADY at the table!");
+                    log::debug!("the player was ALREADY at the table!");
                     self.player_ids_to_configs
                         .insert(player_config.id, player_config);
                     return Ok(i);
@@ -269,6 +821,19 @@ impl Table {
             return Err(JoinTableError::GameIsFull);
         }
 
+        if let Some(index) = seat_index {
+            if index >= self.max_players.into() {
+                return Err(JoinTableError::InvalidSeat(index));
+            }
+            if !self.available_seats().contains(&index) {
+                return Err(JoinTableError::SeatTaken(index));
+            }
+            self.players[index] = Some(player);
+            self.player_ids_to_configs
+                .insert(player_config.id, player_config);
+            return Ok(index);
+        }
+
         for (i, player_spot) in self.players.iter_mut().enumerate() {
             if player_spot.is_none() {
                 *player_spot = Some(player);
@@ -285,8 +850,23 @@ impl Table {
     /// we tell the hub (via a Returned message), and then removethe config from
     /// self.player_ids_to_configs
     fn handle_player_heart_beats(&mut self) {
-	for (_uuid, config) in self.player_ids_to_configs.iter() {
-	    if !config.has_active_heart_beat() {
+	// in a tournament an absent player's chips stay in play, so rather than being removed,
+	// they are sat out and keep posting their blinds and antes until they bust
+	let mut blinding_off = HashSet::new();
+	if self.remove_busted {
+	    for player in self.players.iter_mut().flatten() {
+		let absent = self
+		    .player_ids_to_configs
+		    .get(&player.id)
+		    .is_some_and(|config| !config.has_active_heart_beat());
+		if absent && player.money > 0 {
+		    player.is_sitting_out = true;
+		    blinding_off.insert(player.id);
+		}
+	    }
+	}
+	for (uuid, config) in self.player_ids_to_configs.iter() {
+	    if !config.has_active_heart_beat() && !blinding_off.contains(uuid) {
                 if let Some(hub_addr) = &self.hub_addr {
                     // tell the hub that we left
                     let cloned_config = config.clone(); // clone to send back to the hub
@@ -297,12 +877,32 @@ impl Table {
                 }
 	    }
 	}
+	for config in self.spectators.values() {
+	    if !config.has_active_heart_beat() {
+                if let Some(hub_addr) = &self.hub_addr {
+                    hub_addr.do_send(Returned {
+                        config: config.clone(),
+                        reason: ReturnedReason::HeartBeatFailed,
+                    });
+                }
+	    }
+	}
+	self.spectators.retain(|_uuid, config| config.has_active_heart_beat());
+	let failed: Vec<(Uuid, Option<String>)> = self
+	    .player_ids_to_configs
+	    .values()
+	    .filter(|config| !config.has_active_heart_beat() && !blinding_off.contains(&config.id))
+	    .map(|config| (config.id, config.name.clone()))
+	    .collect();
+	for (id, name) in failed {
+	    self.record_departure(id, name);
+	}
 	// now remove the configs that failed the heart beat
 	// They is probably a better way to code this method, but this works for now
-        self.player_ids_to_configs.retain(|_uuid, config| {
+        self.player_ids_to_configs.retain(|uuid, config| {
             // if a player config has no active heartbeat (i.e. has not done anything in a long time)
             // then we remove their config               
-            config.has_active_heart_beat()
+            config.has_active_heart_beat() || blinding_off.contains(uuid)
         });
     }
     pub fn play(
@@ -312,18 +912,22 @@ impl Table {
         hand_limit: Option<u32>, // how many hands total should be play? None == no limit
     ) {
         let mut non_human_hands = 0; // we only allow a certain number of hands without a human before ending
+        let mut closing_announced_for = None; // the hand we last warned about closing before, to warn only once per hand
         loop {
 	    let between_hands = true;
 
 	    ////
-	    self.handle_meta_actions(&incoming_meta_actions, between_hands, None);
+	    self.handle_meta_actions(incoming_meta_actions, between_hands, None);
 	    self.handle_player_heart_beats();
+	    if self.remove_busted {
+		self.remove_busted_players();
+	    }
             // check if any player left with a meta action or timed out due to heart beat.                 
             // if so, their config will be gone, so now remove the player struct as well.
             for player_spot in self.players.iter_mut() {
                 if let Some(player) = player_spot {
                     if !self.player_ids_to_configs.contains_key(&player.id) {
-                        println!("player is no longer in the config");
+                        log::debug!("player is no longer in the config");
                         *player_spot = None;
 			
                     }
@@ -332,12 +936,26 @@ impl Table {
  	    
             if let Some(limit) = hand_limit {
                 if self.hand_num > limit {
-                    println!("hand limit has been reached");
+                    log::info!("hand limit has been reached");
                     break;
                 }
             }
-            println!(
-                "\n\n\nPlaying hand {}, button_idx = {}",
+	    let time_remaining = self
+		.closing_at
+		.map(|closing_at| closing_at.saturating_duration_since(time::Instant::now()));
+	    if time_remaining.is_some_and(|remaining| remaining.is_zero()) {
+		log::info!("the table has reached its closing time");
+		break;
+	    }
+	    let hands_remaining = hand_limit.map(|limit| limit + 1 - self.hand_num);
+	    let closing_soon = time_remaining.is_some_and(|remaining| remaining <= CLOSING_WARNING)
+		|| hands_remaining.is_some_and(|hands| hands <= CLOSING_WARNING_HANDS);
+	    if closing_soon && closing_announced_for != Some(self.hand_num) {
+		self.announce_closing(time_remaining, hands_remaining);
+		closing_announced_for = Some(self.hand_num);
+	    }
+            log::info!(
+                "Playing hand {}, button_idx = {}",
                 self.hand_num, self.button_idx
             );	    
             let num_human_players = self
@@ -346,66 +964,382 @@ impl Table {
                 .flatten()
                 .filter(|player| player.human_controlled)
                 .count();
-            println!("num human players == {:?}", num_human_players);
-            println!("non human hands == {:?}", non_human_hands);
+            log::debug!("num human players == {:?}", num_human_players);
+            log::debug!("non human hands == {:?}", non_human_hands);
 	    
             if num_human_players == 0 {
                 non_human_hands += 1;
-                println!("num human players == {:?}", num_human_players);
-                println!("non human hands == {:?}", non_human_hands);
+                log::debug!("num human players == {:?}", num_human_players);
+                log::debug!("non human hands == {:?}", non_human_hands);
             }
             if non_human_hands > NON_HUMAN_HANDS_LIMIT {
                 // the table ends no matter what if we haven't had a human after too many turns
                 break;
             }
 
-	    let was_played = self.play_one_hand(&incoming_actions, &incoming_meta_actions);
+	    let level_up = self
+		.blind_schedule
+		.as_mut()
+		.is_some_and(|schedule| schedule.advance_if_due(time::Instant::now()));
+	    if level_up {
+		self.apply_blind_level();
+	    }
+
+	    self.sit_out_short_stacks();
+	    let was_played = self.play_one_hand(incoming_actions, incoming_meta_actions);
 	    if was_played {
+		if !self.rebuys_at_orbit_start {
+		    // top up before the button moves, so that a busted player who rebuys keeps their place
+		    self.auto_rebuy();
+		}
 		// only increment the hand num and find a new button if we indeed played a hand.
 		// if there are not enough players and/or active players, a hand is not dealt/played
 		self.hand_num += 1;
 		
+		if self.is_heads_up_match_over() {
+		    self.offer_rematch(incoming_meta_actions);
+		}
+
 		// attempt to set the next button
-		self.button_idx = self
-		    .find_next_button()
-		    .expect("we could not find a valid button index!");
+		let previous_button_idx = self.button_idx;
+		self.move_button();
+		if self.button_idx <= previous_button_idx {
+		    // the button went all the way around, so that is an orbit
+		    self.replenish_time_banks();
+		    if self.rebuys_at_orbit_start {
+			self.auto_rebuy();
+		    }
+		}
+		if let Some(path) = &self.save_path {
+		    if let Err(e) = self.save(path) {
+			log::warn!("unable to save table {}: {}", self.name, e);
+		    }
+		}
             }
 	    
             // wait for next hand
 	    // this is especially needed when there is only one player at the table
-            let wait_duration = time::Duration::from_secs(1);
-            thread::sleep(wait_duration);
+            let wait_duration = self.pace(time::Duration::from_secs(1));
+            self.pause(wait_duration, incoming_meta_actions, between_hands, None);
 	    
         }
-        println!("about to send the gameover signal to the hub");
+        log::info!("about to send the gameover signal to the hub");
+	if let Some(path) = &self.save_path {
+	    // the table is over, so there is nothing to restore
+	    let _ = fs::remove_file(path);
+	}
+	let reconciliation_report = self.reconciliation_report();
+	PlayerConfig::send_specific_message(
+	    &reconciliation_report.dump(),
+	    self.admin_id,
+	    &self.player_ids_to_configs,
+	);
         // the game is ending, so tell that to the hub
         if let Some(hub_addr) = &self.hub_addr {
             // tell the hub that we left
             hub_addr.do_send(GameOver {
                 table_name: self.name.clone(),
+		reconciliation_report,
             });
         }
     }
 
+    /// a player shows their hole cards to one other player (or spectator), without the rest of the table seeing
+    fn show_to_player(&self, from_id: Uuid, to_id: Uuid) {
+	let from = self
+	    .players
+	    .iter()
+	    .enumerate()
+	    .find_map(|(i, spot)| spot.as_ref().filter(|player| player.id == from_id).map(|player| (i, player)));
+	let Some((index, player)) = from.filter(|(_, player)| !player.hole_cards.is_empty()) else {
+	    let message = object! {
+		msg_type: "error".to_owned(),
+		error: "no_hole_cards".to_owned(),
+		reason: "You don't have any cards to show.".to_owned(),
+	    };
+	    PlayerConfig::send_specific_message(&message.dump(), from_id, &self.player_ids_to_configs);
+	    return;
+	};
+	if from_id == to_id || !(self.player_ids_to_configs.contains_key(&to_id) || self.spectators.contains_key(&to_id)) {
+	    let message = object! {
+		msg_type: "error".to_owned(),
+		error: "invalid_player".to_owned(),
+		reason: "There is no one else at the table with that id.".to_owned(),
+	    };
+	    PlayerConfig::send_specific_message(&message.dump(), from_id, &self.player_ids_to_configs);
+	    return;
+	}
+	let message = object! {
+	    msg_type: "shown_cards".to_owned(),
+	    index: index,
+	    player_name: self.display_name(from_id, self.player_ids_to_configs.get(&from_id).and_then(|config| config.name.clone())),
+	    hole_cards: player.hole_cards_string(),
+	};
+	PlayerConfig::send_specific_message(&message.dump(), to_id, &self.player_ids_to_configs);
+	PlayerConfig::send_specific_message(&message.dump(), to_id, &self.spectators);
+    }
+
+    /// how an anonymous table shows the player in this seat, counting from 1
+    fn seat_label(seat: usize) -> String {
+	format!("Seat {}", seat + 1)
+    }
+
+    /// the name the table is shown for a player or spectator. At an anonymous table that is their seat,
+    /// while their real name stays in their config for the admin's reports
+    fn display_name(&self, id: Uuid, name: Option<String>) -> Option<String> {
+	if !self.anonymous {
+	    return name;
+	}
+	match self.players.iter().position(|spot| spot.as_ref().is_some_and(|player| player.id == id)) {
+	    Some(seat) => Some(Table::seat_label(seat)),
+	    None => Some("Spectator".to_owned()),
+	}
+    }
+
+    /// everyone connected to the table, with their role (admin, player, or spectator),
+    /// their seat (if they have one), and their status (active, sitting out, or away)
+    fn roster(&self) -> json::JsonValue {
+	let mut entries = vec![];
+	for (id, config) in self.player_ids_to_configs.iter().chain(self.spectators.iter()) {
+	    let seat = self
+		.players
+		.iter()
+		.position(|spot| spot.as_ref().is_some_and(|player| player.id == *id));
+	    let player = seat.and_then(|index| self.players[index].as_ref());
+	    let role = if *id == self.admin_id {
+		"admin"
+	    } else if seat.is_some() {
+		"player"
+	    } else {
+		"spectator"
+	    };
+	    let status = match player {
+		Some(player) if player.is_sitting_out => "sitting_out",
+		// a human without an address has lost their connection
+		Some(player) if player.human_controlled && config.player_addr.is_none() => "away",
+		None if config.player_addr.is_none() => "away",
+		_ => "active",
+	    };
+	    entries.push(object! {
+		player_name: self.display_name(*id, config.name.clone()),
+		role: role,
+		index: seat,
+		status: status,
+	    });
+	}
+	object! {
+	    msg_type: "roster".to_owned(),
+	    roster: entries,
+	}
+    }
+
+    /// each player's total buy-ins versus their final stack, so the host can settle up.
+    /// Includes the players who left along the way
+    fn reconciliation_report(&self) -> json::JsonValue {
+	let mut entries = self.departed_results.clone();
+	for player in self.players.iter().flatten() {
+	    // a player without a config has left, so is already in the departed results
+	    if let Some(config) = self.player_ids_to_configs.get(&player.id) {
+		entries.push(player.reconciliation_entry(config.name.clone()));
+	    }
+	}
+	object! {
+	    msg_type: "reconciliation_report".to_owned(),
+	    players: entries,
+	}
+    }
+
+    /// let everyone know who ran out of chips and the place they finished in, and send them back to the hub.
+    /// Like a player leaving, their config is removed here, and their seat is freed with the others
+    fn remove_busted_players(&mut self) {
+	let busted: Vec<(usize, Uuid)> = self
+	    .players
+	    .iter()
+	    .enumerate()
+	    .filter_map(|(i, spot)| spot.as_ref().map(|player| (i, player)))
+	    .filter(|(_, player)| player.money == 0 && self.player_ids_to_configs.contains_key(&player.id))
+	    .map(|(i, player)| (i, player.id))
+	    .collect();
+	// everyone who still has chips finishes ahead, and players who bust on the same hand share a place
+	let num_remaining = self.players.iter().flatten().filter(|player| player.money > 0).count();
+	let finishing_place = num_remaining + busted.len();
+	for (i, id) in busted {
+	    let name = self.player_ids_to_configs.get(&id).and_then(|config| config.name.clone());
+	    let message = object! {
+		msg_type: "player_busted".to_owned(),
+		index: i,
+		player_name: self.display_name(id, name.clone()),
+		finishing_place: finishing_place,
+	    };
+            PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	    self.publish_event(&message);
+	    self.record_departure(id, name);
+	    if let Some(entry) = self.departed_results.last_mut() {
+		entry["finishing_place"] = finishing_place.into();
+	    }
+	    let config = self.player_ids_to_configs.remove(&id).unwrap();
+	    let human = self.players[i].as_ref().is_some_and(|player| player.human_controlled);
+	    if let Some(hub_addr) = &self.hub_addr {
+		if human {
+		    // a bot has nowhere to go back to
+		    hub_addr.do_send(Returned {
+			config,
+			reason: ReturnedReason::Busted,
+		    });
+		}
+	    }
+	}
+    }
+
+    /// bring any player with auto rebuy on who is below their target (the buy-in by default) back up to it,
+    /// and let everyone know how much they added. A target can't be more than the table's max stack
+    fn auto_rebuy(&mut self) {
+	if !self.allow_rebuys {
+	    return;
+	}
+	let max_stack = cmp::max(self.buy_in, self.max_stack.unwrap_or(self.buy_in));
+	let mut rebuys = vec![];
+	for (i, player_spot) in self.players.iter_mut().enumerate() {
+	    let player = match player_spot {
+		Some(player) if player.auto_rebuy => player,
+		_ => continue,
+	    };
+	    let target = cmp::min(player.rebuy_target.unwrap_or(self.buy_in), max_stack);
+	    if player.money >= target {
+		continue;
+	    }
+	    let amount = target - player.money;
+	    player.money += amount;
+	    player.stats.total_buy_in += amount;
+	    rebuys.push((i, player.id, amount));
+	}
+	for (i, id, amount) in rebuys {
+	    let message = object! {
+		msg_type: "rebuy".to_owned(),
+		index: i,
+		player_name: self.display_name(id, self.player_ids_to_configs.get(&id).and_then(|config| config.name.clone())),
+		amount: amount,
+	    };
+            PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	    self.publish_event(&message);
+	}
+    }
+
+    /// before a deal, sit out anyone whose stack is below the table minimum, and prompt them to rebuy.
+    /// Bots have nobody to rebuy for them, so they play on
+    fn sit_out_short_stacks(&mut self) {
+	let mut short = vec![];
+	for player in self.players.iter_mut().flatten() {
+	    if player.human_controlled && !player.is_sitting_out && player.money < self.min_stack_to_play {
+		player.is_sitting_out = true;
+		short.push((player.id, player.money));
+	    }
+	}
+	for (id, money) in short {
+	    let message = object! {
+		msg_type: "below_min_stack".to_owned(),
+		money: money,
+		min_stack_to_play: self.min_stack_to_play,
+		text: format!(
+		    "Your stack of {} is below the table minimum of {}, so you are sitting out until you rebuy and come back",
+		    money, self.min_stack_to_play
+		),
+	    };
+	    PlayerConfig::send_specific_message(&message.dump(), id, &self.player_ids_to_configs);
+	}
+    }
+
+    /// every orbit, each player gets some more time in their time bank
+    fn replenish_time_banks(&mut self) {
+	for player in self.players.iter_mut().flatten() {
+	    // the admin can set any amount, so a huge one tops out rather than overflowing
+	    player.time_bank_seconds = player.time_bank_seconds.saturating_add(self.time_bank_replenish);
+	}
+    }
+
+    /// remember how a departing player did, for the reconciliation report
+    fn record_departure(&mut self, id: Uuid, name: Option<String>) {
+	if let Some(player) = self.players.iter().flatten().find(|player| player.id == id) {
+	    self.departed_results.push(player.reconciliation_entry(name));
+	}
+    }
+
+    /// set the button and blinds for the next hand.
+    /// With the dead button rule, the big blind moves forward exactly one funded seat,
+    /// last hand's big blind seat posts the small blind (which is dead if that player is gone),
+    /// and the button goes to last hand's small blind seat (even if it is now empty).
+    /// Heads-up, the button simply moves to the next player
+    fn move_button(&mut self) {
+	if let (Some(small_blind_idx), Some(big_blind_idx)) = (self.small_blind_idx, self.big_blind_idx) {
+	    let num_funded = self.players.iter().flatten().filter(|player| player.money > 0).count();
+	    if num_funded >= 3 {
+		let next_big_blind_idx = self
+		    .next_funded_seat(big_blind_idx)
+		    .expect("there are funded players, so there is a next big blind");
+		self.button_idx = small_blind_idx;
+		self.small_blind_idx = Some(big_blind_idx);
+		self.big_blind_idx = Some(next_big_blind_idx);
+		return;
+	    }
+	}
+	self.small_blind_idx = None;
+	self.big_blind_idx = None;
+	self.button_idx = self
+	    .find_next_button()
+	    .expect("we could not find a valid button index!");
+    }
+
+    /// the next seat after the given index with a player who has money to post a blind
+    fn next_funded_seat(&self, index: usize) -> Option<usize> {
+	(index + 1..self.players.len())
+	    .chain(0..index + 1)
+	    .find(|&i| self.players[i].as_ref().is_some_and(|player| player.money > 0))
+    }
+
+    /// at the start of a hand, work out which seats post the blinds.
+    /// The dead button rule only applies with three or more players and both blinds.
+    /// If there are no blind seats from the last hand to move forward, they go after the button
+    fn assign_blinds(&mut self, gamehand: &mut GameHand) {
+	let blinds = self.blind_config.blinds_for(gamehand.num_players_dealt);
+	if !gamehand.variant.has_board() || gamehand.num_players_dealt < 3 || blinds != [Blind::Small, Blind::Big] {
+	    self.small_blind_idx = None;
+	    self.big_blind_idx = None;
+	    return;
+	}
+	if self.small_blind_idx.is_none() || self.big_blind_idx.is_none() {
+	    let small_blind_idx = self
+		.next_funded_seat(self.button_idx)
+		.expect("there are enough players for a small blind");
+	    self.small_blind_idx = Some(small_blind_idx);
+	    self.big_blind_idx = self.next_funded_seat(small_blind_idx);
+	}
+	let small_blind_is_live = self.small_blind_idx.is_some_and(|index| {
+	    self.players[index].as_ref().is_some_and(|player| player.is_active)
+	});
+	if !small_blind_is_live {
+	    // a dead small blind: nobody posts it, and the big blind is next
+	    log::debug!("the small blind is dead this hand");
+	    gamehand.num_blinds_posted = 1;
+	}
+    }
+
     /// move the button to the next Player who is not sitting out
     /// if non can be found, then return false
     fn find_next_button(&mut self) -> Result<usize, &'static str> {
-        for i in (self.button_idx + 1..9).chain(0..self.button_idx + 1) {
-            //self.button_idx += 1;
-            //self.button_idx %= 9; // loop back to 0 if we reach the end
+        for i in (self.button_idx + 1..self.players.len()).chain(0..self.button_idx + 1) {
             let button_spot = &mut self.players[i];
             if let Some(button_player) = button_spot {
                 if button_player.is_sitting_out {
-                    println!(
+                    log::debug!(
                         "Player at index {} is sitting out so cannot be the button",
                         i
                     );
                 } else if button_player.money == 0 {
-                    println!("Player at index {} has no money so cannot be the button", i);
+                    log::debug!("Player at index {} has no money so cannot be the button", i);
                 } else {
                     // We found a player who is not sitting out, so it is a valid
                     // button position
-                    println!("found the button!");
+                    log::debug!("found the button!");
                     return Ok(i);
                 }
             }
@@ -425,32 +1359,57 @@ impl Table {
                 MetaAction::Chat(id, text) => {
                     // send the message to all players,
                     // appended by the player name
-                    println!("chat message inside the game hand wow!");
-		    if let Some(player_config) = self.player_ids_to_configs.get_mut(&id) {
+                    log::debug!("chat message from {:?} inside table = {:?}", id, &self.name);
+		    // spectators can chat too
+		    let name = self
+			.player_ids_to_configs
+			.get(&id)
+			.or_else(|| self.spectators.get(&id))
+			.and_then(|config| config.name.clone());
+		    let name = self.display_name(id, name);
+		    let sender = match self.player_ids_to_configs.get_mut(&id) {
+			Some(player_config) => Some(player_config),
+			None => self.spectators.get_mut(&id),
+		    };
+		    if let Some(player_config) = sender {
 			player_config.heart_beat = time::Instant::now(); // this counts as activity
-			let message = object! {
-			    msg_type: "chat".to_owned(),
-			    player_name: player_config.name.clone(),
-			    text: text,
-                        };
-			PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+			if self.muted_ids.contains(&id) {
+			    // only the sender finds out, and nobody else sees the message
+			    let message = object! {
+				msg_type: "error".to_owned(),
+				error: "muted".to_owned(),
+				reason: "You have been muted by the admin, so your chat was not sent.".to_owned(),
+			    };
+			    if let Some(addr) = &player_config.player_addr {
+				addr.do_send(WsMessage(message.dump()));
+			    }
+			} else {
+			    let message = object! {
+				msg_type: "chat".to_owned(),
+				player_name: name,
+				text: text,
+			    };
+			    PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+			    PlayerConfig::send_group_message(&message.dump(), &self.spectators);
+			}
 		    }
                 }		
-                MetaAction::Join(player_config, password) => {
+                MetaAction::Join(player_config, password, seat_index) => {
                     // add a new player to the table
                     let cloned_config = player_config.clone(); // clone in case we need to send back
-                    println!(
+                    log::info!(
                         "handling join meta action for {:?} inside table = {:?}",
                         cloned_config.id, &self.name
                     );
-                    match self.add_human(player_config, password) {
+                    match self.add_human(player_config, password, seat_index) {
                         Ok(index) => {
-                            println!("Joining table at index: {}", index);
+                            log::debug!("Joining table at index: {}", index);
 			    self.send_game_state(gamehand, false);
+			    self.send_recent_actions(gamehand, cloned_config.id);
                         }
                         Err(err) => {
                             // we were unable to add the player
-                            println!("unable to join table: {:?}", err);
+                            log::warn!("unable to join table: {:?}", err);
                             if let Some(hub_addr) = &self.hub_addr {
                                 // tell the hub that we left
                                 hub_addr.do_send(Returned {
@@ -461,19 +1420,53 @@ impl Table {
                         }
                     }
                 }
+                MetaAction::Spectate(player_config, password) => {
+                    let cloned_config = player_config.clone(); // clone in case we need to send back
+                    match self.add_spectator(player_config, password) {
+                        Ok(()) => {
+			    self.send_game_state(gamehand, false);
+			    self.send_recent_actions(gamehand, cloned_config.id);
+                        }
+                        Err(err) => {
+                            log::warn!("unable to spectate table: {:?}", err);
+                            if let Some(hub_addr) = &self.hub_addr {
+                                hub_addr.do_send(Returned {
+                                    config: cloned_config,
+                                    reason: ReturnedReason::FailureToJoin(err),
+                                });
+                            }
+                        }
+                    }
+                }
+                MetaAction::Leave(id) if self.spectators.contains_key(&id) => {
+                    // a spectator has no seat to clean up
+                    let config = self.spectators.remove(&id).unwrap();
+                    if let Some(hub_addr) = &self.hub_addr {
+                        hub_addr.do_send(Returned {
+                            config,
+                            reason: ReturnedReason::Left,
+                        });
+                    }
+                }
                 MetaAction::Leave(id) => {
-                    println!(
+                    log::info!(
                         "handling leave meta action for {:?} inside table = {:?}. between hands = {}",
                         id, &self.name, between_hands
                     );
                     if let Some(config) = self.player_ids_to_configs.remove(&id) {
+			self.record_departure(id, config.name.clone());
+			// let the player know how their session went
+			let player = self.players.iter().flatten().find(|player| player.id == id);
+			if let (Some(player), Some(addr)) = (player, &config.player_addr) {
+			    addr.do_send(WsMessage(player.session_summary().dump()));
+			}
                         // note: we don't remove the player from self.players quite yet,
                         // we use the lack of the config to indicate to the table during a street
                         // that a player has left. If they were active at the time, this information
                         // needs to be taken into account
                         let message = object! {
                             msg_type: "player_left".to_owned(),
-                            name: config.name.clone(),
+                            name: self.display_name(id, config.name.clone()),
                         };
                         PlayerConfig::send_specific_message(
                             &message.dump(),
@@ -491,7 +1484,7 @@ impl Table {
                     } else {
                         // should not normally happen, but check for Some() to be safe
                         // Perhaps if the client sent many leave messages before them being responded to
-                        println!("\n\nA leave message was received for a player that no longer has a config!")
+                        log::warn!("A leave message was received for a player that no longer has a config!")
                     }
                 }
                 MetaAction::SetPlayerName(id, new_name) => {
@@ -501,22 +1494,100 @@ impl Table {
 		    }
 		    
                 }
-                MetaAction::SendPlayerName(id) => {
-		    if let Some(player_config) = self.player_ids_to_configs.get(&id) {
-			player_config.send_player_name();
-		    }
+                MetaAction::AutoMuckDelay(id, seconds) => {
+		    let delay = cmp::min(time::Duration::from_secs(seconds.into()), MAX_AUTO_MUCK_DELAY);
+                    for player in self.players.iter_mut().flatten() {
+                        if player.id == id {
+                            log::debug!("player {} setting auto muck delay to {:?}", id, delay);
+                            player.auto_muck_delay = Some(delay);
+                        }
+                    }
                 }
-                MetaAction::UpdateAddress(id, new_addr) => {
-                    PlayerConfig::set_player_address(id, new_addr, &mut self.player_ids_to_configs);
-		    self.send_game_state(gamehand, false);		    
+                MetaAction::AutoMuck(id, auto_muck) => {
+                    for player in self.players.iter_mut().flatten() {
+                        if player.id == id {
+                            log::debug!("player {} setting auto muck to {}", id, auto_muck);
+                            player.auto_muck = auto_muck;
+                        }
+                    }
                 }
-                MetaAction::TableInfo(addr) => {
-		    println!("about to send table info to {:?}", addr);
-		    let message = object! {
-			"msg_type": "table_info".to_owned(),
+                MetaAction::AutoRebuy(id, auto_rebuy) => {
+                    for player in self.players.iter_mut().flatten() {
+                        if player.id == id {
+                            log::debug!("player {} setting auto rebuy to {}", id, auto_rebuy);
+                            player.auto_rebuy = auto_rebuy;
+                        }
+                    }
+                }
+                MetaAction::RebuyTarget(id, target) => {
+                    for player in self.players.iter_mut().flatten() {
+                        if player.id == id {
+                            log::debug!("player {} setting rebuy target to {:?}", id, target);
+                            player.rebuy_target = target;
+                        }
+                    }
+                }
+                MetaAction::QuickFold(id, quick_fold) => {
+                    for player in self.players.iter_mut().flatten() {
+                        if player.id == id {
+                            log::debug!("player {} setting quick fold to {}", id, quick_fold);
+                            player.quick_fold = quick_fold;
+                        }
+                    }
+                }
+                MetaAction::Straddle(id, straddle) => {
+                    for player in self.players.iter_mut().flatten() {
+                        if player.id == id {
+                            log::debug!("player {} setting straddle to {}", id, straddle);
+                            player.straddle = straddle;
+                        }
+                    }
+                }
+                MetaAction::ProposeChop(id) => {
+		    self.handle_chop_agreement(id, true);
+                }
+                MetaAction::AcceptChop(id) => {
+		    self.handle_chop_agreement(id, false);
+                }
+                MetaAction::Rematch(id) => {
+		    self.handle_rematch_agreement(id);
+                }
+                MetaAction::SendPlayerName(id) => {
+		    if let Some(player_config) = self.player_ids_to_configs.get(&id) {
+			player_config.send_player_name();
+		    }
+                }
+                MetaAction::ShowToPlayer(from_id, to_id) => {
+		    self.show_to_player(from_id, to_id);
+                }
+                MetaAction::Roster(id) => {
+		    PlayerConfig::send_specific_message(&self.roster().dump(), id, &self.player_ids_to_configs);
+		    PlayerConfig::send_specific_message(&self.roster().dump(), id, &self.spectators);
+                }
+                MetaAction::LastHand(id) => {
+		    // e.g. for someone who just sat down, every event of the last completed hand, in order
+		    let message = object! {
+			msg_type: "last_hand".to_owned(),
+			events: self.last_hand_events.clone(),
+		    };
+		    PlayerConfig::send_specific_message(&message.dump(), id, &self.player_ids_to_configs);
+		    PlayerConfig::send_specific_message(&message.dump(), id, &self.spectators);
+                }
+                MetaAction::UpdateAddress(id, new_addr) => {
+                    PlayerConfig::set_player_address(id, new_addr.clone(), &mut self.player_ids_to_configs);
+                    PlayerConfig::set_player_address(id, new_addr, &mut self.spectators);
+		    // only the reconnecting player missed anything, so only they need to be caught up
+		    self.send_game_state_to(gamehand, id);
+		    self.send_recent_actions(gamehand, id);
+                }
+                MetaAction::TableInfo(addr) => {
+		    log::debug!("about to send table info to {:?}", addr);
+		    let message = object! {
+			"msg_type": "table_info".to_owned(),
 			"table_name": self.name.to_owned(),
 			"small_blind": self.small_blind,
 			"big_blind": self.big_blind,
+			"ante_mode": self.ante_mode.to_string(),
 			"buy_in": self.buy_in,
 			"max_players": self.max_players,
 			"num_humans": self.players.iter().flatten().filter(|p| p.human_controlled).count(),
@@ -527,7 +1598,7 @@ impl Table {
                 MetaAction::ImBack(id) => {
                     for player in self.players.iter_mut().flatten() {
                         if player.id == id {
-                            println!("player {} being set to is_sitting_out = false", id);
+                            log::debug!("player {} being set to is_sitting_out = false", id);
                             player.is_sitting_out = false;
                         }
                     }
@@ -539,16 +1610,26 @@ impl Table {
                 MetaAction::SitOut(id) => {
                     for player in self.players.iter_mut().flatten() {
                         if player.id == id {
-                            println!("player {} being set to is_sitting_out = true", id);
+                            log::debug!("player {} being set to is_sitting_out = true", id);
                             player.is_sitting_out = true;
                         }
                     }
 		    self.send_game_state(gamehand, false);		    		    
                 }
 		MetaAction::Admin(id, admin_command) => {
-		    if !between_hands {
+		    let applies_mid_hand = matches!(
+			admin_command,
+			AdminCommand::FreezeClocks
+			    | AdminCommand::UnfreezeClocks
+			    | AdminCommand::ActionTimeout(_)
+			    | AdminCommand::Kick(_)
+			    | AdminCommand::KickSeat(_)
+			    | AdminCommand::Mute(_)
+			    | AdminCommand::Unmute(_)
+		    );
+		    if !between_hands && !applies_mid_hand {
 			// put it back on the meta actions queue to be handled only between hands
-			println!("put the admin_command back on the queue to handle between hands");
+			log::debug!("put the admin_command back on the queue to handle between hands");
 			meta_actions.push_back(MetaAction::Admin(id, admin_command));
 		    } else {
 			self.handle_admin_command(id, admin_command);
@@ -558,8 +1639,66 @@ impl Table {
         }
     }
 
+    /// reject the combinations of settings that can't be played.
+    /// This is checked when a table is created, and whenever the admin changes the stakes
+    pub fn validate_config(
+	small_blind: u32,
+	big_blind: u32,
+	ante_mode: AnteMode,
+	buy_in: u32,
+    ) -> Result<(), ConfigError> {
+	let ante = match ante_mode {
+	    AnteMode::None => 0,
+	    AnteMode::PerPlayer(ante) | AnteMode::BigBlind(ante) => ante,
+	};
+	if big_blind == 0 {
+	    Err(ConfigError::ZeroBigBlind)
+	} else if big_blind < small_blind {
+	    Err(ConfigError::BigBlindBelowSmallBlind)
+	} else if big_blind.checked_add(ante).is_none_or(|forced| forced > buy_in) {
+	    // blinds and an ante too big to even add up can't be covered by any buy-in
+	    Err(ConfigError::BlindsLargerThanBuyIn)
+	} else {
+	    Ok(())
+	}
+    }
+
+    /// would the table still be playable after the admin command?
+    /// With a blind schedule, every level has to be
+    fn check_admin_command(&self, admin_command: &AdminCommand) -> Result<(), ConfigError> {
+	let mut buy_in = self.buy_in;
+	let levels = match admin_command {
+	    AdminCommand::SmallBlind(new) => vec![(*new, self.big_blind, self.ante_mode)],
+	    AdminCommand::BigBlind(new) => vec![(self.small_blind, *new, self.ante_mode)],
+	    AdminCommand::AnteMode(new) => vec![(self.small_blind, self.big_blind, *new)],
+	    AdminCommand::SetBlindSchedule(schedule) => {
+		let mut schedule = schedule.clone();
+		schedule.start(self.ante_mode, time::Instant::now());
+		schedule
+		    .levels()
+		    .iter()
+		    .enumerate()
+		    .map(|(level, &(small_blind, big_blind, _))| (small_blind, big_blind, schedule.ante_mode(level)))
+		    .collect()
+	    }
+	    AdminCommand::BuyIn(new) => {
+		buy_in = *new;
+		vec![(self.small_blind, self.big_blind, self.ante_mode)]
+	    }
+	    AdminCommand::RakePercent(percent) if *percent > 100 => return Err(ConfigError::RakeOverWholePot),
+	    AdminCommand::MinPlayersToStart(num) if *num < 2 || *num > self.max_players.into() => {
+		return Err(ConfigError::MinPlayersToStart)
+	    }
+	    _ => return Ok(()),
+	};
+	for (small_blind, big_blind, ante_mode) in levels {
+	    Table::validate_config(small_blind, big_blind, ante_mode, buy_in)?;
+	}
+	Ok(())
+    }
+
     fn handle_admin_command(&mut self, id: Uuid, admin_command: AdminCommand) {
-	println!("handling admin_command in table: {:?}", admin_command);
+	log::info!("handling admin_command in table: {:?}", admin_command);
 	if self.admin_id != id {
 	    // the player who entered the admin command is not the table's admin!
 	    let message = object! {
@@ -589,6 +1728,20 @@ impl Table {
 	    );
 	    return;
 	}
+
+	if let Err(err) = self.check_admin_command(&admin_command) {
+	    let message = object! {
+		msg_type: "error".to_owned(),
+		error: "invalid_config".to_owned(),
+                reason: err.to_string(),
+	    };
+	    PlayerConfig::send_specific_message(
+		&message.dump(),
+		id,
+		&self.player_ids_to_configs,
+	    );
+	    return;
+	}
 	
 	let message = match admin_command {
 	    AdminCommand::SmallBlind(new) => {
@@ -607,6 +1760,38 @@ impl Table {
                     text: format!("The big blind has been changed to {}", new),
 		}
 	    }		
+	    AdminCommand::AnteMode(new) => {
+		self.ante_mode = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "ante_mode".to_owned(),
+                    text: format!("The ante has been changed to {}", new),
+		}
+	    }
+	    AdminCommand::AnteOrder(new) => {
+		self.ante_order = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "ante_order".to_owned(),
+                    text: format!("The ante is now collected {}", new.to_string().replace('_', " ")),
+		}
+	    }
+	    AdminCommand::SetBlindSchedule(mut schedule) => {
+		// the first level starts now, not when the admin typed out the schedule
+		schedule.start(self.ante_mode, time::Instant::now());
+		let (small_blind, big_blind, ante) = schedule.current();
+		let text = format!(
+		    "The blind schedule has been set, starting at {}/{} with an ante of {}",
+		    small_blind, big_blind, ante
+		);
+		self.blind_schedule = Some(schedule);
+		self.apply_blind_level();
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "blind_schedule".to_owned(),
+                    text: text,
+		}
+	    }
 	    AdminCommand::BuyIn(new) => {
 		self.buy_in = new;
 		object! {
@@ -623,6 +1808,381 @@ impl Table {
                     text: format!("The password has been changed to {}", new),
 		}
 	    }
+	    AdminCommand::MinRaiseRule(new) => {
+		self.min_raise_rule = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "min_raise_rule".to_owned(),
+                    text: format!("The minimum raise rule has been changed to {}", new),
+		}
+	    }
+	    AdminCommand::BettingStructure(new) => {
+		self.betting_structure = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "betting_structure".to_owned(),
+                    text: format!("The betting structure has been changed to {}", new),
+		}
+	    }
+	    AdminCommand::GameVariant(new) => {
+		if new.hand_rules() != self.variant.hand_rules() {
+		    // short-deck is dealt from a deck without the 2s through 5s
+		    self.deck = match new.hand_rules() {
+			HandRules::ShortDeck => Box::new(StandardDeck::short()),
+			HandRules::Standard => Box::new(StandardDeck::new()),
+		    };
+		}
+		self.variant = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "game_variant".to_owned(),
+                    text: format!("The game has been changed to {}", new),
+		}
+	    }
+	    AdminCommand::BadBeatThreshold(new) => {
+		self.bad_beat_threshold = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "bad_beat_threshold".to_owned(),
+                    text: format!("The bad beat threshold has been changed to {:?}", new),
+		}
+	    }
+	    AdminCommand::FreezeClocks | AdminCommand::UnfreezeClocks => {
+		self.clocks_frozen = matches!(admin_command, AdminCommand::FreezeClocks);
+		let frozen_message = object! {
+		    msg_type: "clocks_frozen".to_owned(),
+		    frozen: self.clocks_frozen,
+		};
+		PlayerConfig::send_group_message(&frozen_message.dump(), &self.player_ids_to_configs);
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "clocks_frozen".to_owned(),
+                    text: if self.clocks_frozen {
+			"The action clocks have been frozen".to_owned()
+		    } else {
+			"The action clocks have been unfrozen".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::ActionTimeout(seconds) => {
+		// the new clock applies from the next action, even mid-hand
+		self.action_timeout = seconds;
+		let timeout_message = object! {
+		    msg_type: "action_timeout".to_owned(),
+		    seconds: seconds,
+		};
+		PlayerConfig::send_group_message(&timeout_message.dump(), &self.player_ids_to_configs);
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "action_timeout".to_owned(),
+                    text: format!("Each player now has {} seconds to act", seconds),
+		}
+	    }
+	    AdminCommand::ClockScaling(new) => {
+		self.clock_scaling = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "clock_scaling".to_owned(),
+                    text: format!("The action clock scaling has been changed to {}", new),
+		}
+	    }
+	    AdminCommand::TimeBank(seconds) => {
+		self.time_bank = seconds;
+		// everyone starts over with the new time bank
+		for player in self.players.iter_mut().flatten() {
+		    player.time_bank_seconds = seconds;
+		}
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "time_bank".to_owned(),
+                    text: format!("Each player now has a time bank of {} seconds", seconds),
+		}
+	    }
+	    AdminCommand::TimeBankReplenish(seconds) => {
+		self.time_bank_replenish = seconds;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "time_bank_replenish".to_owned(),
+                    text: format!("Each time bank now gets {} more seconds every orbit", seconds),
+		}
+	    }
+	    AdminCommand::FinishDelay(seconds) => {
+		self.finish_delay = seconds;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "finish_delay".to_owned(),
+                    text: format!("The table now pauses {} seconds for each settlement after a hand", seconds),
+		}
+	    }
+	    AdminCommand::RematchWindow(seconds) => {
+		self.rematch_window = (seconds > 0).then(|| time::Duration::from_secs(seconds.into()));
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "rematch_window".to_owned(),
+                    text: if seconds > 0 {
+			format!("Once a heads-up match is over, the players have {} seconds to agree to a rematch", seconds)
+		    } else {
+			"Rematches are no longer offered".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::AllInRevealOrder(order) => {
+		self.all_in_reveal_order = order;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "all_in_reveal_order".to_owned(),
+                    text: match order {
+			RevealOrder::LastAggressor => "Once everyone is all-in, the last player to bet or raise shows first".to_owned(),
+			RevealOrder::Position => "Once everyone is all-in, the hands are shown in order from left of the button".to_owned(),
+		    },
+		}
+	    }
+	    AdminCommand::AllInRevealDelay(millis) => {
+		self.all_in_reveal_delay = time::Duration::from_millis(millis.into());
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "all_in_reveal_delay".to_owned(),
+                    text: if millis > 0 {
+			format!("Once everyone is all-in, each hand is shown {} milliseconds after the last", millis)
+		    } else {
+			"Once everyone is all-in, the hands are shown all at once".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::Anonymous(anonymous) => {
+		self.anonymous = anonymous;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "anonymous".to_owned(),
+                    text: if anonymous {
+			"The players are now shown to the table by seat instead of by name".to_owned()
+		    } else {
+			"The players are now shown to the table by name".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::BotsOnlyMaxPause(millis) => {
+		self.bots_only_max_pause = time::Duration::from_millis(millis.into());
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "bots_only_max_pause".to_owned(),
+                    text: format!("With only bots seated, the table now pauses at most {} milliseconds at a time", millis),
+		}
+	    }
+	    AdminCommand::MaxFinishDelay(seconds) => {
+		self.max_finish_delay = seconds;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "max_finish_delay".to_owned(),
+                    text: format!("The table now pauses at most {} seconds after a hand", seconds),
+		}
+	    }
+	    AdminCommand::FinishDelayPotScaling(scaling) => {
+		self.finish_delay_pot_scaling = scaling;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "finish_delay_pot_scaling".to_owned(),
+                    text: if scaling {
+			"Bigger pots now get a longer pause after the hand".to_owned()
+		    } else {
+			"Every pot now gets the same pause after the hand".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::CommitShuffle(commit) => {
+		self.commit_shuffle = commit;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "commit_shuffle".to_owned(),
+                    text: if commit {
+			"Each shuffle is now committed to before the hand and revealed after it".to_owned()
+		    } else {
+			"Shuffles are no longer committed to or revealed".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::AllowChop(allow) => {
+		self.allow_chop = allow;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "allow_chop".to_owned(),
+                    text: if allow {
+			"All-in players can now agree to chop the pot".to_owned()
+		    } else {
+			"All-in players can no longer agree to chop the pot".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::RunItTwice(run_twice) => {
+		self.run_it_twice = run_twice;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "run_it_twice".to_owned(),
+                    text: if run_twice {
+			"The board will now be run twice when the players are all-in".to_owned()
+		    } else {
+			"The board will now be run once when the players are all-in".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::ShowRunoutEquities(show) => {
+		self.show_runout_equities = show;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "show_runout_equities".to_owned(),
+                    text: if show {
+			"The equities of all-in hands will now be shown as the board is run out".to_owned()
+		    } else {
+			"The equities of all-in hands will no longer be shown".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::AllowRebuys(allow) => {
+		self.allow_rebuys = allow;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "allow_rebuys".to_owned(),
+                    text: if allow {
+			"Players with auto rebuy on are now topped up to the buy-in between hands".to_owned()
+		    } else {
+			"Players can no longer rebuy".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::MinPlayersToStart(num) => {
+		self.min_players_to_start = num;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "min_players_to_start".to_owned(),
+                    text: format!("A hand is now only dealt once {} players can play", num),
+		}
+	    }
+	    AdminCommand::MinStackToPlay(amount) => {
+		self.min_stack_to_play = amount;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "min_stack_to_play".to_owned(),
+                    text: if amount > 0 {
+			format!("Players below a stack of {} are now sat out until they rebuy", amount)
+		    } else {
+			"Players can now be dealt in with any stack".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::MaxStack(amount) => {
+		// 0 turns it off, so players can only rebuy up to the buy-in
+		self.max_stack = if amount > 0 { Some(amount) } else { None };
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "max_stack".to_owned(),
+                    text: if amount > 0 {
+			format!("Players can now rebuy up to a stack of {}", amount)
+		    } else {
+			"Players can now only rebuy up to the buy-in".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::RakePercent(percent) => {
+		self.rake_percent = percent;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "rake_percent".to_owned(),
+                    text: if percent > 0 {
+			format!("The house now takes {} percent of each pot that sees a flop", percent)
+		    } else {
+			"The pots are no longer raked".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::RakeCap(amount) => {
+		// 0 turns it off, so there is no limit to the rake of a hand
+		self.rake_cap = if amount > 0 { Some(amount) } else { None };
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "rake_cap".to_owned(),
+                    text: if amount > 0 {
+			format!("The rake is now capped at {} per hand", amount)
+		    } else {
+			"The rake is no longer capped".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::RebuysAtOrbitStart(at_orbit_start) => {
+		self.rebuys_at_orbit_start = at_orbit_start;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "rebuys_at_orbit_start".to_owned(),
+                    text: if at_orbit_start {
+			"Rebuys now wait until the start of the next orbit".to_owned()
+		    } else {
+			"Rebuys now happen after every hand".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::MaxStraddles(max) => {
+		self.max_straddles = max;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "max_straddles".to_owned(),
+                    text: if max > 0 {
+			format!("Up to {} straddles can now be posted on top of the blinds", max)
+		    } else {
+			"Straddles are no longer allowed".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::RemoveBusted(remove) => {
+		self.remove_busted = remove;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "remove_busted".to_owned(),
+                    text: if remove {
+			"Players who run out of chips now lose their seat".to_owned()
+		    } else {
+			"Players who run out of chips now keep their seat".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::CheckItDown(check_it_down) => {
+		self.check_it_down = check_it_down;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "check_it_down".to_owned(),
+                    text: if check_it_down {
+			"Once a player is all-in, the other players will check it down".to_owned()
+		    } else {
+			"Players can bet against each other when a player is all-in".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::ExposeBotDecisions(expose) => {
+		self.expose_bot_decisions = expose;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "expose_bot_decisions".to_owned(),
+                    text: if expose {
+			"The decisions of the bots will be shown to the admin".to_owned()
+		    } else {
+			"The decisions of the bots will no longer be shown".to_owned()
+		    },
+		}
+	    }
+	    AdminCommand::ReshufflePolicy(new) => {
+		self.reshuffle_policy = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "reshuffle_policy".to_owned(),
+                    text: format!("The reshuffle policy has been changed to {}", new),
+		}
+	    }
+	    AdminCommand::ExposurePolicy(new) => {
+		self.exposure_policy = new;
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "exposure_policy".to_owned(),
+                    text: format!("The exposed card policy has been changed to {}", new),
+		}
+	    }
 	    AdminCommand::ShowPassword => {
 		let pass_str = if let Some(password) = &self.password {
 		    format!("The password is {:?}", password)
@@ -634,6 +2194,14 @@ impl Table {
                     text: pass_str,
 		}
 	    }	    
+	    AdminCommand::CloseIn(minutes) => {
+		self.closing_at = Some(time::Instant::now() + time::Duration::from_secs(60 * u64::from(minutes)));
+		object! {
+		    msg_type: "admin_success".to_owned(),
+		    updated: "closing_at".to_owned(),
+                    text: format!("The table will close in {} minutes, once the hand in progress is over", minutes),
+		}
+	    }
 	    AdminCommand::AddBot => {
 		match self.add_bot("Bot".to_string()) {
 		    Ok(_) => {
@@ -657,11 +2225,12 @@ impl Table {
 		for player_spot in self.players.iter_mut() {
 		    if let Some(player) = player_spot {
 			if !player.human_controlled {
-			    println!("remove the bot!");
-			    self
+			    log::debug!("remove the bot!");
+			    let config = self
 				.player_ids_to_configs
 				.remove(&player.id)
 				.expect("how was the bot a player but not a config");
+			    self.departed_results.push(player.reconciliation_entry(config.name));
 			    *player_spot = None;
 			    found = true;
 			    break;
@@ -682,14 +2251,54 @@ impl Table {
 		    }
 		}
 	    }
+	    AdminCommand::Kick(target) => self.kick_player(target),
+	    AdminCommand::KickSeat(index) => {
+		match self.players.get(index).and_then(|spot| spot.as_ref()) {
+		    Some(player) => self.kick_player(player.id),
+		    None => object! {
+			msg_type: "error".to_owned(),
+			error: "invalid_kick".to_owned(),
+			reason: format!("There is nobody in seat {}.", index),
+		    },
+		}
+	    }
+	    AdminCommand::Mute(target) | AdminCommand::Unmute(target) => {
+		let mute = matches!(admin_command, AdminCommand::Mute(_));
+		let name = self
+		    .player_ids_to_configs
+		    .get(&target)
+		    .or_else(|| self.spectators.get(&target))
+		    .map(|config| config.name.clone().unwrap_or_default());
+		match name {
+		    None => object! {
+			msg_type: "error".to_owned(),
+			error: "invalid_mute".to_owned(),
+			reason: "That player is not at the table.".to_owned(),
+		    },
+		    Some(name) => {
+			if mute {
+			    self.muted_ids.insert(target);
+			} else {
+			    self.muted_ids.remove(&target);
+			}
+			object! {
+			    msg_type: "admin_success".to_owned(),
+			    updated: if mute { "muted" } else { "unmuted" },
+			    text: format!("{} has been {}.", name, if mute { "muted" } else { "unmuted" }),
+			}
+		    }
+		}
+	    }
 	    AdminCommand::Restart => {
 		// set every player to have the buy_in amount of money
-		println!("inside restart");
-		for player_spot in self.players.iter_mut() {
-		    if let Some(player) = player_spot {
-			player.money = self.buy_in;
-		    }
+		log::debug!("inside restart");
+		for player in self.players.iter_mut().flatten() {
+		    player.money = self.buy_in;
+		    player.stats = SessionStats::new(self.buy_in);
 		}
+		// the blinds start over from the button
+		self.small_blind_idx = None;
+		self.big_blind_idx = None;
 		object! {
 		    msg_type: "admin_success".to_owned(),
 		    updated: "game_restarted".to_owned(),
@@ -704,102 +2313,601 @@ impl Table {
 	);
     }
 	
-    fn transition(&mut self, gamehand: &mut GameHand) {
-	gamehand.current_bet = 0;
-	gamehand.index_to_act = None;
-        match gamehand.street {
-            Street::Preflop => {
+    /// the admin removes a player. Just like when a player leaves, their config is removed now,
+    /// and their seat is freed once the table notices (which folds them if they are in a hand).
+    /// Their stack goes into the departed results, so it is settled up like any other player's.
+    /// Returns the message for the admin
+    fn kick_player(&mut self, target: Uuid) -> json::JsonValue {
+	if target == self.admin_id {
+	    return object! {
+		msg_type: "error".to_owned(),
+		error: "invalid_kick".to_owned(),
+		reason: "You cannot kick yourself.".to_owned(),
+	    };
+	}
+	let Some(config) = self.player_ids_to_configs.remove(&target) else {
+	    return object! {
+		msg_type: "error".to_owned(),
+		error: "invalid_kick".to_owned(),
+		reason: "That player is not at the table.".to_owned(),
+	    };
+	};
+	self.record_departure(target, config.name.clone());
+	let message = object! {
+	    msg_type: "player_kicked".to_owned(),
+	    name: self.display_name(target, config.name.clone()),
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	let name = config.name.clone().unwrap_or_default();
+	if let Some(hub_addr) = &self.hub_addr {
+	    // the hub tells the player they were kicked, and puts them back in the lobby
+	    hub_addr.do_send(Returned {
+		config,
+		reason: ReturnedReason::Kicked,
+	    });
+	}
+	object! {
+	    msg_type: "admin_success".to_owned(),
+	    updated: "player_kicked".to_owned(),
+	    text: format!("{} has been removed from the table.", name),
+	}
+    }
+
+    /// move to the next street, dealing the board as needed.
+    /// Returns the reason if the board could not be dealt
+    fn transition(&mut self, gamehand: &mut GameHand) -> Result<(), String> {
+	gamehand.new_betting_round(self.big_blind);
+	gamehand.index_to_act = None;
+	if !gamehand.variant.has_board() && gamehand.street != Street::ShowDown {
+	    // in Stud, each street deals every player still in the hand a card of their own
+	    gamehand.street = match gamehand.street {
+		Street::Preflop => Street::Flop,
+		Street::Flop => Street::Turn,
+		Street::Turn => Street::River,
+		Street::River => Street::Seventh,
+		_ => Street::ShowDown,
+	    };
+	    if gamehand.street != Street::ShowDown {
+		self.deal_stud_street(gamehand)?;
+	    }
+	    self.send_game_state(Some(gamehand), false);
+	    return Ok(());
+	}
+        match gamehand.street {
+            Street::Preflop => {
                 gamehand.street = Street::Flop;
-                self.deal_flop(gamehand);
-                println!(
-                    "\n===========================\nFlop = {:?}\n===========================",
-                    gamehand.flop
-                );
+                self.deal_flop(gamehand)?;
+                log::debug!("Flop = {:?}", gamehand.flop);
             }
             Street::Flop => {
                 gamehand.street = Street::Turn;
-                self.deal_turn(gamehand);
-                println!(
-                    "\n==========================\nTurn = {:?}\n==========================",
-                    gamehand.turn
-                );
+                self.deal_turn(gamehand)?;
+                log::debug!("Turn = {:?}", gamehand.turn);
             }
             Street::Turn => {
                 gamehand.street = Street::River;
-                self.deal_river(gamehand);
-                println!(
-                    "\n==========================\nRiver = {:?}\n==========================",
-                    gamehand.river
-                );
+                self.deal_river(gamehand)?;
+                log::debug!("River = {:?}", gamehand.river);
             }
-            Street::River => {
+            Street::River | Street::Seventh => {
                 gamehand.street = Street::ShowDown;
-                println!(
-                    "\n==========================\nShowDown!\n================================"
-                );
+                log::debug!("ShowDown!");
             }
             Street::ShowDown => (), // we are already in the end street (from players folding during the street)
         }
 	self.send_game_state(Some(gamehand), false);	
+	Ok(())
     }
 
-    fn deal_hands(&mut self) {
-        for player in self.players.iter_mut().flatten() {
-            if player.is_active {
-                for _ in 0..2 {
-                    if let Some(card) = self.deck.draw_card() {
-                        player.hole_cards.push(card)
-                    } else {
-                        panic!("The deck is out of cards somehow?");
-                    }
+    /// shuffle the deck before a hand, if the reshuffle policy calls for it.
+    /// returns a bool indicating if the deck was shuffled
+    fn prepare_deck(&mut self, num_players: usize) -> bool {
+	// the hole cards, plus the full board and a burn card before each of its three deals.
+	// When the board may be run twice, it could need all of that again
+	// Stud has no board, but a card is still burned before each of its four later streets
+	let board_cards = if !self.variant.has_board() {
+	    4
+	} else if self.run_it_twice {
+	    2 * (5 + 3)
+	} else {
+	    5 + 3
+	};
+	let cards_needed = self.variant.num_hole_cards() * num_players + board_cards;
+	// a committed shuffle is revealed after the hand, so the rest of that deck can't be dealt again
+	let should_shuffle = self.commit_shuffle || match self.reshuffle_policy {
+	    ReshufflePolicy::EveryHand => true,
+	    ReshufflePolicy::WhenNeeded => self.deck.remaining() < cards_needed,
+	};
+	if should_shuffle {
+	    self.shuffle_deck();
+	}
+	should_shuffle
+    }
+
+    /// shuffle the deck, with the next seed if the session is seeded,
+    /// and committing to the seed if the table publishes its shuffles
+    fn shuffle_deck(&mut self) {
+	let seed = self.shuffle_seeds.as_mut().map(|seeds| seeds.gen::<u64>());
+	if self.commit_shuffle {
+	    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen::<u64>());
+	    self.shuffle_commitment = Some(self.deck.shuffle_with_commitment(seed));
+	} else {
+	    self.shuffle_commitment = None;
+	    match seed {
+		Some(seed) => self.deck.shuffle_with_seed(seed),
+		None => self.deck.shuffle(),
+	    }
+	}
+	if seed.is_some() {
+	    self.shuffle_seed = seed;
+	}
+    }
+
+    /// the deck could only run out if it was under-filled (e.g. a rigged deck),
+    /// in which case we return the reason so the hand can be cancelled
+    fn draw_or_exhausted(&mut self) -> Result<Card, String> {
+        self.deck
+            .draw_card()
+            .ok_or_else(|| "The deck ran out of cards.".to_owned())
+    }
+
+    fn deal_hands(&mut self) -> Result<(), String> {
+        for i in 0..self.players.len() {
+            if self.players[i].as_ref().is_some_and(|player| player.is_active) {
+                for _ in 0..self.variant.num_starting_cards() {
+                    let card = self.draw_or_exhausted()?;
+                    self.players[i].as_mut().unwrap().hole_cards.push(card);
                 }
             }
         }
+        Ok(())
+    }
+
+    /// a card is burned before each deal of the board, so nobody can gain from a marked top card
+    fn burn_card(&mut self, gamehand: &mut GameHand) -> Result<(), String> {
+        let card = self.draw_or_exhausted()?;
+        gamehand.burned_cards.push(card);
+        Ok(())
     }
 
-    fn deal_flop(&mut self, gamehand: &mut GameHand) {
+    fn deal_flop(&mut self, gamehand: &mut GameHand) -> Result<(), String> {
+        self.burn_card(gamehand)?;
         let mut flop = Vec::<Card>::with_capacity(3);
         for _ in 0..3 {
-            if let Some(card) = self.deck.draw_card() {
-                flop.push(card)
-            } else {
-                panic!("we exhausted the deck somehow");
-            }
+            flop.push(self.draw_or_exhausted()?);
         }
         gamehand.flop = Some(flop);
+        Ok(())
+    }
+
+    fn deal_turn(&mut self, gamehand: &mut GameHand) -> Result<(), String> {
+        self.burn_card(gamehand)?;
+        gamehand.turn = Some(self.draw_or_exhausted()?);
+        Ok(())
+    }
+
+    fn deal_river(&mut self, gamehand: &mut GameHand) -> Result<(), String> {
+        self.burn_card(gamehand)?;
+        gamehand.river = Some(self.draw_or_exhausted()?);
+        Ok(())
+    }
+
+    /// in Stud, a card is burned and then each player still in the hand is dealt their next card.
+    /// With a full table the deck can run short for the last card, so in that case a single
+    /// card is dealt face up in the middle as a community card that everyone plays
+    fn deal_stud_street(&mut self, gamehand: &mut GameHand) -> Result<(), String> {
+	self.burn_card(gamehand)?;
+	let in_hand: Vec<usize> = (0..self.players.len())
+	    .filter(|&i| self.players[i].as_ref().is_some_and(|player| player.is_active))
+	    .collect();
+	if gamehand.street == Street::Seventh && self.deck.remaining() < in_hand.len() {
+	    gamehand.river = Some(self.draw_or_exhausted()?);
+	    return Ok(());
+	}
+	for i in in_hand {
+	    let card = self.draw_or_exhausted()?;
+	    self.players[i].as_mut().unwrap().hole_cards.push(card);
+	}
+	log::debug!("{} street dealt", gamehand.street);
+	Ok(())
+    }
+
+    /// the board is run a second time from where the betting closed, with fresh cards from the deck.
+    /// Both boards are shown to everyone before the pots are paid out
+    fn deal_second_board(&mut self, gamehand: &mut GameHand, num_shared: usize) -> Result<(), String> {
+	let first_board = gamehand.board();
+	let mut second_board: Vec<Card> = first_board[..num_shared].to_vec();
+	while second_board.len() < first_board.len() {
+	    if matches!(second_board.len(), 0 | 3 | 4) {
+		// like the first run, a card is burned before the flop, the turn and the river
+		self.burn_card(gamehand)?;
+	    }
+	    second_board.push(self.draw_or_exhausted()?);
+	}
+	let message = object! {
+	    msg_type: "run_it_twice".to_owned(),
+	    boards: vec![
+		first_board.iter().map(|card| card.to_string()).collect::<Vec<String>>(),
+		second_board.iter().map(|card| card.to_string()).collect::<Vec<String>>(),
+	    ],
+	};
+	gamehand.second_board = Some(second_board);
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+	Ok(())
+    }
+
+    /// a player agrees to the chop that is on offer. Proposing the chop is the same as agreeing to it,
+    /// except that the other players need a proposal before they can accept.
+    fn handle_chop_agreement(&mut self, id: Uuid, is_proposal: bool) {
+	let index = self.players.iter().position(|spot| {
+	    spot.as_ref().is_some_and(|player| player.id == id && player.is_active)
+	});
+	let reason = match (&mut self.chop_agreements, index) {
+	    (None, _) => Some("There is no chop on offer right now."),
+	    (Some(_), None) => Some("You are not in this hand."),
+	    (Some(agreements), Some(_)) if !is_proposal && agreements.is_empty() => {
+		Some("Nobody has proposed a chop yet.")
+	    }
+	    (Some(agreements), Some(_)) => {
+		agreements.insert(id);
+		None
+	    }
+	};
+	let message = if let Some(reason) = reason {
+	    let message = object! {
+		msg_type: "error".to_owned(),
+		error: "invalid_chop".to_owned(),
+		reason: reason.to_owned(),
+	    };
+	    PlayerConfig::send_specific_message(&message.dump(), id, &self.player_ids_to_configs);
+	    return;
+	} else if is_proposal {
+	    object! {
+		msg_type: "chop_proposed".to_owned(),
+		index: index,
+	    }
+	} else {
+	    object! {
+		msg_type: "chop_accepted".to_owned(),
+		index: index,
+	    }
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+    }
+
+    /// two players were seated, and one of them has won all the chips (and wasn't topped back up)
+    fn is_heads_up_match_over(&self) -> bool {
+	let seated: Vec<&Player> = self.players.iter().flatten().collect();
+	seated.len() == 2 && seated.iter().filter(|player| player.money > 0).count() == 1
+    }
+
+    /// a player agrees to the rematch that is on offer
+    fn handle_rematch_agreement(&mut self, id: Uuid) {
+	let index = self.players.iter().position(|spot| spot.as_ref().is_some_and(|player| player.id == id));
+	let reason = match (&mut self.rematch_agreements, index) {
+	    (None, _) => Some("There is no rematch on offer right now."),
+	    (Some(_), None) => Some("You are not seated at this table."),
+	    (Some(agreements), Some(_)) => {
+		agreements.insert(id);
+		None
+	    }
+	};
+	if let Some(reason) = reason {
+	    let message = object! {
+		msg_type: "error".to_owned(),
+		error: "invalid_rematch".to_owned(),
+		reason: reason.to_owned(),
+	    };
+	    PlayerConfig::send_specific_message(&message.dump(), id, &self.player_ids_to_configs);
+	    return;
+	}
+	let message = object! {
+	    msg_type: "rematch_accepted".to_owned(),
+	    index: index,
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
     }
 
-    fn deal_turn(&mut self, gamehand: &mut GameHand) {
-        gamehand.turn = self.deck.draw_card();
+    /// once a heads-up match is over, the two players can agree to play again from the buy-in.
+    /// We wait for both of them to agree within the rematch window, and if they do, reset the stacks.
+    /// Returns a bool indicating whether the rematch was agreed to
+    fn offer_rematch(&mut self, incoming_meta_actions: &Arc<Mutex<VecDeque<MetaAction>>>) -> bool {
+	let Some(window) = self.rematch_window else {
+	    return false;
+	};
+	let seated_ids: HashSet<Uuid> = self.players.iter().flatten().map(|player| player.id).collect();
+	let message = object! {
+	    msg_type: "rematch_offer".to_owned(),
+	    seconds: window.as_secs(),
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+
+	self.rematch_agreements = Some(HashSet::new());
+	let offer_start = time::Instant::now();
+	let mut agreed = false;
+	while offer_start.elapsed() < window {
+	    self.handle_meta_actions(incoming_meta_actions, true, None);
+	    if self.rematch_agreements.as_ref().is_some_and(|agreements| agreements.is_superset(&seated_ids)) {
+		agreed = true;
+		break;
+	    }
+	    thread::sleep(POLL_INTERVAL);
+	}
+	self.rematch_agreements = None;
+	if !agreed {
+	    let message = object! {
+		msg_type: "rematch_declined".to_owned(),
+	    };
+            PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	    return false;
+	}
+	for player in self.players.iter_mut().flatten() {
+	    // the buy-in is adjusted along with the stack, so each player's net from the first match still counts
+	    player.stats.total_buy_in = (player.stats.total_buy_in + self.buy_in).saturating_sub(player.money);
+	    player.money = self.buy_in;
+	    player.is_sitting_out = false;
+	}
+	let message = object! {
+	    msg_type: "rematch_started".to_owned(),
+	    stack: self.buy_in,
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+	self.send_game_state(None, false);
+	true
+    }
+
+    /// once the betting is closed because the players are all-in, they can agree to chop the pots
+    /// by equity instead of running out the board. We show everyone the equities and wait
+    /// for every player in the hand to agree.
+    /// Returns a bool indicating whether the chop was agreed to
+    fn offer_chop(
+	&mut self,
+	incoming_meta_actions: &Arc<Mutex<VecDeque<MetaAction>>>,
+	gamehand: &GameHand,
+    ) -> bool {
+	let in_hand_ids: HashSet<Uuid> = self
+	    .players
+	    .iter()
+	    .flatten()
+	    .filter(|player| player.is_active)
+	    .map(|player| player.id)
+	    .collect();
+	let message = object! {
+	    msg_type: "chop_offer".to_owned(),
+	    equities: self.active_equities(gamehand),
+	    seconds: CHOP_WAIT.as_secs(),
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+
+	self.chop_agreements = Some(HashSet::new());
+	let offer_start = time::Instant::now();
+	let mut agreed = false;
+	while offer_start.elapsed() < CHOP_WAIT {
+	    self.handle_meta_actions(incoming_meta_actions, false, Some(gamehand));
+	    if self.chop_agreements.as_ref().is_some_and(|agreements| agreements.is_superset(&in_hand_ids)) {
+		agreed = true;
+		break;
+	    }
+	    thread::sleep(POLL_INTERVAL);
+	}
+	self.chop_agreements = None;
+	if !agreed {
+	    let message = object! {
+		msg_type: "chop_declined".to_owned(),
+	    };
+            PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	}
+	agreed
     }
 
-    fn deal_river(&mut self, gamehand: &mut GameHand) {
-        gamehand.river = self.deck.draw_card();
+    /// is there no more betting to be done in the hand, since (all but one of) the players are all-in
+    fn is_betting_closed(&self) -> bool {
+	let num_active = self.players.iter().flatten().filter(|player| player.is_active).count();
+	let num_all_in = self.players.iter().flatten().filter(|player| player.is_all_in()).count();
+	num_active >= 2 && num_all_in + 1 >= num_active
     }
 
-    fn finish_hand(&mut self, gamehand: &mut GameHand) {
+    fn finish_hand(
+	&mut self,
+	incoming_meta_actions: &Arc<Mutex<VecDeque<MetaAction>>>,
+	gamehand: &mut GameHand,
+	chopped: bool,
+    ) {
         if self.player_ids_to_configs.is_empty() {
             // the game is currently empty, so there is nothing to finish
             return;
         }
+	// a quick fold the action never reached goes with this hand,
+	// while one queued during the pause below is kept for the next
+	for player in self.players.iter_mut().flatten() {
+	    player.quick_fold = false;
+	}
         // Notify players the hand is over
         let mut finish_hand_message = object! {
             msg_type: "finish_hand".to_owned()
         };
 
-	let starting_idx = self.get_starting_idx();
-	let settlements = gamehand.divvy_pots(&mut self.players, &self.player_ids_to_configs, starting_idx);
-	println!("blah settlements = {:?}", settlements);
-        let wait_time = 3*settlements.len();
-        finish_hand_message["settlements"] = settlements.into();	
+	let bad_beat = gamehand.find_bad_beat(&self.players, &self.player_ids_to_configs, self.bad_beat_threshold);
+	// the rake comes off the top, so the settlements are for what is left in the pots
+	let rake = gamehand.take_rake(self.rake_percent, self.rake_cap);
+	self.total_raked += rake;
+	finish_hand_message["rake"] = rake.into();
+	finish_hand_message["total_raked"] = self.total_raked.into();
+	let starting_idx = self.get_starting_idx(gamehand);
+	let settlements = if chopped {
+	    gamehand.chop_pots(&mut self.players, &self.player_ids_to_configs)
+	} else {
+	    gamehand.divvy_pots(&mut self.players, &self.player_ids_to_configs, starting_idx)
+	};
+	log::debug!("settlements = {:?}", settlements);
+        let wait_time = self.finish_delay(gamehand, settlements.len());
+	self.update_session_stats(gamehand, &settlements);
+	let winner_idxs: HashSet<usize> = settlements
+	    .iter()
+	    .filter(|settlement| settlement.winner)
+	    .map(|settlement| settlement.seat)
+	    .collect();
+	// every action of the hand in order, so it can be replayed
+	finish_hand_message["action_log"] = gamehand.action_log_json();
+	finish_hand_message["hand_history"] = gamehand
+	    .to_hand_history(&self.game_state(None, false), &self.players, &settlements)
+	    .into();
+	// the structured payouts are for moving the chips of each pot, the settlements are the readable summary
+        finish_hand_message["payouts"] = settlements.iter().map(Settlement::payout_json).collect::<Vec<_>>().into();
+	if self.audit_deck {
+	    // for debugging, the burned cards can be checked against the audited deck order
+	    finish_hand_message["burned_cards"] = gamehand.burned_cards.iter().map(|card| card.to_string()).collect::<Vec<String>>().into();
+	}
+	if let Some(commitment) = &self.shuffle_commitment {
+	    // the seed behind the hash sent with new_hand, so players can rebuild the deck and check the deal
+	    finish_hand_message["shuffle_reveal"] = object! {
+		hash: commitment.hash.clone(),
+		seed: commitment.seed.to_string(),
+		salt: commitment.salt.clone(),
+	    };
+	}
+        finish_hand_message["settlements"] = settlements
+	    .into_iter()
+	    .map(|mut settlement| {
+		if self.anonymous {
+		    settlement.summary["player_name"] = Table::seat_label(settlement.seat).into();
+		}
+		settlement.summary
+	    })
+	    .collect::<Vec<_>>()
+	    .into();
         PlayerConfig::send_group_message(&finish_hand_message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&finish_hand_message);
+	if let Some(bad_beat_message) = bad_beat {
+            PlayerConfig::send_group_message(&bad_beat_message.dump(), &self.player_ids_to_configs);
+	    self.publish_event(&bad_beat_message);
+	}
         
-        let pause_duration = time::Duration::from_secs(wait_time.try_into().unwrap());
-        thread::sleep(pause_duration);	
+        let mut pause_duration = wait_time;
+	// a losing player may want to look at their hand for longer than the usual pause
+	for (i, player) in self.players.iter().enumerate() {
+	    if let Some(Player { auto_muck_delay: Some(delay), .. }) = player {
+		if !winner_idxs.contains(&i) {
+		    pause_duration = cmp::max(pause_duration, *delay);
+		}
+	    }
+	}
+	let pause_duration = self.pace(pause_duration);
+	let pause_start = time::Instant::now();
+	loop {
+	    let elapsed = pause_start.elapsed();
+	    if self.muck_expired_hands(elapsed, &winner_idxs) {
+		self.send_game_state(Some(gamehand), false);
+	    }
+	    if elapsed >= pause_duration {
+		break;
+	    }
+	    self.pause(cmp::min(time::Duration::from_secs(1), pause_duration - elapsed), incoming_meta_actions, false, Some(gamehand));
+	}
         // take the players' cards
         for player in self.players.iter_mut().flatten() {
             player.hole_cards.drain(..);
         }
+	self.last_hand_events = Some(self.hand_events.take());
+    }
+
+    /// wait out a pause without going deaf to the table: the meta actions (chat, joins, sitting out and so on)
+    /// are handled as they come in, rather than piling up until the pause is over
+    fn pause(
+	&mut self,
+	duration: time::Duration,
+	incoming_meta_actions: &Arc<Mutex<VecDeque<MetaAction>>>,
+	between_hands: bool,
+	gamehand: Option<&GameHand>,
+    ) {
+	self.pause_until(duration, incoming_meta_actions, between_hands, gamehand, || false);
+    }
+
+    /// the same as pause, but cut short as soon as done() is true, e.g. once the player we are waiting on acts
+    fn pause_until(
+	&mut self,
+	duration: time::Duration,
+	incoming_meta_actions: &Arc<Mutex<VecDeque<MetaAction>>>,
+	between_hands: bool,
+	gamehand: Option<&GameHand>,
+	done: impl Fn() -> bool,
+    ) {
+	let deadline = time::Instant::now() + duration;
+	loop {
+	    if !incoming_meta_actions.lock().unwrap().is_empty() {
+		self.handle_meta_actions(incoming_meta_actions, between_hands, gamehand);
+	    }
+	    let now = time::Instant::now();
+	    if now >= deadline || done() {
+		break;
+	    }
+	    thread::sleep(cmp::min(POLL_INTERVAL, deadline - now));
+	}
+    }
+
+    /// a pause for the humans to follow along, which is cut short when only bots are seated
+    fn pace(&self, duration: time::Duration) -> time::Duration {
+	let duration = self.max_pause.map_or(duration, |max_pause| cmp::min(duration, max_pause));
+	if self.players.iter().flatten().any(|player| player.human_controlled) {
+	    duration
+	} else {
+	    cmp::min(duration, self.bots_only_max_pause)
+	}
+    }
+
+    /// how long to pause after a hand, so everyone can see the result.
+    /// Each settlement gets the base finish_delay, and with pot scaling big pots get a little longer,
+    /// but never longer than the max_finish_delay
+    fn finish_delay(&self, gamehand: &GameHand, num_settlements: usize) -> time::Duration {
+	let base = self.finish_delay * num_settlements as u32;
+	let extra = if self.finish_delay_pot_scaling {
+	    gamehand.total_pot() / cmp::max(1, FINISH_DELAY_POT_BIG_BLINDS * self.big_blind)
+	} else {
+	    0
+	};
+	time::Duration::from_secs(cmp::min(base + extra, self.max_finish_delay).into())
+    }
+
+    /// add the results of the hand to each player's running session stats
+    fn update_session_stats(&mut self, gamehand: &GameHand, settlements: &[Settlement]) {
+        for player in self.players.iter_mut().flatten() {
+	    if gamehand.vpip_ids.contains(&player.id) {
+		player.stats.vpip_hands += 1;
+	    }
+	    if gamehand.pfr_ids.contains(&player.id) {
+		player.stats.pfr_hands += 1;
+	    }
+	    let won: u32 = settlements
+		.iter()
+		.filter(|settlement| settlement.player_id == player.id)
+		.map(|settlement| settlement.amount_won)
+		.sum();
+	    player.stats.biggest_pot_won = cmp::max(player.stats.biggest_pot_won, won);
+	}
+    }
+
+    /// losing players with an auto muck delay have their hole cards taken once
+    /// the given time since the showdown has elapsed.
+    /// returns a bool indicating if any hand was mucked
+    fn muck_expired_hands(&mut self, elapsed: time::Duration, winner_idxs: &HashSet<usize>) -> bool {
+	let mut mucked = false;
+        for (i, player_spot) in self.players.iter_mut().enumerate() {
+	    if let Some(player) = player_spot {
+		if winner_idxs.contains(&i) || player.hole_cards.is_empty() {
+		    continue;
+		}
+		if let Some(delay) = player.auto_muck_delay {
+		    if elapsed >= delay {
+			log::debug!("mucking the hand of player {}", player.id);
+			player.hole_cards.drain(..);
+			mucked = true;
+		    }
+		}
+	    }
+	}
+	mucked
     }
 
     /// play a single hand of poker
@@ -810,8 +2918,10 @@ impl Table {
         incoming_actions: &Arc<Mutex<HashMap<Uuid, PlayerAction>>>,
         incoming_meta_actions: &Arc<Mutex<VecDeque<MetaAction>>>,
     ) -> bool {
-        println!("inside of play(). button_idx = {:?}", self.button_idx);
+        log::debug!("inside of play(). button_idx = {:?}", self.button_idx);
         let mut gamehand = GameHand::default();
+        gamehand.variant = self.variant;
+        gamehand.new_betting_round(self.big_blind);
 	let mut num_active = 0;
         for player in self.players.iter_mut().flatten() {
             if player.money == 0 {
@@ -823,8 +2933,8 @@ impl Table {
 		num_active += 1;
             }
         }
-        if self.player_ids_to_configs.len() < 1 || num_active < 2 {
-	    // not enough players or active players to play a hand,
+        if self.player_ids_to_configs.is_empty() || num_active < cmp::max(2, self.min_players_to_start) {
+	    // not enough players or active players (at least the table minimum) to play a hand,
 	    // send a game state indicating that the same is suspended,
 	    // and return false to the main loop.
 	    let game_suspended = true;
@@ -832,24 +2942,81 @@ impl Table {
             return false;
         }
 
-	let message = object! {
+        self.prepare_deck(num_active);
+	let mut message = object! {
 	    msg_type: "new_hand".to_owned(),
 	    hand_num: self.hand_num,
 	    button_index: self.button_idx,
         };
+	if let Some(seed) = self.shuffle_seed {
+	    // a seeded session is for debugging, so the seed is shared to be able to replay the deal
+	    message["deck_seed"] = seed.to_string().into();
+	}
+	if let Some(commitment) = &self.shuffle_commitment {
+	    message["shuffle_commitment"] = commitment.hash.clone().into();
+	}
 	PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	// the events of this hand are recorded from here on, for the replay once it is done
+	self.hand_events.borrow_mut().clear();
+	self.publish_event(&message);
 	
-	// drain any lingering actions from a previous hand
-        let mut actions = incoming_actions.lock().unwrap();
-	actions.drain();
-	std::mem::drop(actions); // give back the lock
-	
+	// Note: we do NOT drain the incoming actions here. Any lingering actions were cleared
+	// when the betting of the previous hand closed, so anything in the map now was sent
+	// during the pause between hands (or right as this hand started) and should be honored.
 	self.send_game_state(Some(&gamehand), false);	
-        self.deck.shuffle();
-        self.deal_hands();
+	if self.audit_deck {
+	    let deck_order = self.deck.peek_remaining();
+	    log::info!("deck order for hand {}: {:?}", self.hand_num, deck_order);
+	    self.audited_deck_order = Some(deck_order);
+	}
+        let dealt = self.deal_hands();
+        gamehand.num_players_dealt = num_active;
+	for (i, player_spot) in self.players.iter().enumerate() {
+	    if let Some(player) = player_spot.as_ref().filter(|player| player.is_active) {
+		gamehand.starting_stacks[i] = player.money;
+	    }
+	}
+        for player in self.players.iter_mut().flatten() {
+	    if !player.hole_cards.is_empty() {
+		player.stats.hands_played += 1;
+	    }
+        }
+	if let Err(reason) = dealt {
+	    self.cancel_hand(&mut gamehand, reason);
+	    return false;
+	}
+	if let Some(card) = self.detect_exposure() {
+	    let reason = format!("The card {} was exposed during the deal.", card);
+	    match self.exposure_policy {
+		ExposurePolicy::VoidHand => {
+		    // the hand is dead, and is not counted as played, so the same hand is dealt again
+		    self.cancel_hand(&mut gamehand, reason);
+		    return false;
+		}
+		ExposurePolicy::Continue => {
+		    // the hand plays on, but at least nobody has an information edge
+		    let message = object! {
+			msg_type: "card_exposed".to_owned(),
+			card: card.to_string(),
+			reason: reason,
+		    };
+		    PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+		    self.publish_event(&message);
+		}
+	    }
+	}
 
-        println!("players = {:?}", self.players);
+        log::debug!("players = {:?}", self.players);
+	self.assign_blinds(&mut gamehand);
+	if let (AnteMode::PerPlayer(ante), AnteOrder::BeforeBlinds) = (self.ante_mode, self.ante_order) {
+	    self.post_antes(&mut gamehand, ante);
+	}
 
+	let mut chop_offered = false;
+	let mut chopped = false;
+	let mut betting_closed_announced = false;
+	let mut all_in_revealed = false;
+	let mut run_twice_from: Option<usize> = None;
         while gamehand.street != Street::ShowDown {
 	    // before each street, set the player's last action to None
             for player in self.players.iter_mut().flatten() {
@@ -859,27 +3026,260 @@ impl Table {
             let finished =
                 self.play_street(incoming_actions, incoming_meta_actions, &mut gamehand);
             // pause for a second for dramatic effect heh
-            let pause_duration = time::Duration::from_secs(2);
-            thread::sleep(pause_duration);
+            let pause_duration = self.pace(time::Duration::from_secs(2));
+            self.pause(pause_duration, incoming_meta_actions, false, Some(&gamehand));
 	    
+            if let Some(reason) = gamehand.misdeal.take() {
+		// the hand is cancelled, and is not counted as played, so the same hand is dealt again
+		Table::clear_stale_actions(incoming_actions);
+		self.cancel_hand(&mut gamehand, reason);
+		return false;
+            }
             if finished {
                 // if the game is over from players folding
-                println!("\nGame is ending before showdown!");
+                log::debug!("Game is ending before showdown!");
                 break;
             } else {
+		if !all_in_revealed && gamehand.street != gamehand.variant.last_street() && self.is_betting_closed() {
+		    // there is no more betting, so the hands are turned face up for the run out
+		    all_in_revealed = true;
+		    self.reveal_all_in_hands(incoming_meta_actions, &gamehand);
+		    if self.show_runout_equities && gamehand.variant.has_board() {
+			self.broadcast_runout_equities(&gamehand);
+		    }
+		}
+		if self.allow_chop && gamehand.variant.has_board() && !chop_offered && gamehand.street != gamehand.variant.last_street() && self.is_betting_closed() {
+		    // the board doesn't need to be run out if the all-in players agree to chop
+		    chop_offered = true;
+		    if self.offer_chop(incoming_meta_actions, &gamehand) {
+			log::debug!("The players agreed to chop, so no need to run out the board");
+			chopped = true;
+			break;
+		    }
+		}
+		if !betting_closed_announced && gamehand.street != gamehand.variant.last_street() && self.is_betting_closed() {
+		    // nobody will be prompted again this hand, so let everyone know to just watch the board
+		    betting_closed_announced = true;
+		    self.announce_betting_closed(&gamehand);
+		    if self.run_it_twice && gamehand.variant.has_board() {
+			// the cards dealt so far are shared, the rest is dealt once for each run
+			run_twice_from = Some(gamehand.board().len());
+		    }
+		}
                 // otherwise we move to the next street
-                self.transition(&mut gamehand)
+                if let Err(reason) = self.transition(&mut gamehand) {
+		    // the board couldn't be dealt, so the hand is cancelled and everyone is refunded
+		    Table::clear_stale_actions(incoming_actions);
+		    self.cancel_hand(&mut gamehand, reason);
+		    return false;
+		}
+		if all_in_revealed && self.show_runout_equities && gamehand.variant.has_board() && gamehand.street != Street::ShowDown {
+		    // each new street can swing the equities, so everyone gets the update
+		    self.broadcast_runout_equities(&gamehand);
+		}
             }
         }
+	// the betting is closed, so any actions that are still lingering belong to this hand.
+	// Clear them now, so that the pause before the next hand acts as a grace period
+	// where actions for the next hand are buffered rather than dropped
+	Table::clear_stale_actions(incoming_actions);
+	if let (Some(num_shared), false) = (run_twice_from, chopped) {
+	    if let Err(reason) = self.deal_second_board(&mut gamehand, num_shared) {
+		self.cancel_hand(&mut gamehand, reason);
+		return false;
+	    }
+	}
         // now we finish up and pay the pot to the winner
-        self.finish_hand(&mut gamehand);
+        self.finish_hand(incoming_meta_actions, &mut gamehand, chopped);
 	true // the hand was indeed played
     }
 
-    fn get_starting_idx(&self) -> usize {
-        // the starting index is either the person one more from the button on most streets,
-        // or 3 down on the preflop (since the blinds already had to buy in)
-        // TODO: this needs to be smarter in small games
+    /// show everyone the hole cards of each player still in the hand (and whether they are all-in),
+    /// once the betting is closed and before the rest of the board is dealt.
+    /// The hands go in the table's reveal order. With a reveal delay, each hand is sent on its own,
+    /// one delay after the last, so viewers can take in each one. Otherwise they are all sent together
+    fn reveal_all_in_hands(&mut self, incoming_meta_actions: &Arc<Mutex<VecDeque<MetaAction>>>, gamehand: &GameHand) {
+	let num_seats = self.players.len();
+	let left_of_button = (self.button_idx + 1) % num_seats;
+	let first_idx = match self.all_in_reveal_order {
+	    RevealOrder::LastAggressor => gamehand
+		.action_log
+		.iter()
+		.rev()
+		.find(|(_, action, _)| matches!(action, PlayerAction::Bet(_) | PlayerAction::Raise(_)))
+		.map_or(left_of_button, |(seat, _, _)| *seat),
+	    RevealOrder::Position => left_of_button,
+	};
+	let mut hands = vec![];
+	for i in (0..num_seats).map(|offset| (first_idx + offset) % num_seats) {
+	    if let Some(player) = &self.players[i] {
+		if !player.is_active || player.hole_cards.is_empty() {
+		    continue;
+		}
+		hands.push(object! {
+		    index: i,
+		    player_name: self.display_name(player.id, self.player_ids_to_configs.get(&player.id).and_then(|config| config.name.clone())),
+		    hole_cards: player.hole_cards_string(),
+		    all_in: player.is_all_in(),
+		});
+	    }
+	}
+	let reveals = if self.all_in_reveal_delay.is_zero() {
+	    vec![hands]
+	} else {
+	    hands.into_iter().map(|hand| vec![hand]).collect()
+	};
+	for (i, hands) in reveals.into_iter().enumerate() {
+	    if i > 0 {
+		let delay = self.pace(self.all_in_reveal_delay);
+		self.pause(delay, incoming_meta_actions, false, Some(gamehand));
+	    }
+	    let message = object! {
+		msg_type: "all_in_reveal".to_owned(),
+		hands: hands,
+	    };
+            PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	    self.publish_event(&message);
+	}
+    }
+
+    /// the equity of each player still in the hand, given the board so far
+    fn active_equities(&self, gamehand: &GameHand) -> Vec<json::JsonValue> {
+	let in_hand: Vec<(usize, &Player)> = self
+	    .players
+	    .iter()
+	    .enumerate()
+	    .filter_map(|(i, spot)| spot.as_ref().map(|player| (i, player)))
+	    .filter(|(_, player)| player.is_active)
+	    .collect();
+	let hands: Vec<Vec<Card>> = in_hand.iter().map(|(_, player)| player.hole_cards.clone()).collect();
+	let equities = equity::equities(gamehand.variant, &hands, &gamehand.board());
+	in_hand
+	    .iter()
+	    .zip(equities.iter())
+	    .map(|((i, _), equity)| object! { index: *i, equity: *equity })
+	    .collect()
+    }
+
+    /// during an all-in run out, show everyone where the equities stand as of the current street
+    fn broadcast_runout_equities(&self, gamehand: &GameHand) {
+	let message = object! {
+	    msg_type: "runout_equities".to_owned(),
+	    street: gamehand.street.to_string(),
+	    equities: self.active_equities(gamehand),
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+    }
+
+    /// let everyone know the table acted for a player, and why (e.g. "timeout" or "invalid_action")
+    fn announce_forced_action(&self, index: usize, action: PlayerAction, reason: &str, text: String) {
+	let message = object! {
+	    msg_type: "forced_action".to_owned(),
+	    index: index,
+	    action: action.to_string(),
+	    reason: reason,
+	    text: text,
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+    }
+
+    /// the all-in players are done betting, and the rest of the board is about to be dealt out
+    fn announce_betting_closed(&self, gamehand: &GameHand) {
+	let message = object! {
+	    msg_type: "betting_closed".to_owned(),
+	    street: gamehand.street.to_string(),
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+    }
+
+    /// warn everyone that the table is closing soon, in however many seconds and/or hands are left
+    fn announce_closing(&self, time_remaining: Option<time::Duration>, hands_remaining: Option<u32>) {
+	let message = object! {
+	    msg_type: "table_closing".to_owned(),
+	    seconds_remaining: time_remaining.map(|remaining| remaining.as_secs()),
+	    hands_remaining: hands_remaining,
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+    }
+
+    /// set the blinds and ante to the current level of the blind schedule,
+    /// and let everyone know
+    fn apply_blind_level(&mut self) {
+	let schedule = match &self.blind_schedule {
+	    Some(schedule) => schedule,
+	    None => return,
+	};
+	let (small_blind, big_blind, ante) = schedule.current();
+	let level = schedule.current_level();
+	self.small_blind = small_blind;
+	self.big_blind = big_blind;
+	self.ante_mode = schedule.ante_mode(level);
+	let message = object! {
+	    msg_type: "blind_level_up".to_owned(),
+	    level: level,
+	    small_blind: small_blind,
+	    big_blind: big_blind,
+	    ante: ante,
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+    }
+
+    /// everyone dealt in puts in the ante, or whatever they have left
+    fn post_antes(&mut self, gamehand: &mut GameHand, ante: u32) {
+	for (i, player_spot) in self.players.iter_mut().enumerate() {
+	    if let Some(player) = player_spot {
+		if !player.is_active || player.money == 0 {
+		    // after the blinds, a blind might already be all-in
+		    continue;
+		}
+		let amount = cmp::min(ante, player.money);
+		player.money -= amount;
+		if !matches!(
+		    player.last_action,
+		    Some(PlayerAction::PostSmallBlind(_) | PlayerAction::PostBigBlind(_) | PlayerAction::PostBringIn(_))
+		) {
+		    // after the blinds, a blind's last action stays the blind
+		    player.last_action = Some(PlayerAction::PostAnte(amount));
+		}
+		gamehand.contribute_ante(i, player.id, amount, player.is_all_in());
+		gamehand.action_log.push((i, PlayerAction::PostAnte(amount), gamehand.street));
+	    }
+	}
+	self.send_game_state(Some(gamehand), false);
+    }
+
+    /// drain any actions left in the incoming actions map.
+    /// This is called once the betting for a hand is closed, e.g. a player who
+    /// clicked fold just as the hand ended should not have that fold applied to the next hand
+    fn clear_stale_actions(incoming_actions: &Arc<Mutex<HashMap<Uuid, PlayerAction>>>) {
+        let mut actions = incoming_actions.lock().unwrap();
+	actions.drain();
+    }
+
+    fn get_starting_idx(&self, gamehand: &GameHand) -> usize {
+        if !gamehand.variant.has_board() && gamehand.street != Street::ShowDown {
+            return self.stud_starting_idx(gamehand);
+        }
+        if gamehand.street == Street::Preflop {
+            if let (Some(small_blind_idx), Some(big_blind_idx)) = (self.small_blind_idx, self.big_blind_idx) {
+                // the blinds were assigned by the dead button rule
+                return if gamehand.num_blinds_posted == 0 { small_blind_idx } else { big_blind_idx };
+            }
+        }
+        // the starting index is the person one more from the button, who posts the first blind preflop.
+        // Heads-up with two blinds, the button posts the small blind and acts first preflop,
+        // while the big blind acts first after the flop
+        if gamehand.street == Street::Preflop
+            && gamehand.num_players_dealt == 2
+            && self.blind_config.blinds_for(2).len() == 2
+        {
+            return self.button_idx;
+        }
         let mut starting_idx = self.button_idx + 1;
         if starting_idx >= self.players.len() {
             starting_idx = 0;
@@ -887,6 +3287,45 @@ impl Table {
         starting_idx
     }
 
+    /// Stud has no blinds, so the order doesn't follow the button. On third street the lowest card showing
+    /// brings it in, with suits breaking ties (clubs lowest, then diamonds, hearts and spades).
+    /// After that the best hand showing acts first, and a tie goes to whoever is first after the button
+    fn stud_starting_idx(&self, gamehand: &GameHand) -> usize {
+	let in_hand: Vec<(usize, &Player)> = (self.button_idx + 1..self.players.len())
+	    .chain(0..self.button_idx + 1)
+	    .filter_map(|i| self.players[i].as_ref().filter(|player| player.is_active).map(|player| (i, player)))
+	    .collect();
+	let first = if gamehand.street == Street::Preflop {
+	    in_hand.iter().min_by_key(|(_, player)| {
+		player.up_cards(gamehand.variant).first().map(|card| (card.rank, card.suit as u8))
+	    })
+	} else {
+	    in_hand.iter().min_by_key(|(_, player)| {
+		cmp::Reverse(hand_eval::showing_strength(&player.up_cards(gamehand.variant)))
+	    })
+	};
+	first.map_or(self.button_idx, |(i, _)| *i)
+    }
+
+    /// how many forced bets open the first betting round: the blinds, or just the bring-in in Stud
+    fn num_forced_bets(&self, gamehand: &GameHand) -> usize {
+	if gamehand.variant.has_board() {
+	    self.blind_config.blinds_for(gamehand.num_players_dealt).len()
+	} else {
+	    1
+	}
+    }
+
+    /// the next seat after the given index with a player who can still act,
+    /// i.e. active with money left. None if there is no one else
+    fn next_to_act_after(&self, index: usize) -> Option<usize> {
+	(index + 1..self.players.len()).chain(0..index).find(|&i| {
+	    self.players[i]
+		.as_ref()
+		.is_some_and(|player| player.is_active && player.money > 0)
+	})
+    }
+
     /// this method returns a bool indicating whether the hand is over or not
     fn play_street(
         &mut self,
@@ -909,7 +3348,7 @@ impl Table {
             .filter(|player| player.is_active)
             .count();
         if num_active < 2 {
-            println!(
+            log::debug!(
                 "num_active players = {}, so we cannot play a hand!",
                 num_active
             );
@@ -917,41 +3356,46 @@ impl Table {
         }
 
         if num_all_in + 1 == num_active {
-            println!("only one person is not all in, so don't bother with the street!");
+            log::debug!("only one person is not all in, so don't bother with the street!");
             return false;
         }
 
         // once every player is either all-in or settled, then we move to the next street
         let mut num_settled = 0; // keep track of how many players have put in enough chips to move on
-        println!("num active players = {}", num_active);
+        log::debug!("num active players = {}", num_active);
 
         if num_settled > 0 {
-            println!("num settled (i.e. all in players) = {}", num_settled);
+            log::debug!("num settled (i.e. all in players) = {}", num_settled);
             PlayerConfig::send_group_message(
                 &format!("num settled (i.e. all in players) = {}", num_settled),
                 &self.player_ids_to_configs,
             );
         }
 	
-        let starting_idx = self.get_starting_idx(); // which player starts the betting
+        let starting_idx = self.get_starting_idx(gamehand); // which player starts the betting
 
-        gamehand.street_contributions.insert(gamehand.street, [0;9]);
+        gamehand.street_contributions.insert(gamehand.street, [0; MAX_SEATS]);
 	
 	let between_hands = false;		
 	
 	let mut hand_over = false;	
+	// with the ante after the blinds, it is collected as soon as the last blind is posted
+	let mut antes_pending = match (self.ante_mode, self.ante_order) {
+	    (AnteMode::PerPlayer(ante), AnteOrder::AfterBlinds) if gamehand.street == Street::Preflop => Some(ante),
+	    _ => None,
+	};
         // iterate over the players from the starting index to the end of the vec,
         // and then from the beginning back to the starting index
-        for i in (starting_idx..9).chain(0..starting_idx).cycle() {
+        for i in (starting_idx..self.players.len()).chain(0..starting_idx).cycle() {
 	    // handle meta actions once right at the beginning to be responsive to sitout messages for example
-            self.handle_meta_actions(&incoming_meta_actions, between_hands, Some(gamehand));
+            self.handle_meta_actions(incoming_meta_actions, between_hands, Some(gamehand));
 	    
             // double check if any players left as a meta-action during the previous
             // player's turn. They should no longer be considered as active or all_in
             for player_spot in self.players.iter_mut() {
 		if let Some(player) = player_spot {
                     if !self.player_ids_to_configs.contains_key(&player.id) {
-			println!("player is no longer in the config");
+			log::debug!("player is no longer in the config");
 			if player.is_all_in() {
                         num_all_in -= 1;
 			}
@@ -963,15 +3407,25 @@ impl Table {
                     }
 		}
             }
+
+	    if let Some(ante) = antes_pending {
+		if gamehand.num_blinds_posted >= self.num_forced_bets(gamehand) {
+		    antes_pending = None;
+		    let all_in_before = self.players.iter().flatten().filter(|player| player.is_all_in()).count();
+		    self.post_antes(gamehand, ante);
+		    let all_in_after = self.players.iter().flatten().filter(|player| player.is_all_in()).count();
+		    num_all_in += all_in_after - all_in_before;
+		}
+	    }
 	    
             if num_active == 1 {
-                println!("Only one active player left so lets break the steet loop");
+                log::debug!("Only one active player left so lets break the steet loop");
                 // end the street and indicate to the caller that the hand is finished
 		hand_over = true;
                 break;
             }
             if num_settled + num_all_in == num_active {
-                println!(
+                log::debug!(
                     "everyone is ready to go to the next street! num_settled = {}",
                     num_settled
                 );
@@ -980,7 +3434,7 @@ impl Table {
             }
 
 	    if let Some(player) = &self.players[i]  {
-		println!("Player = {:?}, i = {}", player, i);		
+		log::debug!("Player = {:?}, i = {}", player, i);		
 		if !(player.is_active && player.money > 0) {
 		    // if the player is not active with money, they can't do anything.
                     continue;
@@ -991,7 +3445,16 @@ impl Table {
 	    }
 	    
 	    gamehand.index_to_act = Some(i);
-	    self.send_game_state(Some(&gamehand), false);
+	    self.send_game_state(Some(gamehand), false);
+	    if let Some(next_idx) = self.next_to_act_after(i) {
+		// give the next player a heads up that their turn is coming
+		let next_id = self.players[next_idx].as_ref().unwrap().id;
+		let message = object! {
+		    msg_type: "on_deck".to_owned(),
+		    acting_index: i,
+		};
+		PlayerConfig::send_specific_message(&message.dump(), next_id, &self.player_ids_to_configs);
+	    }
 	    	    
             let action = self.get_and_validate_action(
                 incoming_actions,
@@ -1000,18 +3463,20 @@ impl Table {
 		i
             );
 
-	    println!("action = {:?}", action);
+	    log::debug!("action = {:?}", action);
 	    let player_cumulative = gamehand.street_contributions.get_mut(&gamehand.street).unwrap()[i];
             // now that we have gotten the current player's action and handled
             // any meta actions, we are free to respond and mutate the player
             // so we re-borrow it as mutable
             let player = self.players[i].as_mut().unwrap();
 	    player.last_action = Some(action);
+	    gamehand.action_log.push((i, action, gamehand.street));
             match action {
                 PlayerAction::PostSmallBlind(amount) => {	
                     player.money -= amount;		    	    
                     // regardless if the player couldn't afford it, the new street bet is the big blind
                     gamehand.current_bet = self.small_blind;
+                    gamehand.num_blinds_posted += 1;
                     let all_in = if player.is_all_in() {
                         num_all_in += 1;
                         true
@@ -1024,31 +3489,76 @@ impl Table {
                     player.money -= amount;		    		    
                     // regardless if the player couldn't afford it, the new street bet is the big blind
                     gamehand.current_bet = self.big_blind;
+                    gamehand.last_full_bet = self.big_blind;
+                    gamehand.num_blinds_posted += 1;
                     let all_in = if player.is_all_in() {
                         num_all_in += 1;
                         true
                     } else {
                         false
                     };
-                    gamehand.contribute(i, player.id, amount, all_in);
+                    // anything beyond the blind is a big blind ante. If they can't cover both,
+                    // the blind comes first. The ante is dead money, so it isn't part of their bet
+                    let blind = cmp::min(amount, self.big_blind);
+                    gamehand.contribute(i, player.id, blind, all_in);
+                    if amount > blind {
+                        gamehand.contribute_dead_ante(i, player.id, amount - blind);
+                    }
                     // note: we dont count the big blind as a "settled" player,
                     // since they still get a chance to act after the small blind
                 }
-                PlayerAction::Fold => {
-                    player.deactivate();
-                    num_active -= 1;
-                }
+                PlayerAction::PostBringIn(amount) => {
+                    player.money -= amount;
+                    // like a blind, the bring-in is the bet to call even if the player couldn't cover it.
+                    // The next player can call it or raise by at least the big blind
+                    gamehand.current_bet = self.small_blind;
+                    gamehand.num_blinds_posted += 1;
+                    let all_in = if player.is_all_in() {
+                        num_all_in += 1;
+                        true
+                    } else {
+                        // unlike the big blind, the bring-in has no option if everyone just calls it
+                        num_settled += 1;
+                        false
+                    };
+                    gamehand.contribute(i, player.id, amount, all_in);
+                }
+                PlayerAction::PostStraddle(amount) => {
+                    player.money -= amount;
+                    // the straddler becomes the new big blind: the bet to call, and the size of the next raise
+                    gamehand.current_bet = amount;
+                    gamehand.last_full_bet = amount;
+                    gamehand.last_full_raise_size = amount;
+                    gamehand.num_straddles += 1;
+                    let all_in = if player.is_all_in() {
+                        num_all_in += 1;
+                        true
+                    } else {
+                        false
+                    };
+                    gamehand.contribute(i, player.id, amount, all_in);
+                    // like the big blind, the straddler isn't settled, so they get the last action preflop
+                }
+                PlayerAction::Fold => {
+                    player.deactivate();
+                    num_active -= 1;
+                }
                 PlayerAction::SitOut => {
                     player.deactivate();
                     num_active -= 1;
                 }
                 PlayerAction::Check => {
+                    gamehand.acted_since_full_raise.insert(player.id);
                     num_settled += 1;
                 }
                 PlayerAction::Call => {
+		    if gamehand.street == Street::Preflop {
+			gamehand.vpip_ids.insert(player.id);
+		    }
+                    gamehand.acted_since_full_raise.insert(player.id);
                     let difference = gamehand.current_bet - player_cumulative;
                     let (amount, all_in) = if difference >= player.money {
-                        println!("you have to put in the rest of your chips");
+                        log::debug!("you have to put in the rest of your chips");
                         num_all_in += 1;
 			(player.money, true)
                     } else {
@@ -1059,13 +3569,26 @@ impl Table {
                     gamehand.contribute(i, player.id, amount, all_in);
 		    
                 }
-                PlayerAction::Bet(new_bet) => {
+                PlayerAction::Bet(_) | PlayerAction::Raise(_) => {
+		    let new_bet = match action {
+			PlayerAction::Raise(amount) => gamehand.current_bet + amount,
+			PlayerAction::Bet(new_bet) => new_bet,
+			_ => unreachable!(),
+		    };
+		    if gamehand.street == Street::Preflop {
+			gamehand.vpip_ids.insert(player.id);
+			gamehand.pfr_ids.insert(player.id);
+		    }
                     let difference = new_bet - player_cumulative;
-                    println!("difference = {}", difference);
-                    gamehand.current_bet = new_bet;
+                    log::debug!("difference = {}", difference);
+                    gamehand.raise_to(new_bet, self.min_raise_rule);
+                    gamehand.acted_since_full_raise.insert(player.id);
+		    if matches!(action, PlayerAction::Raise(_)) {
+			gamehand.num_raises += 1;
+		    }
                     player.money -= difference;		    		    
                     let all_in = if player.is_all_in() {
-                        println!("Just bet the rest of our money!");
+                        log::debug!("Just bet the rest of our money!");
                         num_all_in += 1;
                         num_settled = 0;
                         true
@@ -1075,23 +3598,120 @@ impl Table {
                     };
                     gamehand.contribute(i, player.id, difference, all_in);
                 }
+                PlayerAction::RaiseTo(_) | PlayerAction::RaiseBy(_) => {
+		    unreachable!("explicit raises are normalized by get_and_validate_action");
+                }
+                PlayerAction::PostAnte(_) => {
+		    unreachable!("antes are posted by post_antes before the betting starts");
+                }
             }
+	    if !matches!(
+		action,
+		PlayerAction::PostSmallBlind(_) | PlayerAction::PostBigBlind(_) | PlayerAction::PostBringIn(_) | PlayerAction::PostStraddle(_)
+	    ) {
+		// a straddle can only follow the blinds or another straddle
+		gamehand.straddling_closed = true;
+	    }
+	    if gamehand.street == Street::Preflop && gamehand.num_blinds_posted == self.num_forced_bets(gamehand)
+		&& matches!(action, PlayerAction::PostSmallBlind(_) | PlayerAction::PostBigBlind(_) | PlayerAction::PostBringIn(_))
+	    {
+		// the blinds are all in, so this is the moment to make sure the deal is valid
+		if let Some(reason) = self.detect_misdeal() {
+		    gamehand.misdeal = Some(reason);
+		    hand_over = true;
+		    break;
+		}
+	    }
         };
-	self.send_game_state(Some(&gamehand), false);	
+	self.send_game_state(Some(gamehand), false);	
 	hand_over
     }
+
+    /// is any player still in the hand all-in
+    fn is_anyone_all_in(&self) -> bool {
+	self.players
+	    .iter()
+	    .flatten()
+	    .any(|player| player.is_all_in())
+    }
+
+    /// once the blinds have been posted, check that the hand was dealt properly.
+    /// Returns the reason for a misdeal if it wasn't
+    fn detect_misdeal(&self) -> Option<String> {
+	let in_hand: Vec<&Player> = self
+	    .players
+	    .iter()
+	    .flatten()
+	    .filter(|player| player.is_active && self.player_ids_to_configs.contains_key(&player.id))
+	    .collect();
+	if in_hand.len() < 2 {
+	    return Some("There are not enough players left after the blinds were posted.".to_owned());
+	}
+	if in_hand.iter().any(|player| player.hole_cards.len() != self.variant.num_starting_cards()) {
+	    return Some("A player was dealt the wrong number of cards.".to_owned());
+	}
+	// note: Card equality only compares ranks, so we need to compare the suits as well
+	let cards: Vec<&Card> = in_hand.iter().flat_map(|player| player.hole_cards.iter()).collect();
+	for (i, card) in cards.iter().enumerate() {
+	    if cards[i + 1..].iter().any(|other| card.rank == other.rank && card.suit == other.suit) {
+		return Some(format!("The card {} was dealt more than once.", card));
+	    }
+	}
+	None
+    }
+
+    /// once the hole cards are dealt, check whether any of them was exposed.
+    /// Returns the first exposed hole card, if any. The exposures are used up either way
+    fn detect_exposure(&mut self) -> Option<Card> {
+	let exposed_cards = std::mem::take(&mut self.exposed_cards);
+	// note: Card equality only compares ranks, so we need to compare the suits as well
+	self.players
+	    .iter()
+	    .flatten()
+	    .flat_map(|player| player.hole_cards.iter())
+	    .find(|card| {
+		exposed_cards
+		    .iter()
+		    .any(|exposed| exposed.rank == card.rank && exposed.suit == card.suit)
+	    })
+	    .copied()
+    }
+
+    /// the hand was misdealt, so give everyone back what they put in, and let everyone know.
+    /// The same hand is then dealt again
+    fn cancel_hand(&mut self, gamehand: &mut GameHand, reason: String) {
+	log::info!("misdeal: {}", reason);
+	gamehand.refund_contributions(&mut self.players);
+        for player in self.players.iter_mut().flatten() {
+	    if !player.hole_cards.is_empty() {
+		// the cancelled hand doesn't count towards the session stats
+		player.stats.hands_played -= 1;
+	    }
+            player.hole_cards.drain(..);
+        }
+	let message = object! {
+	    msg_type: "misdeal".to_owned(),
+	    hand_num: self.hand_num,
+	    reason: reason,
+	};
+        PlayerConfig::send_group_message(&message.dump(), &self.player_ids_to_configs);
+	self.publish_event(&message);
+	self.send_game_state(None, false);
+    }
     
     /// if the player is a human, then we look for their action in the incoming_actions hashmap
     /// this value is set by the table hub when handling a message from a player client
     fn get_action_from_player(
         &self,
         incoming_actions: &Arc<Mutex<HashMap<Uuid, PlayerAction>>>,
+        gamehand: &GameHand,
+        index: usize,
         player: &Player,
     ) -> Option<PlayerAction> {
         if player.human_controlled {
             let mut actions = incoming_actions.lock().unwrap();
             if let Some(action) = actions.get_mut(&player.id) {
-                println!("Player: {:?} has action {:?}", player.id, action);
+                log::debug!("Player: {:?} has action {:?}", player.id, action);
                 let value = *action;
                 actions.remove(&player.id); // wipe this action so we don't repeat it next time
                 Some(value)
@@ -1100,9 +3720,9 @@ impl Table {
             }
         } else {
             let num = rand::thread_rng().gen_range(0..100);
-            match num {
-                0..=20 => Some(PlayerAction::Fold),
-                21..=55 => Some(PlayerAction::Check),
+            let (action, reason) = match num {
+                0..=20 => (PlayerAction::Fold, "rolled a fold"),
+                21..=55 => (PlayerAction::Check, "rolled a check"),
                 56..=70 => {
                     let amount: u32 = if player.money <= 100 {
                         // just go all in if we are at 10% starting
@@ -1110,13 +3730,94 @@ impl Table {
                     } else {
                         rand::thread_rng().gen_range(1..player.money / 2_u32)
                     };
-                    Some(PlayerAction::Bet(amount))
+                    (PlayerAction::RaiseTo(amount), "rolled a bet")
                 }
-                _ => Some(PlayerAction::Call),
-            }
+                _ => (PlayerAction::Call, "rolled a call"),
+            };
+	    if self.expose_bot_decisions {
+		self.send_bot_decision(gamehand, index, player, action, &format!("{} ({} out of 100)", reason, num));
+	    }
+            Some(action)
         }
     }
 
+    /// for debugging, tell the admin what a bot decided to do.
+    /// Note: the action might still be rejected, e.g. a bot can roll a check when it has to call
+    fn send_bot_decision(&self, gamehand: &GameHand, index: usize, player: &Player, action: PlayerAction, reason: &str) {
+	let name = self
+	    .player_ids_to_configs
+	    .get(&player.id)
+	    .and_then(|config| config.name.clone())
+	    .unwrap_or_default();
+	let message = object! {
+	    msg_type: "bot_decision".to_owned(),
+	    player_name: name,
+	    action: action.to_string(),
+	    reason: reason.to_owned(),
+	    // what the bot was facing when it decided
+	    to_call: gamehand.amount_to_call(index),
+	    total_pot: gamehand.total_pot(),
+	    invested: gamehand.invested_this_hand(index),
+	};
+	// only the admin, since the reasons can give away what the bot is holding
+	PlayerConfig::send_specific_message(&message.dump(), self.admin_id, &self.player_ids_to_configs);
+    }
+
+    /// how many seconds the player to act gets before their time bank kicks in.
+    /// With clock scaling, the base action_timeout grows for big pots or multiway hands,
+    /// up to double the base
+    fn scaled_action_timeout(&self, gamehand: &GameHand) -> u32 {
+	let extra = match self.clock_scaling {
+	    ClockScaling::Off => 0,
+	    ClockScaling::PotSize => {
+		gamehand.total_pot() / cmp::max(1, CLOCK_SCALING_POT_BIG_BLINDS * self.big_blind)
+	    }
+	    ClockScaling::PlayersInHand => {
+		let num_active = self.players.iter().flatten().filter(|player| player.is_active).count() as u32;
+		num_active.saturating_sub(2) * CLOCK_SCALING_SECONDS_PER_PLAYER
+	    }
+	};
+	self.action_timeout + cmp::min(extra, self.action_timeout)
+    }
+
+    /// check a bet/raise to new_bet against the limits of the betting structure.
+    /// Returns the reason to give the player if it is not allowed
+    fn check_betting_structure(
+	&self,
+	gamehand: &GameHand,
+	new_bet: u32,
+	player_cumulative: u32,
+	player_money: u32,
+    ) -> Result<(), String> {
+	match self.betting_structure {
+	    BettingStructure::NoLimit => Ok(()),
+	    BettingStructure::PotLimit => {
+		let max_raise_to = gamehand.max_pot_limit_raise_to(player_cumulative);
+		if new_bet > max_raise_to {
+		    Err(format!("In pot limit, the maximum bet is to {}!", max_raise_to))
+		} else {
+		    Ok(())
+		}
+	    }
+	    BettingStructure::FixedLimit => {
+		// a small bet on the early streets, and a big bet on the later ones
+		let bet_size = match gamehand.street {
+		    Street::Preflop | Street::Flop => self.big_blind,
+		    _ => 2 * self.big_blind,
+		};
+		let limit_bet = gamehand.current_bet + bet_size;
+		let all_in_for_less = new_bet == player_money + player_cumulative && new_bet < limit_bet;
+		if gamehand.num_raises >= FIXED_LIMIT_RAISE_CAP {
+		    Err("The betting is capped, so you can only call or fold!".to_owned())
+		} else if new_bet != limit_bet && !all_in_for_less {
+		    Err(format!("In fixed limit, the bet must be to exactly {}!", limit_bet))
+		} else {
+		    Ok(())
+		}
+	    }
+	}
+    }
+
     fn get_and_validate_action(
         &mut self,
         incoming_actions: &Arc<Mutex<HashMap<Uuid, PlayerAction>>>,
@@ -1128,9 +3829,9 @@ impl Table {
         // then it loops
         // position is our spot in the order, with 0 == small blind, etc
 	
-        // we sleep a little bit each time so that the output doesnt flood the user at one moment
-        let pause_duration = time::Duration::from_secs(1);
-        thread::sleep(pause_duration);
+        // we pause a little bit each time so that the output doesnt flood the user at one moment
+        let pause_duration = self.pace(time::Duration::from_secs(1));
+        self.pause(pause_duration, incoming_meta_actions, false, Some(gamehand));
 
 	// note: several times in this method we access player within a scope, so that
 	// we can call handle_meta_actions in between. Since that method wants to modify self.players,
@@ -1141,25 +3842,61 @@ impl Table {
 	// I don't know if this is somewhat common, or if I have coded myself into a corner...
 	let player_id = {
 	    let player = self.players[index].as_ref().unwrap();	   	
-            if gamehand.street == Street::Preflop && gamehand.current_bet == 0 {
-		// collect small blind!
-		return PlayerAction::PostSmallBlind(cmp::min(self.small_blind, player.money));
-            } else if gamehand.street == Street::Preflop && gamehand.current_bet == self.small_blind {
-		// collect big blind!
-		return PlayerAction::PostBigBlind(cmp::min(self.big_blind, player.money));
+            if gamehand.street == Street::Preflop && !gamehand.variant.has_board() {
+		if gamehand.num_blinds_posted == 0 {
+		    // the lowest card showing acts first on third street, and must bring it in
+		    return PlayerAction::PostBringIn(cmp::min(self.small_blind, player.money));
+		}
+            } else if gamehand.street == Street::Preflop {
+		// the first players to act preflop need to post the blinds
+		match self.blind_config.blinds_for(gamehand.num_players_dealt).get(gamehand.num_blinds_posted) {
+		    Some(Blind::Small) => {
+			// collect small blind!
+			return PlayerAction::PostSmallBlind(cmp::min(self.small_blind, player.money));
+		    }
+		    Some(Blind::Big) => {
+			// collect big blind!
+			// with a big blind ante, the ante for the whole table comes with the blind
+			let ante = match self.ante_mode {
+			    AnteMode::BigBlind(ante) => ante,
+			    _ => 0,
+			};
+			return PlayerAction::PostBigBlind(cmp::min(self.big_blind + ante, player.money));
+		    }
+		    None => {
+			// past the blinds, a player who opted in can straddle while nobody has acted yet
+			let straddle = gamehand.current_bet * 2;
+			if player.straddle
+			    && !gamehand.straddling_closed
+			    && gamehand.num_straddles < self.max_straddles as usize
+			    && gamehand.street_contribution(index) == 0
+			    && player.money >= straddle
+			{
+			    return PlayerAction::PostStraddle(straddle);
+			}
+		    }
+		}
             }
+	    if player.quick_fold {
+		// any blind has been posted by now, so the queued fold goes through without a prompt.
+		// It is used up, so it doesn't carry over to the next hand
+		log::debug!("player queued a quick fold");
+		self.players[index].as_mut().unwrap().quick_fold = false;
+		return PlayerAction::Fold;
+	    }
 
-	    let player_cumulative = gamehand.street_contributions.get(&gamehand.street).unwrap()[index];	
-	    let prompt = if gamehand.current_bet > player_cumulative {
-		let diff = gamehand.current_bet - player_cumulative;
-		format!("Enter action ({} to call): ", diff)
+	    let to_call = gamehand.amount_to_call(index);
+	    let prompt = if to_call > 0 {
+		format!("Enter action ({} to call): ", to_call)
 	    } else {
 		format!("Enter action (current bet = {}): ", gamehand.current_bet)
 	    };
 	    let message = object! {
 		msg_type: "prompt".to_owned(),
 		prompt: prompt,
-        current_bet: gamehand.current_bet
+		current_bet: gamehand.current_bet,
+		to_call: to_call,
+		time_bank_remaining: player.time_bank_seconds,
 	    };
 	    PlayerConfig::send_specific_message(
 		&message.dump(),
@@ -1170,46 +3907,80 @@ impl Table {
 	};
         let mut action = None;
         let mut attempts = 0;
+        let mut invalid_actions = 0;
+        let mut rejected = false; // whether the last action received was turned down
+        let mut too_many_invalid = false;
+        let mut waiting = false; // whether nothing has come in yet this attempt, so we wait a moment for it
+        let action_timeout = self.scaled_action_timeout(gamehand);
         let retry_duration = time::Duration::from_secs(1); // how long to wait between trying again
 	let between_hands = false;		
-        while attempts < 45 && action.is_none() {
+        while action.is_none() {
             // the first thing we do on each loop is handle meta action
             // this lets us display messages in real-time without having to wait until after the
             // current player gives their action
-            self.handle_meta_actions(&incoming_meta_actions, between_hands, Some(gamehand));
+            self.handle_meta_actions(incoming_meta_actions, between_hands, Some(gamehand));
 	    {
-		let player = self.players[index].as_ref().unwrap();	   	
-		let player_cumulative = gamehand.street_contributions.get(&gamehand.street).unwrap()[index];
-		if player.human_controlled {
+		let player = self.players[index].as_mut().unwrap();
+		if rejected && player.human_controlled {
+		    // they were already told why, but there is no point waiting on someone who keeps at it
+		    invalid_actions += 1;
+		    if invalid_actions >= MAX_INVALID_ACTIONS {
+			too_many_invalid = true;
+			break;
+		    }
+		}
+		if player.human_controlled && !self.clocks_frozen {
 		    // we don't need to count the attempts at getting a response from a computer
 		    // TODO: the computer can give a better than random guess at a move
 		    // Currently it might try to check when it has to call for example,
-		    attempts += 1;
+		    // Also, nobody's clock runs down while the clocks are frozen
+		    if attempts < action_timeout {
+			attempts += 1;
+		    } else if player.time_bank_seconds > 0 {
+			// the normal clock ran out, so draw down the time bank
+			player.time_bank_seconds -= 1;
+		    } else {
+			break;
+		    }
 		}
+	    }
+	    {
+		let player = self.players[index].as_ref().unwrap();
+		let player_cumulative = gamehand.street_contributions.get(&gamehand.street).unwrap()[index];
 		if player.is_sitting_out {
-		    println!("player is sitting out, so sitout/fold");
+		    log::debug!("player is sitting out, so sitout/fold");
 		    action = Some(PlayerAction::SitOut);
 		    break;
 		}
+		if player.quick_fold {
+		    // queued while we were waiting on them
+		    self.players[index].as_mut().unwrap().quick_fold = false;
+		    action = Some(PlayerAction::Fold);
+		    break;
+		}
 		if !self.player_ids_to_configs.contains_key(&player.id) {
 		    // the config no longer exists for this player, so they must have left
-		    println!("player config no longer exists, so the player must have left");
+		    log::debug!("player config no longer exists, so the player must have left");
 		    action = Some(PlayerAction::Fold);
 		    break;
 		}
 
-		println!("Attempting to get player action on attempt {:?}", attempts);
-		match self.get_action_from_player(incoming_actions, &player) {
+		log::debug!("Attempting to get player action on attempt {:?}", attempts);
+		let action_opt = self
+		    .get_action_from_player(incoming_actions, gamehand, index, player)
+		    .map(|action| action.normalize(gamehand.current_bet));
+		// anything received that doesn't end up as the action was turned down
+		rejected = action_opt.is_some();
+		match action_opt {
 		    None => {
-			// we give the user a second to place their action
-			thread::sleep(retry_duration);
+			waiting = true;
 		    }
 
 		    Some(PlayerAction::Fold) => {
 			if gamehand.current_bet <= player_cumulative {
 			    // if the player has put in enough then no sense folding
 			    if player.human_controlled {
-				println!("you said fold but we will let you check!");
+				log::debug!("you said fold but we will let you check!");
 				let message = json::object! {
 				msg_type: "error".to_owned(),
 				error: "invalid_action".to_owned(),
@@ -1250,7 +4021,7 @@ impl Table {
 			if gamehand.current_bet <= player_cumulative {
 			    if gamehand.current_bet != 0 {
 				// if the street bet isn't 0 then this makes no sense
-				println!("should we even be here???!");
+				log::warn!("should we even be here???!");
 			    }
 			    let message = json::object! {
 				msg_type: "error".to_owned(),
@@ -1267,14 +4038,73 @@ impl Table {
 			}
 			action = Some(PlayerAction::Call);
 		    }
-		    Some(PlayerAction::Bet(new_bet)) => {
+		    Some(bet_or_raise @ (PlayerAction::Bet(_) | PlayerAction::Raise(_))) => {
 			if gamehand.current_bet < player_cumulative {
 			    // will this case happen?
-			    println!("this should not happen!");
+			    log::warn!("this should not happen!");
+			    continue;
+			}
+			// a bet opens the betting on the street, and a raise goes on top of an existing bet.
+			// Either way, we work out the new total for the street
+			let (new_bet, reason) = match bet_or_raise {
+			    PlayerAction::Bet(new_bet) => (
+				new_bet,
+				"There is already a bet, so you need to raise!",
+			    ),
+			    PlayerAction::Raise(amount) => (
+				gamehand.current_bet.saturating_add(amount),
+				"There is no bet to raise, so you need to bet!",
+			    ),
+			    _ => unreachable!(),
+			};
+			if self.check_it_down && gamehand.current_bet == 0 && self.is_anyone_all_in() {
+			    // with an all-in player, the others agreed to check it down
+			    // rather than betting into the (dry) side pot
+			    log::debug!("no betting into a side pot while checking it down");
+			    let message = json::object! {
+				msg_type: "error".to_owned(),
+				error: "invalid_action".to_owned(),
+				reason: "A player is all-in, so the hand is being checked down!".to_owned(),
+			    };
+			    PlayerConfig::send_specific_message(
+				&message.dump(),
+				player.id,
+				&self.player_ids_to_configs,
+			    );
+			    continue;
+			}
+			if gamehand.acted_since_full_raise.contains(&player.id) {
+			    // the only raises since this player acted were all-ins for less than a full raise,
+			    // which don't reopen the betting to them
+			    log::debug!("betting was not reopened for this player");
+			    let message = json::object! {
+				msg_type: "error".to_owned(),
+				error: "invalid_action".to_owned(),
+				reason: "The betting was not reopened by a full raise, so you can only call or fold!".to_owned(),
+			    };
+			    PlayerConfig::send_specific_message(
+				&message.dump(),
+				player.id,
+				&self.player_ids_to_configs,
+			    );
+			    continue;
+			}
+			if matches!(bet_or_raise, PlayerAction::Bet(_)) != (gamehand.current_bet == 0) {
+			    log::debug!("wrong kind of bet for the current bet");
+			    let message = json::object! {
+				msg_type: "error".to_owned(),
+				error: "invalid_action".to_owned(),
+				reason: reason.to_owned(),
+			    };
+			    PlayerConfig::send_specific_message(
+				&message.dump(),
+				player.id,
+				&self.player_ids_to_configs,
+			    );
 			    continue;
 			}
 			if new_bet > player.money + player_cumulative {
-			    println!("cant bet more than you have");
+			    log::debug!("cant bet more than you have");
 			    let message = json::object! {
 				msg_type: "error".to_owned(),
 				error: "invalid_action".to_owned(),
@@ -1288,7 +4118,7 @@ impl Table {
 			    continue;
 			}
 			if new_bet <= gamehand.current_bet {
-			    println!("new bet must be larger than current");
+			    log::debug!("new bet must be larger than current");
 			    let message = json::object! {
 				msg_type: "error".to_owned(),
 				error: "invalid_action".to_owned(),
@@ -1301,13 +4131,51 @@ impl Table {
 			    );
 			    continue;
 			}
-			action = Some(PlayerAction::Bet(new_bet));
+			if let Err(reason) = self.check_betting_structure(gamehand, new_bet, player_cumulative, player.money) {
+			    log::debug!("the bet is not allowed by the betting structure");
+			    let message = json::object! {
+				msg_type: "error".to_owned(),
+				error: "invalid_action".to_owned(),
+				reason: reason,
+			    };
+			    PlayerConfig::send_specific_message(
+				&message.dump(),
+				player.id,
+				&self.player_ids_to_configs,
+			    );
+			    continue;
+			}
+			let min_raise_to = gamehand.min_raise_to(self.min_raise_rule);
+			if new_bet < min_raise_to && new_bet < player.money + player_cumulative {
+			    // a raise smaller than the minimum is only allowed when going all-in
+			    log::debug!("new bet is smaller than the minimum raise");
+			    let message = json::object! {
+				msg_type: "error".to_owned(),
+				error: "invalid_action".to_owned(),
+				reason: format!("the minimum raise is to {}!", min_raise_to),
+			    };
+			    PlayerConfig::send_specific_message(
+				&message.dump(),
+				player.id,
+				&self.player_ids_to_configs,
+			    );
+			    continue;
+			}
+			action = Some(bet_or_raise);
 		    }
 		    other => {
 			action = other;
 		    }
 		}
 	    }
+	    if waiting {
+		// we give the user a second to place their action, but pick it up as soon as it comes in.
+		// In the meantime the meta actions are still handled, so e.g. the chat keeps flowing
+		waiting = false;
+		self.pause_until(retry_duration, incoming_meta_actions, between_hands, Some(gamehand), || {
+		    incoming_actions.lock().unwrap().contains_key(&player_id)
+		});
+	    }
         }
         // if we got a valid action, then we can return it,
         // otherwise, we timed out, so sit out
@@ -1317,7 +4185,23 @@ impl Table {
 		player_config.heart_beat = time::Instant::now();
 	    }
 	    action
+        } else if too_many_invalid {
+	    // unlike a timeout, the player is clearly still there, so they don't sit out
+	    let action = if gamehand.amount_to_call(index) == 0 { PlayerAction::Check } else { PlayerAction::Fold };
+	    self.announce_forced_action(
+		index,
+		action,
+		"invalid_action",
+		format!("After {} invalid actions, the player was made to {}", MAX_INVALID_ACTIONS, action),
+	    );
+	    action
         } else {
+	    self.announce_forced_action(
+		index,
+		PlayerAction::SitOut,
+		"timeout",
+		"The player ran out of time, so they are sitting out".to_owned(),
+	    );
 	    // send a meta action (to ourself) that this player should be sitting out
             incoming_meta_actions
                 .lock()
@@ -1329,12 +4213,142 @@ impl Table {
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
     use crate::logic::card::{Rank, Suit};
     use crate::logic::deck::RiggedDeck;    
+    use std::str::FromStr;
+    use crate::messages::test_helpers::start_collector;
     use std::collections::HashMap;
 
+    /// the (pot index, seat, amount) of every structured payout that won chips, in the finish_hand message
+    fn winning_payouts(messages: &Mutex<Vec<String>>) -> Vec<(usize, usize, u32)> {
+        let finish_hand = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "finish_hand")
+            .unwrap();
+        let mut payouts: Vec<(usize, usize, u32)> = finish_hand["payouts"]
+            .members()
+            .filter(|payout| payout["amount_won"].as_u32().unwrap() > 0)
+            .map(|payout| (
+                payout["pot_index"].as_usize().unwrap(),
+                payout["seat"].as_usize().unwrap(),
+                payout["amount_won"].as_u32().unwrap(),
+            ))
+            .collect();
+        payouts.sort();
+        payouts
+    }
+
+    /// a table of humans whose actions come from a script, so a test can drive hands without sleeping
+    /// until the table is ready for each action. A player's next scripted action is handed to the table
+    /// as soon as their last one is used, so the script only has to be in order for each player.
+    /// There are no pauses between actions or streets either
+    struct TestTable {
+        table: Table,
+        ids: Vec<Uuid>,
+        messages: Arc<Mutex<Vec<String>>>, // everything sent to the player in seat 0
+        scripts: HashMap<Uuid, VecDeque<PlayerAction>>,
+        incoming_actions: Arc<Mutex<HashMap<Uuid, PlayerAction>>>,
+        incoming_meta_actions: Arc<Mutex<VecDeque<MetaAction>>>,
+    }
+
+    impl TestTable {
+        /// the given number of humans, seated in order from seat 0
+        fn new(num_players: usize) -> Self {
+            let mut table = Table::default();
+            table.max_pause = Some(time::Duration::ZERO);
+            let (addr, messages) = start_collector();
+            let ids: Vec<Uuid> = (0..num_players).map(|_| uuid::Uuid::new_v4()).collect();
+            for (i, id) in ids.iter().enumerate() {
+                let addr = if i == 0 { Some(addr.clone()) } else { None };
+                let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+                table.add_human(settings, None, None).unwrap();
+            }
+            Self {
+                table,
+                ids,
+                messages,
+                scripts: HashMap::new(),
+                incoming_actions: Arc::new(Mutex::new(HashMap::new())),
+                incoming_meta_actions: Arc::new(Mutex::new(VecDeque::new())),
+            }
+        }
+
+        /// the player in this seat takes the action at their next decision
+        fn act(&mut self, seat: usize, action: PlayerAction) -> &mut Self {
+            self.scripts.entry(self.ids[seat]).or_default().push_back(action);
+            self
+        }
+
+        /// the latest message of this type sent to seat 0. The messages arrive on another thread,
+        /// so this waits for the first one to show up
+        fn last_message(&self, msg_type: &str) -> json::JsonValue {
+            let start = time::Instant::now();
+            loop {
+                let found = self
+                    .messages
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .rev()
+                    .map(|message| json::parse(message).unwrap())
+                    .find(|message| message["msg_type"] == msg_type);
+                if let Some(message) = found {
+                    return message;
+                }
+                assert!(start.elapsed() < time::Duration::from_secs(1), "no {} message", msg_type);
+                thread::yield_now();
+            }
+        }
+
+        /// hand over the next scripted action of anyone whose last one has been used
+        fn feed(incoming_actions: &Mutex<HashMap<Uuid, PlayerAction>>, scripts: &mut HashMap<Uuid, VecDeque<PlayerAction>>) {
+            let mut actions = incoming_actions.lock().unwrap();
+            for (id, script) in scripts.iter_mut() {
+                if !actions.contains_key(id) {
+                    if let Some(action) = script.pop_front() {
+                        actions.insert(*id, action);
+                    }
+                }
+            }
+        }
+
+        /// run the table on its own thread, feeding it the scripted actions as it goes
+        fn run<R: Send>(
+            &mut self,
+            f: impl FnOnce(&mut Table, &Arc<Mutex<HashMap<Uuid, PlayerAction>>>, &Arc<Mutex<VecDeque<MetaAction>>>) -> R + Send,
+        ) -> R {
+            let TestTable { table, scripts, incoming_actions, incoming_meta_actions, .. } = self;
+            // the first actions are already waiting when the hand starts
+            TestTable::feed(incoming_actions, scripts);
+            thread::scope(|scope| {
+                let handle = scope.spawn(|| f(table, incoming_actions, incoming_meta_actions));
+                while !handle.is_finished() {
+                    TestTable::feed(incoming_actions, scripts);
+                    thread::yield_now();
+                }
+                handle.join().unwrap()
+            })
+        }
+
+        /// play one hand. Returns whether the hand was played
+        fn play_hand(&mut self) -> bool {
+            self.run(|table, actions, meta_actions| table.play_one_hand(actions, meta_actions))
+        }
+
+        /// play the table's main loop until it has played this many hands.
+        /// The table clears any actions left over once a hand's betting closes,
+        /// so script one hand at a time with play_hand() when more than one needs actions
+        fn play(&mut self, hand_limit: u32) {
+            self.run(|table, actions, meta_actions| table.play(actions, meta_actions, Some(hand_limit)))
+        }
+    }
+
     #[test]
     fn add_bot() {
         let mut table = Table::default();
@@ -1354,7 +4368,7 @@ mod tests {
         let id = uuid::Uuid::new_v4();
         let name = "Human".to_string();
         let settings = PlayerConfig::new(id, Some(name), None);
-        table.add_human(settings, None).expect("could not add user");
+        table.add_human(settings, None, None).expect("could not add user");
         assert_eq!(table.players.len(), 9);
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
@@ -1381,7 +4395,7 @@ mod tests {
         incoming_meta_actions
             .lock()
             .unwrap()
-            .push_back(MetaAction::Join(settings, Some(password)));
+            .push_back(MetaAction::Join(settings, Some(password), None));
 
         table.handle_meta_actions(&cloned_meta_actions, true, None);
         assert_eq!(table.players.len(), 9);
@@ -1407,7 +4421,7 @@ mod tests {
         incoming_meta_actions
             .lock()
             .unwrap()
-            .push_back(MetaAction::Join(settings, Some("345".to_string())));
+            .push_back(MetaAction::Join(settings, Some("345".to_string()), None));
 
         table.handle_meta_actions(&incoming_meta_actions, true, None);
 	
@@ -1433,7 +4447,7 @@ mod tests {
         incoming_meta_actions
             .lock()
             .unwrap()
-            .push_back(MetaAction::Join(settings, None)); // no password passed in
+            .push_back(MetaAction::Join(settings, None, None)); // no password passed in
 
         table.handle_meta_actions(&incoming_meta_actions, true, None);	
 
@@ -1471,136 +4485,607 @@ mod tests {
         assert_eq!(some_players as u8, max_players);
     }
 
+    /// a 6-max table only has six seats, so the 7th player is turned away,
+    /// and the button wraps around after the 6th seat
+    #[actix::test]
+    async fn six_seat_table() {
+        use actix::Actor;
+        let hub = TableHub::new().start();
+        let mut table = Table::new(hub, "ABCD".to_string(), None, None, 6, 4, 8, 1000, None, Uuid::new_v4());
+        assert_eq!(table.players.len(), 6);
+        for i in 0..6 {
+            assert_eq!(table.add_bot(format!("Bot {}", i)).unwrap(), i);
+        }
+        assert!(matches!(
+            table.add_bot("Bot 6".to_string()),
+            Err(JoinTableError::GameIsFull)
+        ));
+
+        table.button_idx = 5;
+        assert_eq!(table.find_next_button(), Ok(0));
+    }
+
+    /// a player who reconnects mid-hand is sent the game state right away,
+    /// with their own hole cards and whose turn it is
+    #[test]
+    fn reconnect_resyncs_game_state() {
+        let mut table = Table::default();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            table.add_human(PlayerConfig::new(*id, Some(format!("Human{}", i)), None), None, None).unwrap();
+            table.players[i].as_mut().unwrap().is_active = true;
+        }
+        table.players[1].as_mut().unwrap().hole_cards = vec![
+            Card { rank: Rank::Queen, suit: Suit::Heart },
+            Card { rank: Rank::Queen, suit: Suit::Spade },
+        ];
+        let mut gamehand = GameHand::default();
+        gamehand.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+        gamehand.index_to_act = Some(1);
+
+        let (addr, messages) = start_collector();
+        let meta_actions = Arc::new(Mutex::new(VecDeque::from([MetaAction::UpdateAddress(ids[1], addr)])));
+        table.handle_meta_actions(&meta_actions, false, Some(&gamehand));
+
+        let mut state = None;
+        for _ in 0..50 {
+            state = messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .find(|message| message["msg_type"] == "game_state");
+            if state.is_some() {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        let state = state.unwrap();
+        assert_eq!(state["your_index"], 1);
+        assert_eq!(state["hole_cards"], "QhQs");
+        assert_eq!(state["index_to_act"], 1);
+    }
+
+    /// a player who joins mid-hand is caught up with the latest actions of the hand, oldest first
+    #[test]
+    fn mid_hand_joiner_gets_recent_actions() {
+        let mut table = Table::default();
+        for i in 0..3 {
+            let settings = PlayerConfig::new(uuid::Uuid::new_v4(), Some(format!("Human{}", i)), None);
+            table.add_human(settings, None, None).unwrap();
+        }
+        let mut gamehand = GameHand::default();
+        gamehand.action_log = vec![
+            (1, PlayerAction::PostSmallBlind(4), Street::Preflop),
+            (2, PlayerAction::PostBigBlind(8), Street::Preflop),
+            (0, PlayerAction::Raise(16), Street::Preflop),
+            (1, PlayerAction::Call, Street::Preflop),
+            (2, PlayerAction::Call, Street::Preflop),
+            (1, PlayerAction::Check, Street::Flop),
+            (2, PlayerAction::Check, Street::Flop),
+            (0, PlayerAction::Bet(30), Street::Flop),
+            (1, PlayerAction::Fold, Street::Flop),
+            (2, PlayerAction::Raise(60), Street::Flop),
+        ];
+
+        let (addr, messages) = start_collector();
+        let joiner = uuid::Uuid::new_v4();
+        let settings = PlayerConfig::new(joiner, Some("Joiner".to_string()), Some(addr));
+        let meta_actions = Arc::new(Mutex::new(VecDeque::from([MetaAction::Join(settings, None, None)])));
+        table.handle_meta_actions(&meta_actions, false, Some(&gamehand));
+
+        let mut recent = None;
+        for _ in 0..50 {
+            recent = messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .find(|message| message["msg_type"] == "recent_actions");
+            if recent.is_some() {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        let recent = recent.unwrap();
+        // only the last few are sent, ending with the action that was just taken
+        assert_eq!(recent["actions"].len(), RECENT_ACTIONS);
+        assert_eq!(recent["actions"][0], object! { seat: 0, action: "raise:16", street: "preflop" });
+        let last = &recent["actions"][RECENT_ACTIONS - 1];
+        assert_eq!(last["seat"], 2);
+        assert_eq!(last["action"], "raise:60");
+        assert_eq!(last["street"], "flop");
+    }
+
+    /// a saved table is loaded back with the same seats, stacks, button and hand number,
+    /// and with every player disconnected until they reconnect
+    #[actix::test]
+    async fn save_and_load() {
+        use actix::Actor;
+        let hub = TableHub::new().start();
+        let mut table = Table::new(hub.clone(), "ABCD".to_string(), None, None, 6, 4, 8, 1000, Some("secret".to_string()), Uuid::new_v4());
+        let (addr, _messages) = start_collector();
+        let id = uuid::Uuid::new_v4();
+        table.add_human(PlayerConfig::new(id, Some("Human".to_owned()), Some(addr)), Some("secret".to_string()), None).unwrap();
+        table.add_bot("Bot 1".to_string()).unwrap();
+        table.add_bot("Bot 2".to_string()).unwrap();
+        table.players[0].as_mut().unwrap().money = 1234;
+        table.players[2].as_mut().unwrap().money = 766;
+        table.button_idx = 2;
+        table.hand_num = 17;
+        table.ante_mode = AnteMode::BigBlind(8);
+        table.ante_order = AnteOrder::AfterBlinds;
+        table.betting_structure = BettingStructure::PotLimit;
+        table.variant = GameVariant::ShortDeck;
+        let mut schedule = BlindSchedule::parse("10 4/8/0,8/16/2,15/30/5").unwrap();
+        let now = time::Instant::now();
+        schedule.start(table.ante_mode, now - time::Duration::from_secs(15 * 60));
+        schedule.advance_if_due(now - time::Duration::from_secs(5 * 60));
+        table.blind_schedule = Some(schedule);
+        table.rake_percent = 5;
+        table.rake_cap = Some(30);
+        table.total_raked = 120;
+        table.max_straddles = 2;
+        table.run_it_twice = true;
+        table.action_timeout = 20;
+        table.time_bank = 60;
+        table.time_bank_replenish = 10;
+        table.finish_delay = 1;
+        table.max_finish_delay = 5;
+        table.all_in_reveal_delay = time::Duration::from_millis(1500);
+
+        let path = std::env::temp_dir().join(format!("{}.json", Uuid::new_v4()));
+        table.save(&path).unwrap();
+        let loaded = Table::load(&path, hub).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.name, "ABCD");
+        assert_eq!(loaded.players.len(), 6);
+        let stacks: Vec<Option<u32>> = loaded.players.iter().map(|spot| spot.as_ref().map(|player| player.money)).collect();
+        let expected: Vec<Option<u32>> = table.players.iter().map(|spot| spot.as_ref().map(|player| player.money)).collect();
+        assert_eq!(stacks, expected);
+        assert_eq!(loaded.button_idx, 2);
+        assert_eq!(loaded.hand_num, 17);
+        assert_eq!((loaded.small_blind, loaded.big_blind, loaded.buy_in), (4, 8, 1000));
+        assert!(loaded.has_password());
+        assert_eq!(loaded.human_ids(), vec![id]);
+        let config = &loaded.player_ids_to_configs[&id];
+        assert_eq!(config.name.as_deref(), Some("Human"));
+        assert!(config.player_addr.is_none());
+        assert_eq!(loaded.player_ids_to_configs.len(), 3);
+
+        // the settings survive too
+        assert_eq!(loaded.ante_mode, AnteMode::BigBlind(8));
+        assert_eq!(loaded.ante_order, AnteOrder::AfterBlinds);
+        assert_eq!(loaded.betting_structure, BettingStructure::PotLimit);
+        assert_eq!(loaded.variant, GameVariant::ShortDeck);
+        assert_eq!(loaded.deck.remaining(), 36);
+        let schedule = loaded.blind_schedule.as_ref().unwrap();
+        assert_eq!(schedule.levels(), &[(4, 8, 0), (8, 16, 2), (15, 30, 5)]);
+        assert_eq!(schedule.current_level(), 1);
+        assert_eq!(schedule.ante_mode(1), AnteMode::BigBlind(2));
+        // the level picks up where it left off, rather than starting over
+        let remaining = schedule.time_remaining(time::Instant::now()).unwrap();
+        assert!(remaining <= time::Duration::from_secs(5 * 60) && remaining > time::Duration::from_secs(4 * 60));
+        assert_eq!((loaded.rake_percent, loaded.rake_cap, loaded.total_raked), (5, Some(30), 120));
+        assert_eq!(loaded.max_straddles, 2);
+        assert!(loaded.run_it_twice);
+        assert_eq!((loaded.action_timeout, loaded.time_bank, loaded.time_bank_replenish), (20, 60, 10));
+        assert_eq!((loaded.finish_delay, loaded.max_finish_delay), (1, 5));
+        assert_eq!(loaded.all_in_reveal_delay, time::Duration::from_millis(1500));
+    }
+
+    /// the game state lists the empty seats below max_players
+    #[test]
+    fn available_seats_in_game_state() {
+        let mut table = Table::default();
+        table.max_players = 5;
+        for i in 0..4 {
+            table.add_bot(format!("Bot {}", i)).unwrap();
+        }
+        // open up seats 1 and 3
+        table.players[1] = None;
+        table.players[3] = None;
+
+        let state = table.game_state(None, false).to_json();
+        let seats: Vec<usize> = state["available_seats"]
+            .members()
+            .map(|seat| seat.as_usize().unwrap())
+            .collect();
+        assert_eq!(seats, vec![1, 3, 4]);
+
+        // once max_players are seated, nothing is available
+        table.max_players = 2;
+        let state = table.game_state(None, false).to_json();
+        assert!(state["available_seats"].is_empty());
+    }
+
+    /// the typed game state keeps the field names the front-end reads, only sends the optional ones
+    /// when they apply, and deserializes back into the same state
+    #[test]
+    fn game_state_keys() {
+        let mut table = Table::default();
+        table.set_show_deck_count(true);
+        for i in 0..2 {
+            let settings = PlayerConfig::new(uuid::Uuid::new_v4(), Some(format!("Human{}", i)), None);
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.players[1].as_mut().unwrap().is_sitting_out = true;
+        let mut gamehand = GameHand::default();
+        gamehand.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+        gamehand.index_to_act = Some(0);
+
+        let keys = |value: &json::JsonValue| {
+            let mut keys: Vec<String> = value.entries().map(|(key, _)| key.to_owned()).collect();
+            keys.sort();
+            keys
+        };
+        let state = table.game_state(Some(&gamehand), false);
+        let value = json::parse(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(
+            keys(&value),
+            [
+                "ante_mode", "available_seats", "betting_structure", "big_blind", "button_idx", "buy_in",
+                "clocks_frozen", "current_bet", "deck_remaining", "game_suspended", "hand_num", "hole_cards",
+                "index_to_act", "made_hand", "max_players", "msg_type", "name", "password", "players", "pots",
+                "small_blind", "street", "total_pot", "variant", "your_index",
+            ]
+        );
+        assert_eq!(value["msg_type"], "game_state");
+        assert_eq!(
+            keys(&value["players"][0]),
+            ["index", "invested", "is_active", "money", "player_name", "preflop_cont"]
+        );
+        assert_eq!(value["players"][1]["is_sitting_out"], true);
+        assert!(value["players"][2].is_null());
+
+        // between hands, none of the hand fields are sent
+        let value = table.game_state(None, false).to_json();
+        assert!(!value.has_key("street") && !value.has_key("pots") && !value.has_key("index_to_act"));
+        assert!(!value["players"][0].has_key("invested"));
+
+        let parsed: GameStateDto = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(parsed, state);
+    }
+
     /// the small blind folds, so the big blind should win and get paid
     #[test]
     fn instant_fold() {
+        // seat 0 starts as the button, which heads-up is the small blind
+        let mut game = TestTable::new(2);
+        game.act(0, PlayerAction::Fold);
+        assert!(game.play_hand());
+
+        // check that the money changed hands
+        assert_eq!(game.table.players[0].as_ref().unwrap().money, 996);
+        assert_eq!(game.table.players[1].as_ref().unwrap().money, 1004);
+    }
+
+    /// an action that is already waiting when the hand starts should be honored,
+    /// rather than being drained as a lingering action from a previous hand
+    #[test]
+    fn action_at_hand_start_honored() {
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
         let id1 = uuid::Uuid::new_v4();
-        let name1 = "Human1".to_string();
-        let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), None);
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
         let id2 = uuid::Uuid::new_v4();
-        let name2 = "Human1".to_string();
-        let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
-        // flatten to get all the Some() players
-        let some_players = table.players.iter().flatten().count();
-        assert_eq!(some_players, 2);
-        assert!(table.players[0].as_ref().unwrap().human_controlled);
-
-        let handler = std::thread::spawn(move || {
-            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
-            table // return the table back
-        });
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
 
-        // set the action that player2 folds
+        // the small blind folds before the hand has even started
         incoming_actions
             .lock()
             .unwrap()
             .insert(id2, PlayerAction::Fold);
 
-        // get the game back from the thread
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
         let table = handler.join().unwrap();
 
-        // check that the money changed hands
         assert_eq!(table.players[0].as_ref().unwrap().money, 1004);
         assert_eq!(table.players[1].as_ref().unwrap().money, 996);
+        // the betting closed, so nothing lingers for the next hand
+        assert!(incoming_actions.lock().unwrap().is_empty());
     }
 
-    /// the small blind calls, the big blind checks to the flop
-    /// the small blind bets on the flop, and the big blind folds
+    /// a losing player with a longer auto muck delay keeps their cards until the delay passes,
+    /// while a player with no delay has their losing hand mucked right away
     #[test]
-    fn call_check_bet_fold() {
+    fn auto_muck_delay() {
         let mut table = Table::default();
-        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
-        let cloned_actions = incoming_actions.clone();
-        let cloned_meta_actions = incoming_meta_actions.clone();
-
-        // player1 will start as the button
-        let id1 = uuid::Uuid::new_v4();
-        let name1 = "Human1".to_string();
-        let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
-
-        // player2 will start as the small blind
-        let id2 = uuid::Uuid::new_v4();
-        let name2 = "Human1".to_string();
-        let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
-        // flatten to get all the Some() players
-        let some_players = table.players.iter().flatten().count();
-        assert_eq!(some_players, 2);
-        assert!(table.players[0].as_ref().unwrap().human_controlled);
-
-        let handler = std::thread::spawn(move || {
-            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
-            table // return the table back
-        });
-
-        // set the action that player2 calls
-        incoming_actions
+	let mut ids = vec![];
+	for i in 0..3 {
+            let id = uuid::Uuid::new_v4();
+            let settings = PlayerConfig::new(id, Some(format!("Human{}", i)), None);
+            table.add_human(settings, None, None).unwrap();
+	    ids.push(id);
+	}
+        incoming_meta_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Call);
-        // player1 checks
-        incoming_actions
+            .push_back(MetaAction::AutoMuckDelay(ids[0], 0));
+        incoming_meta_actions
             .lock()
             .unwrap()
-            .insert(id1, PlayerAction::Check);
-
-        // wait for the flop
-        let wait_duration = time::Duration::from_secs(7);
-        thread::sleep(wait_duration);
+            .push_back(MetaAction::AutoMuckDelay(ids[1], 5));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
 
-        // player2 bets on the flop
-        println!("now sending the flop actions");
-        incoming_actions
-            .lock()
-            .unwrap()
-            .insert(id2, PlayerAction::Bet(10));
-        // player1 folds
-        incoming_actions
-            .lock()
-            .unwrap()
-            .insert(id1, PlayerAction::Fold);
+	for player in table.players.iter_mut().flatten() {
+	    player.hole_cards = vec![
+		Card { rank: Rank::Two, suit: Suit::Club },
+		Card { rank: Rank::Three, suit: Suit::Club },
+	    ];
+	}
+	// the third player won the hand
+	let winner_idxs = HashSet::from([2]);
+
+	assert!(table.muck_expired_hands(time::Duration::from_secs(2), &winner_idxs));
+	assert!(table.players[0].as_ref().unwrap().hole_cards.is_empty());
+	assert_eq!(table.players[1].as_ref().unwrap().hole_cards.len(), 2);
+	assert_eq!(table.players[2].as_ref().unwrap().hole_cards.len(), 2);
+
+	assert!(table.muck_expired_hands(time::Duration::from_secs(5), &winner_idxs));
+	assert!(table.players[1].as_ref().unwrap().hole_cards.is_empty());
+	// the winner is never mucked
+	assert_eq!(table.players[2].as_ref().unwrap().hole_cards.len(), 2);
+    }
 
-        // get the game back from the thread
-        let table = handler.join().unwrap();
+    /// the small blind calls, the big blind checks to the flop
+    /// the big blind checks the flop, the small blind bets, and the big blind folds
+    #[test]
+    fn call_check_bet_fold() {
+        let mut game = TestTable::new(2);
+        game.table.button_idx = 1; // heads-up, the button posts the small blind
+        game.act(1, PlayerAction::Call)
+            .act(0, PlayerAction::Check)
+            .act(0, PlayerAction::Check)
+            .act(1, PlayerAction::Bet(10))
+            .act(0, PlayerAction::Fold);
+        assert!(game.play_hand());
 
         // check that the money changed hands
-        assert_eq!(table.players[0].as_ref().unwrap().money, 992);
-        assert_eq!(table.players[1].as_ref().unwrap().money, 1008);
+        assert_eq!(game.table.players[0].as_ref().unwrap().money, 992);
+        assert_eq!(game.table.players[1].as_ref().unwrap().money, 1008);
+    }
+
+    /// the test table hands each player's scripted actions over one decision at a time, in order,
+    /// and never pauses, so a hand plays straight through without sleeping or waiting on the clock
+    #[test]
+    fn test_table_plays_scripts_in_order() {
+        let mut game = TestTable::new(2);
+        assert_eq!(game.table.pace(time::Duration::from_secs(30)), time::Duration::ZERO);
+        game.table.button_idx = 1; // heads-up, the button posts the small blind
+        // both players queue up their whole hand before it starts
+        game.act(0, PlayerAction::Check)
+            .act(0, PlayerAction::Check)
+            .act(0, PlayerAction::Bet(20))
+            .act(0, PlayerAction::Check)
+            .act(0, PlayerAction::Fold);
+        game.act(1, PlayerAction::Call)
+            .act(1, PlayerAction::Check)
+            .act(1, PlayerAction::Call)
+            .act(1, PlayerAction::Bet(40));
+        let start = time::Instant::now();
+        assert!(game.play_hand());
+
+        assert!(game.scripts.values().all(|script| script.is_empty()));
+        let log: Vec<(usize, String)> = game.last_message("finish_hand")["action_log"]
+            .members()
+            .skip(2) // the blinds
+            .map(|entry| (entry["seat"].as_usize().unwrap(), entry["action"].to_string()))
+            .collect();
+        let expected: Vec<(usize, String)> = [
+            (1, "call"), (0, "check"), // preflop
+            (0, "check"), (1, "check"), // flop
+            (0, "bet:20"), (1, "call"), // turn
+            (0, "check"), (1, "bet:40"), (0, "fold"), // river
+        ]
+        .iter()
+        .map(|(seat, action)| (*seat, action.to_string()))
+        .collect();
+        assert_eq!(log, expected);
+        assert_eq!(game.table.players[0].as_ref().unwrap().money, 972);
+        assert_eq!(game.table.players[1].as_ref().unwrap().money, 1028);
+        // nobody was left waiting on the action clock
+        assert!(start.elapsed() < time::Duration::from_secs(game.table.action_timeout.into()));
+    }
+
+    /// with a short all-in, the main pot and the side pot are paid out to different players,
+    /// and the finish_hand message says structurally who won how much of which pot, next to the readable summary
+    #[test]
+    fn side_pot_payouts() {
+        let mut game = TestTable::new(3);
+        game.table.deck = Box::new(RiggedDeck::from_str("As Ad Kc Kh Qc Qh 2s 3d 7s 9c 4c Jd 5s 8h").unwrap());
+        // seat 0 acts first, seat 1 is the small blind, and seat 2 the big blind
+        game.table.button_idx = 0;
+        game.table.players[0].as_mut().unwrap().money = 300;
+        game.act(0, PlayerAction::Raise(292))
+            .act(1, PlayerAction::Raise(700))
+            .act(2, PlayerAction::Call);
+        assert!(game.play_hand());
+
+        let stacks: Vec<u32> = game.table.players.iter().flatten().map(|player| player.money).collect();
+        assert_eq!(stacks, vec![900, 1400, 0]);
+        let finish_hand = game.last_message("finish_hand");
+        let payouts: Vec<(usize, usize, u32)> = finish_hand["payouts"]
+            .members()
+            .filter(|payout| payout["amount_won"].as_u32().unwrap() > 0)
+            .map(|payout| (
+                payout["pot_index"].as_usize().unwrap(),
+                payout["seat"].as_usize().unwrap(),
+                payout["amount_won"].as_u32().unwrap(),
+            ))
+            .collect();
+        assert_eq!(payouts, vec![(0, 0, 900), (1, 1, 1400)]);
+        // the player ids double as session ids, so they stay out of the message
+        assert!(finish_hand["payouts"].members().all(|payout| !payout.has_key("player_id")));
+        let side_pot_summary = finish_hand["settlements"]
+            .members()
+            .find(|summary| summary["pot_index"] == 1 && summary["winner"] == true)
+            .unwrap();
+        assert_eq!(side_pot_summary["player_name"], "Human1");
+        assert_eq!(side_pot_summary["payout"], 1400);
+    }
+
+    /// a card is burned before the flop, the turn and the river, so they never show up on the board,
+    /// and with auditing on they are listed in the finish_hand message
+    #[test]
+    fn burned_cards_skipped_on_board() {
+        let mut game = TestTable::new(2);
+        game.table.deck = Box::new(RiggedDeck::from_str("As Ad Kc Kh 2s 3d 7s 9c 4c Jd 5s 8h").unwrap());
+        game.table.set_audit_deck(true);
+        game.table.button_idx = 1; // heads-up, the button posts the small blind
+        game.act(1, PlayerAction::Raise(992)).act(0, PlayerAction::Call);
+        assert!(game.play_hand());
+
+        let finish_hand = game.last_message("finish_hand");
+        let burned: Vec<String> = finish_hand["burned_cards"].members().map(|card| card.to_string()).collect();
+        assert_eq!(burned, vec!["2s", "4c", "5s"]);
+        assert!(finish_hand["hand_history"].to_string().contains("Board [3d 7s 9c Jd 8h]"));
+    }
+
+    /// a hand with a showdown, exported as a text hand history
+    #[test]
+    fn hand_history() {
+        let mut game = TestTable::new(2);
+        game.table.deck = Box::new(RiggedDeck::from_str("As Ad 2c 7d 3h 9s 5c Kd 4h Qs 8s Jh Ts 6d").unwrap());
+        game.table.button_idx = 1; // heads-up, the button posts the small blind
+        game.act(1, PlayerAction::Raise(8))
+            .act(0, PlayerAction::Call)
+            .act(0, PlayerAction::Bet(20))
+            .act(1, PlayerAction::Raise(20))
+            .act(0, PlayerAction::Call)
+            .act(0, PlayerAction::Check)
+            .act(1, PlayerAction::Check)
+            .act(0, PlayerAction::Bet(50))
+            .act(1, PlayerAction::Call);
+        assert!(game.play_hand());
+
+        let history = game.last_message("finish_hand")["hand_history"].to_string();
+        let expected = [
+            "Hand #1: Hold'em No Limit (4/8)",
+            "Table 'Table' 9-max Seat #2 is the button",
+            "Seat 1: Human0 (1000 in chips)",
+            "Seat 2: Human1 (1000 in chips)",
+            "Human1: posts small blind 4",
+            "Human0: posts big blind 8",
+            "*** HOLE CARDS ***",
+            "Human1: raises 8 to 16",
+            "Human0: calls 8",
+            "*** FLOP *** [9s 5c Kd]",
+            "Human0: bets 20",
+            "Human1: raises 20 to 40",
+            "Human0: calls 20",
+            "*** TURN *** [9s 5c Kd] [Qs]",
+            "Human0: checks",
+            "Human1: checks",
+            "*** RIVER *** [9s 5c Kd Qs] [Jh]",
+            "Human0: bets 50",
+            "Human1: calls 50",
+            "*** SHOW DOWN ***",
+            "Human0: shows [As Ad]",
+            // the losing hand didn't have to be shown at the table, so it stays hidden here too
+            "Human0 collected 212 from pot",
+            "*** SUMMARY ***",
+            "Total pot 212 | Rake 0",
+            "Board [9s 5c Kd Qs Jh]",
+        ]
+        .join("\n");
+        assert_eq!(history, expected);
+    }
+
+    /// a losing player who auto mucks doesn't show their hand at the table once it is beaten,
+    /// so it stays hidden in the hand history too. The first to show still has to
+    #[test]
+    fn hand_history_leaves_out_mucked_hand() {
+        let mut game = TestTable::new(3);
+        game.table.deck = Box::new(RiggedDeck::from_str("As Ad 2c 7d Kc Kh 3h 9s 5c Qd 4h Js 6c 8h").unwrap());
+        // the small blind in seat 1 shows first, then the kings in seat 2, then the aces on the button
+        game.table.button_idx = 0;
+        game.table.players[2].as_mut().unwrap().auto_muck = true;
+        game.act(0, PlayerAction::Call).act(1, PlayerAction::Call).act(2, PlayerAction::Check);
+        for _ in 0..3 {
+            game.act(1, PlayerAction::Check).act(2, PlayerAction::Check).act(0, PlayerAction::Check);
+        }
+        assert!(game.play_hand());
+
+        let history = game.last_message("finish_hand")["hand_history"].to_string();
+        assert!(history.contains("Human1: shows [2c 7d]"));
+        assert!(history.contains("Human0: shows [As Ad]"));
+        assert!(!history.contains("Kc Kh"));
+        assert!(history.contains("Human0 collected 24 from pot"));
+    }
+
+    /// every action goes in the log in order, blinds included, along with its seat and street
+    #[test]
+    fn action_log() {
+        let mut game = TestTable::new(2);
+        game.table.button_idx = 1; // heads-up, the button posts the small blind
+        game.act(1, PlayerAction::Call)
+            .act(0, PlayerAction::Check)
+            .act(0, PlayerAction::Bet(10))
+            .act(1, PlayerAction::Call)
+            .act(0, PlayerAction::Bet(20))
+            .act(1, PlayerAction::Fold);
+        assert!(game.play_hand());
+
+        let log: Vec<(usize, String, String)> = game.last_message("finish_hand")["action_log"]
+            .members()
+            .map(|entry| (
+                entry["seat"].as_usize().unwrap(),
+                entry["action"].to_string(),
+                entry["street"].to_string(),
+            ))
+            .collect();
+        let expected = [
+            (1, "small_blind:4", "preflop"),
+            (0, "big_blind:8", "preflop"),
+            (1, "call", "preflop"),
+            (0, "check", "preflop"),
+            (0, "bet:10", "flop"),
+            (1, "call", "flop"),
+            (0, "bet:20", "turn"),
+            (1, "fold", "turn"),
+        ];
+        let expected: Vec<(usize, String, String)> = expected
+            .iter()
+            .map(|(seat, action, street)| (*seat, action.to_string(), street.to_string()))
+            .collect();
+        assert_eq!(log, expected);
     }
 
     /// the small blind bets, the big blind folds
     #[test]
     fn pre_flop_bet_fold() {
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human1".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -1611,14 +5096,11 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
-        // set the action that player2 bets
+        // set the action that player2 raises
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(22));
+            .insert(id2, PlayerAction::Raise(14));
         // player1 folds
         incoming_actions
             .lock()
@@ -1639,7 +5121,7 @@ mod tests {
     fn big_blind_not_enough_money() {
         let mut deck = RiggedDeck::new();
 
-        // we want the button/big blind to win
+        // we want the big blind to win
         deck.push(Card {
             rank: Rank::Ten,
             suit: Suit::Club,
@@ -1657,6 +5139,11 @@ mod tests {
             rank: Rank::Three,
             suit: Suit::Club,
         });
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // now the full run out
         deck.push(Card {
             rank: Rank::Ten,
@@ -1670,16 +5157,27 @@ mod tests {
             rank: Rank::King,
             suit: Suit::Club,
         });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Heart,
         });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
         deck.push(Card {
             rank: Rank::Queen,
             suit: Suit::Club,
         });
 
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         table.deck = Box::new(deck);
 
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
@@ -1687,18 +5185,18 @@ mod tests {
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button/big blind
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
         table.players[0].as_mut().unwrap().money = 3; // set the player to have less than the norm 8 BB
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human1".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -1708,15 +5206,12 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.5)); 
-	
-        // set the action that player (small blind) bets,
+        // set the action that player (small blind) raises,
         // even though player1 is already all-in, so the BB can only 3 win bucks
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(22));
+            .insert(id2, PlayerAction::RaiseTo(22));
 
         // get the game back from the thread
         let table = handler.join().unwrap();
@@ -1731,22 +5226,23 @@ mod tests {
     #[test]
     fn bet_call_bet_fold() {
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human1".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -1757,14 +5253,11 @@ mod tests {
             table // return the table back
         });
 	
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-		      
-        // set the action that player2 bets
+        // set the action that player2 raises
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(22));
+            .insert(id2, PlayerAction::Raise(14));
         // player1 calls
         incoming_actions
             .lock()
@@ -1800,7 +5293,7 @@ mod tests {
     fn all_in_call() {
         let mut deck = RiggedDeck::new();
 
-        // we want the button/big blind to lose for testing purposes
+        // we want the big blind to lose for testing purposes
         deck.push(Card {
             rank: Rank::Two,
             suit: Suit::Club,
@@ -1820,6 +5313,11 @@ mod tests {
             suit: Suit::Heart,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // now the full run out
         deck.push(Card {
             rank: Rank::Ten,
@@ -1833,33 +5331,44 @@ mod tests {
             rank: Rank::King,
             suit: Suit::Club,
         });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Heart,
         });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
         deck.push(Card {
             rank: Rank::Queen,
             suit: Suit::Club,
         });
 
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         table.deck = Box::new(deck);
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human1".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -1870,14 +5379,11 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
-        // set the action that player2 bets
+        // set the action that player2 raises
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(1000));
+            .insert(id2, PlayerAction::Raise(992));
         // player1 calls
         incoming_actions
             .lock()
@@ -1898,7 +5404,7 @@ mod tests {
     fn call_all_in() {
         let mut deck = RiggedDeck::new();
 
-        // we want the button/big blind to lose for testing purposes
+        // we want the big blind to lose for testing purposes
         deck.push(Card {
             rank: Rank::Two,
             suit: Suit::Club,
@@ -1918,6 +5424,11 @@ mod tests {
             suit: Suit::Heart,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // now the full run out
         deck.push(Card {
             rank: Rank::Ten,
@@ -1931,35 +5442,46 @@ mod tests {
             rank: Rank::King,
             suit: Suit::Club,
         });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Heart,
         });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
         deck.push(Card {
             rank: Rank::Queen,
             suit: Suit::Club,
         });
 
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         table.deck = Box::new(deck);
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
         table.players[0].as_mut().unwrap().money = 500; // set the player to have less money
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human1".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -1970,14 +5492,11 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
-        // set the action that player2 bets
+        // set the action that player2 raises
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(500));
+            .insert(id2, PlayerAction::Raise(492));
         // player1 calls
         incoming_actions
             .lock()
@@ -2000,7 +5519,7 @@ mod tests {
     fn small_stack_call_all_in() {
         let mut deck = RiggedDeck::new();
 
-        // we want the button/big blind to win for testing purposes
+        // we want the big blind to win for testing purposes
         deck.push(Card {
             rank: Rank::Ten,
             suit: Suit::Club,
@@ -2020,6 +5539,11 @@ mod tests {
             suit: Suit::Club,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // now the full run out
         deck.push(Card {
             rank: Rank::Ten,
@@ -2033,35 +5557,46 @@ mod tests {
             rank: Rank::King,
             suit: Suit::Club,
         });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Heart,
         });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
         deck.push(Card {
             rank: Rank::Queen,
             suit: Suit::Club,
         });
 
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         table.deck = Box::new(deck);
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button/big
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Big".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
         table.players[0].as_mut().unwrap().money = 500; // set the player to have less money
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Small".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -2072,14 +5607,11 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
-        // set the action that player2 bets a bunch
+        // set the action that player2 raises a bunch
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(1000));
+            .insert(id2, PlayerAction::Raise(992));
         // player1 calls
         incoming_actions
             .lock()
@@ -2134,6 +5666,11 @@ mod tests {
             suit: Suit::Heart,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // now the full run out
         deck.push(Card {
             rank: Rank::Three,
@@ -2147,10 +5684,20 @@ mod tests {
             rank: Rank::Nine,
             suit: Suit::Club,
         });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Heart,
         });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Club,
@@ -2166,8 +5713,9 @@ mod tests {
         // player1 will start as the button
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Button".to_string();
-        let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        let (addr, messages) = start_collector();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
         // set the button to have less money so there is a side pot
         table.players[0].as_mut().unwrap().money = 500;
 
@@ -2175,13 +5723,13 @@ mod tests {
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Small".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 
         // player3 will start as the big blind
         let id3 = uuid::Uuid::new_v4();
         let name3 = "Big".to_string();
         let settings3 = PlayerConfig::new(id3, Some(name3), None);
-        table.add_human(settings3, None).unwrap();
+        table.add_human(settings3, None, None).unwrap();
 
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
@@ -2195,19 +5743,16 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
         // the button goes all in with the short stack
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id1, PlayerAction::Bet(500));
+            .insert(id1, PlayerAction::Raise(492));
         // the small blind goes all in with a full stack
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(1000));
+            .insert(id2, PlayerAction::Raise(500));
         // the big blind calls the full all-in
         incoming_actions
             .lock()
@@ -2225,6 +5770,9 @@ mod tests {
 
         // the big blind lost everything
         assert_eq!(table.players[2].as_ref().unwrap().money, 0);
+
+        // the structured payouts move the main pot to the button, and the side pot to the small blind
+        assert_eq!(winning_payouts(&messages), vec![(0, 0, 1500), (1, 1, 1000)]);
     }
 
     /// if a player goes all-in, then can only win as much as is called up to that amount,
@@ -2248,7 +5796,7 @@ mod tests {
         // the small blind will tie the main and win the side pot against the big blind
         deck.push(Card {
             rank: Rank::Ace,
-            suit: Suit::Club,
+            suit: Suit::Spade,
         });
         deck.push(Card {
             rank: Rank::Ace,
@@ -2265,6 +5813,11 @@ mod tests {
             suit: Suit::Heart,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // now the full run out
         deck.push(Card {
             rank: Rank::Three,
@@ -2278,10 +5831,20 @@ mod tests {
             rank: Rank::Nine,
             suit: Suit::Club,
         });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Heart,
         });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Club,
@@ -2297,8 +5860,9 @@ mod tests {
         // player1 will start as the button
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Button".to_string();
-        let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        let (addr, messages) = start_collector();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
         // set the button to have less money so there is a side pot
         table.players[0].as_mut().unwrap().money = 500;
 
@@ -2306,13 +5870,13 @@ mod tests {
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Small".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 
         // player3 will start as the big blind
         let id3 = uuid::Uuid::new_v4();
         let name3 = "Big".to_string();
         let settings3 = PlayerConfig::new(id3, Some(name3), None);
-        table.add_human(settings3, None).unwrap();
+        table.add_human(settings3, None, None).unwrap();
 
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
@@ -2326,19 +5890,16 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
         // the button goes all in with the short stack
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id1, PlayerAction::Bet(500));
+            .insert(id1, PlayerAction::Raise(492));
         // the small blind goes all in with a full stack
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(1000));
+            .insert(id2, PlayerAction::Raise(500));
         // the big blind calls the full all-in
         incoming_actions
             .lock()
@@ -2356,6 +5917,9 @@ mod tests {
 
         // the big blind lost everything
         assert_eq!(table.players[2].as_ref().unwrap().money, 0);
+
+        // the main pot is split, and the side pot goes to the small blind
+        assert_eq!(winning_payouts(&messages), vec![(0, 0, 750), (0, 1, 750), (1, 1, 1000)]);
     }
 
     /// if a player goes all-in, then can only win as much as is called up to that amount,
@@ -2406,6 +5970,11 @@ mod tests {
             suit: Suit::Heart,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // now the full run out
         deck.push(Card {
             rank: Rank::Three,
@@ -2419,10 +5988,20 @@ mod tests {
             rank: Rank::Nine,
             suit: Suit::Club,
         });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Heart,
         });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
         deck.push(Card {
             rank: Rank::King,
             suit: Suit::Club,
@@ -2438,8 +6017,9 @@ mod tests {
         // player1 will start as the button
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Button".to_string();
-        let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        let (addr, messages) = start_collector();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
         // set the button to have less money so there is a side pot
         table.players[0].as_mut().unwrap().money = 500;
 
@@ -2447,19 +6027,19 @@ mod tests {
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Small".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 
         // player3 will start as the big blind
         let id3 = uuid::Uuid::new_v4();
         let name3 = "Big".to_string();
         let settings3 = PlayerConfig::new(id3, Some(name3), None);
-        table.add_human(settings3, None).unwrap();
+        table.add_human(settings3, None, None).unwrap();
 
         // player4 will start as UTG
         let id4 = uuid::Uuid::new_v4();
         let name4 = "UTG".to_string();
         let settings4 = PlayerConfig::new(id4, Some(name4), None);
-        table.add_human(settings4, None).unwrap();
+        table.add_human(settings4, None, None).unwrap();
         // set UTG to have medium money so there is a second side pot
         table.players[3].as_mut().unwrap().money = 750;
 
@@ -2476,14 +6056,11 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
         // UTG goes all in with the medium stack
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id4, PlayerAction::Bet(750));
+            .insert(id4, PlayerAction::Raise(742));
         // the button calls (and thus goes all in with the short stack)
         incoming_actions
             .lock()
@@ -2493,7 +6070,7 @@ mod tests {
         incoming_actions
             .lock()
             .unwrap()
-            .insert(id2, PlayerAction::Bet(1000));
+            .insert(id2, PlayerAction::Raise(250));
         // the big blind calls the full all-in
         incoming_actions
             .lock()
@@ -2514,28 +6091,32 @@ mod tests {
 
         // UTG won the second side pot
         assert_eq!(table.players[3].as_ref().unwrap().money, 750);
+
+        // the structured payouts have a winner for each of the three pots
+        assert_eq!(winning_payouts(&messages), vec![(0, 0, 2000), (1, 3, 750), (2, 1, 500)]);
     }
 
     /// can we pass a hand limit of 2 and the game comes to an end
     #[test]
     fn hand_limit() {
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human1".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -2546,16 +6127,13 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.5)); 
-	
         // set the action that player2 folds
         incoming_actions
             .lock()
             .unwrap()
             .insert(id2, PlayerAction::Fold);
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
+	// wait for the first hand to finish and the next one to start
         thread::sleep(time::Duration::from_secs_f32(10.5)); 
 	println!("ADDING THE FOLD OUTSIDE GAME\n\n");	
         // then player1 folds next hand
@@ -2572,6 +6150,66 @@ mod tests {
         assert_eq!(table.players[1].as_ref().unwrap().money, 1000);
     }
     
+    /// the heads-up match is over when the kings go all-in into the aces.
+    /// Both players agree to a rematch, so their stacks are reset and the next hand is played from the buy-in
+    #[test]
+    fn heads_up_rematch() {
+        let deck = RiggedDeck::from_str(
+            "As Ad Kc Kd 3h 2c 7d 9h 3s 4c 3c Js \
+             Qs Qd Jc Jd 5h 6h 8s 8c Tc Th 5c 5d",
+        )
+        .unwrap();
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.rematch_window = Some(time::Duration::from_secs(10));
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        let handler = std::thread::spawn(move || {
+            table.play(&cloned_actions, &cloned_meta_actions, Some(2));
+            table // return the table back
+        });
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(1000));
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+
+        let has_message = |msg_type: &str| {
+            messages
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|message| json::parse(message).unwrap()["msg_type"] == msg_type)
+        };
+        while !has_message("rematch_offer") {
+            thread::sleep(time::Duration::from_millis(50));
+        }
+        for id in ids.iter() {
+            incoming_meta_actions.lock().unwrap().push_back(MetaAction::Rematch(*id));
+        }
+        while !has_message("rematch_started") {
+            thread::sleep(time::Duration::from_millis(50));
+        }
+        // the button moved, so the aces post the small blind of the new game, and fold
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Fold);
+        let table = handler.join().unwrap();
+
+        assert_eq!(table.players[0].as_ref().unwrap().money, 996);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 1004);
+        // the settle-up still counts the first match, which the aces won
+        let report = table.reconciliation_report();
+        assert_eq!(report["players"][0]["net"], 996);
+        assert_eq!(report["players"][1]["net"], -996);
+    }
+
     /// the game should end after N hands if there are no human players in the game
     /// even if there is no hand limit or a high hand limit
     /// Note: in this test there are no players period, but the game will still count each check
@@ -2603,6 +6241,69 @@ mod tests {
         assert_eq!(table.hand_num, NON_HUMAN_HANDS_LIMIT + 1);
     }
 
+    /// with only bots seated, the table gets through its NON_HUMAN_HANDS_LIMIT hands in less time
+    /// than a table with a human would spend just waiting between those hands
+    #[test]
+    fn bots_only_table_winds_down_quickly() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        for i in 0..2 {
+            table.add_bot(format!("Bot {}", i)).unwrap();
+        }
+        // without a human, even the second between hands is cut short
+        assert_eq!(table.pace(time::Duration::from_secs(1)), BOTS_ONLY_MAX_PAUSE);
+
+        let start = time::Instant::now();
+        table.play(&incoming_actions, &incoming_meta_actions, None);
+        assert_eq!(table.hand_num, NON_HUMAN_HANDS_LIMIT + 1);
+        assert!(start.elapsed() < time::Duration::from_secs(NON_HUMAN_HANDS_LIMIT.into()));
+
+        let settings = PlayerConfig::new(uuid::Uuid::new_v4(), Some("Human".to_string()), None);
+        table.add_human(settings, None, None).unwrap();
+        assert_eq!(table.pace(time::Duration::from_secs(1)), time::Duration::from_secs(1));
+    }
+
+    /// with a minimum of three players to start, two players aren't dealt a hand.
+    /// A table with only bots still winds down without playing, since the non-human hands are counted anyway
+    #[test]
+    fn min_players_to_start() {
+        let mut table = Table::default();
+        table.min_players_to_start = 3;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (addr, messages) = start_collector();
+        let settings = PlayerConfig::new(uuid::Uuid::new_v4(), Some("Human0".to_string()), Some(addr));
+        table.add_human(settings, None, None).unwrap();
+        let settings = PlayerConfig::new(uuid::Uuid::new_v4(), Some("Human1".to_string()), None);
+        table.add_human(settings, None, None).unwrap();
+
+        let was_played = table.play_one_hand(&incoming_actions, &incoming_meta_actions);
+        thread::sleep(time::Duration::from_millis(100));
+        assert!(!was_played);
+        for player in table.players.iter().flatten() {
+            assert_eq!(player.money, 1000);
+            assert!(player.hole_cards.is_empty());
+        }
+        let messages: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        assert!(messages.iter().all(|message| message["msg_type"] != "new_hand"));
+        assert!(messages.iter().any(|message| message["msg_type"] == "game_state" && message["game_suspended"] == true));
+
+        let mut table = Table::default();
+        table.min_players_to_start = 3;
+        for i in 0..2 {
+            table.add_bot(format!("Bot {}", i)).unwrap();
+        }
+        table.play(&incoming_actions, &incoming_meta_actions, None);
+        assert_eq!(table.hand_num, 1);
+    }
+
     /// check that the button moves around properly
     /// we play 4 hands with 3 players with everyone folding whenever it gets to them,
     /// Note: we sleep several seconds in the test to let the game finish its hand in its thread,
@@ -2620,17 +6321,17 @@ mod tests {
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human2".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 
         let id3 = uuid::Uuid::new_v4();
         let name3 = "Human3".to_string();
         let settings3 = PlayerConfig::new(id3, Some(name3), None);
-        table.add_human(settings3, None).unwrap();
+        table.add_human(settings3, None, None).unwrap();
 
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
@@ -2643,9 +6344,6 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.5)); 
-	
         // id3 should not have to act as the big blind
         println!("\n\nsetting 1!");
         incoming_actions
@@ -2718,22 +6416,23 @@ mod tests {
     #[test]
     fn join_mid_hand() {
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human2".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
         assert_eq!(some_players, 2);
@@ -2744,9 +6443,6 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
         // set the action that player2 calls
         incoming_actions
             .lock()
@@ -2766,7 +6462,7 @@ mod tests {
         incoming_meta_actions
             .lock()
             .unwrap()
-            .push_back(MetaAction::Join(settings3, None)); // no password needed
+            .push_back(MetaAction::Join(settings3, None, None)); // no password needed
 
         // wait for the flop
         let wait_duration = time::Duration::from_secs(8);
@@ -2825,6 +6521,11 @@ mod tests {
             suit: Suit::Heart,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // the flop
         deck.push(Card {
             rank: Rank::Ace,
@@ -2840,23 +6541,24 @@ mod tests {
         });
 
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         table.deck = Box::new(deck);
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human2".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
@@ -2877,9 +6579,6 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.2)); 
-	
         // set the action that player2 calls
         incoming_actions
             .lock()
@@ -2952,6 +6651,11 @@ mod tests {
             suit: Suit::Heart,
         });
 
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
         // the flop
         deck.push(Card {
             rank: Rank::Ace,
@@ -2967,23 +6671,24 @@ mod tests {
         });
 
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         table.deck = Box::new(deck);
         let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         let cloned_actions = incoming_actions.clone();
         let cloned_meta_actions = incoming_meta_actions.clone();
 
-        // player1 will start as the button
+        // player1 will start as the big blind
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
-        // player2 will start as the small blind
+        // player2 will start as the button/small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human2".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 
         // flatten to get all the Some() players
         let some_players = table.players.iter().flatten().count();
@@ -2995,14 +6700,15 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we wait before adding the leave meta action
-        thread::sleep(time::Duration::from_secs_f32(1.2)); 
-	
         // set the action that player2 calls
         incoming_actions
             .lock()
             .unwrap()
             .insert(id2, PlayerAction::Call);
+	// wait for the call before adding the leave meta action, so the blinds are in
+        while incoming_actions.lock().unwrap().contains_key(&id2) {
+            thread::sleep(time::Duration::from_millis(50));
+        }
 
         // player1 leave, which folds and ends the hand
         incoming_meta_actions
@@ -3024,6 +6730,48 @@ mod tests {
         assert_eq!(table.players[1].as_ref().unwrap().money, 1008);
     }
 
+    /// the table keeps handling meta actions while it pauses after a hand,
+    /// so a chat sent then goes out right away rather than after the pause
+    #[test]
+    fn chat_during_finish_pause() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            table.add_human(PlayerConfig::new(*id, Some(format!("Human{}", i)), addr), None, None).unwrap();
+        }
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+        });
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::Fold);
+        let received = |msg_type: &str| {
+            messages
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|message| json::parse(message).unwrap()["msg_type"] == msg_type)
+        };
+        while !received("finish_hand") {
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        incoming_meta_actions.lock().unwrap().push_back(MetaAction::Chat(ids[1], "gg".to_owned()));
+        let sent = time::Instant::now();
+        while !received("chat") {
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        // the pause after the hand is three seconds, and the chat didn't have to wait for it
+        assert!(sent.elapsed() < time::Duration::from_millis(500));
+        assert!(!handler.is_finished());
+        handler.join().unwrap();
+    }
+
     /// if someone who is not the admin attempts an admin command, it does not work
     #[test]
     fn not_admin() {
@@ -3047,7 +6795,6 @@ mod tests {
             .unwrap()
             .push_back(MetaAction::Admin(id, AdminCommand::SmallBlind(new_blind)));
 
-	
         table.handle_meta_actions(&cloned_meta_actions, true, None);
 	assert_eq!(table.small_blind, new_blind - 1); // nothing changed	
     }
@@ -3182,6 +6929,124 @@ mod tests {
     /// test that the admin can add and remove bots with a meta action
     /// in this test, we add three bots, then remove one.
     /// the empty seat is at index 0
+    /// the admin kicks a player, who loses their seat and is settled up with their stack.
+    /// Nobody else is affected, and only the admin can kick
+    #[test]
+    fn admin_kick() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.admin_id = ids[0];
+        // only private tables can be updated
+        table.password = Some("arbitrary".to_string());
+        table.players[2].as_mut().unwrap().money = 1500;
+
+        // a player who isn't the admin can't kick anyone
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(ids[1], AdminCommand::Kick(ids[2])));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+        assert_eq!(table.player_ids_to_configs.len(), 3);
+
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(ids[0], AdminCommand::KickSeat(2)));
+        // the hand limit is already reached, so this only tidies up the seats before ending
+        table.play(&incoming_actions, &incoming_meta_actions, Some(0));
+
+        assert!(table.players[2].is_none());
+        assert!(!table.player_ids_to_configs.contains_key(&ids[2]));
+        assert_eq!(table.departed_results.len(), 1);
+        assert_eq!(table.departed_results[0]["player_name"], "Human2");
+        assert_eq!(table.departed_results[0]["final_stack"], 1500);
+        for (i, id) in ids.iter().take(2).enumerate() {
+            let player = table.players[i].as_ref().unwrap();
+            assert_eq!(player.id, *id);
+            assert_eq!(player.money, 1000);
+            assert!(table.player_ids_to_configs.contains_key(id));
+        }
+        // the messages arrive asynchronously
+        let kicked = || {
+            messages.lock().unwrap().iter().any(|message| {
+                let message = json::parse(message).unwrap();
+                message["msg_type"] == "admin_success" && message["updated"] == "player_kicked"
+            })
+        };
+        let start = time::Instant::now();
+        while !kicked() {
+            assert!(start.elapsed() < time::Duration::from_secs(5), "the admin was never told about the kick");
+            thread::sleep(time::Duration::from_millis(50));
+        }
+    }
+
+    /// a muted player's chat isn't shown to anyone else, and they are told why. Once unmuted, it is again
+    #[test]
+    fn admin_mute() {
+        let mut table = Table::default();
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (listener_addr, listener_messages) = start_collector();
+        let (sender_addr, sender_messages) = start_collector();
+        let ids: Vec<Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = match i {
+                1 => Some(listener_addr.clone()),
+                2 => Some(sender_addr.clone()),
+                _ => None,
+            };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.admin_id = ids[0];
+        // only private tables can be updated
+        table.password = Some("arbitrary".to_string());
+
+        for meta_action in [
+            MetaAction::Admin(ids[0], AdminCommand::Mute(ids[2])),
+            MetaAction::Chat(ids[2], "while muted".to_owned()),
+            MetaAction::Admin(ids[0], AdminCommand::Unmute(ids[2])),
+            MetaAction::Chat(ids[2], "after unmuting".to_owned()),
+        ] {
+            incoming_meta_actions.lock().unwrap().push_back(meta_action);
+        }
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+
+        // the messages arrive asynchronously, but in order
+        let chats = |messages: &Mutex<Vec<String>>| -> Vec<String> {
+            messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .filter(|message| message["msg_type"] == "chat")
+                .map(|message| message["text"].to_string())
+                .collect()
+        };
+        let start = time::Instant::now();
+        while chats(&listener_messages).is_empty() || chats(&sender_messages).is_empty() {
+            assert!(start.elapsed() < time::Duration::from_secs(5), "the chat after unmuting never arrived");
+            thread::sleep(time::Duration::from_millis(50));
+        }
+        assert_eq!(chats(&listener_messages), ["after unmuting"]);
+        assert_eq!(chats(&sender_messages), ["after unmuting"]);
+        let told_muted = sender_messages
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|message| json::parse(message).unwrap()["error"] == "muted");
+        assert!(told_muted);
+    }
+
     #[test]
     fn admin_bots() {
         let mut table = Table::default();
@@ -3239,6 +7104,7 @@ mod tests {
     #[test]
     fn admin_restart() {
         let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
         //let _incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
         let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
         //let cloned_actions = incoming_actions.clone();
@@ -3247,13 +7113,13 @@ mod tests {
         let id1 = uuid::Uuid::new_v4();
         let name1 = "1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
         table.players[0].as_mut().unwrap().money = 500;
 
         let id2 = uuid::Uuid::new_v4();
         let name2 = "2".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 	
         // need the id for the admin command
 	table.admin_id = id1; // set the game's admin
@@ -3288,19 +7154,19 @@ mod tests {
         let id1 = uuid::Uuid::new_v4();
         let name1 = "Human1".to_string();
         let settings1 = PlayerConfig::new(id1, Some(name1), None);
-        table.add_human(settings1, None).unwrap();
+        table.add_human(settings1, None, None).unwrap();
 
         // player2 will start as the small blind
         let id2 = uuid::Uuid::new_v4();
         let name2 = "Human2".to_string();
         let settings2 = PlayerConfig::new(id2, Some(name2), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 	
         // player3 will start as the big blind
         let id3 = uuid::Uuid::new_v4();
         let name3 = "Human3".to_string();
         let settings2 = PlayerConfig::new(id3, Some(name3), None);
-        table.add_human(settings2, None).unwrap();
+        table.add_human(settings2, None, None).unwrap();
 
 	// player2 is_sitting_out
         table.players[1].as_mut().unwrap().is_sitting_out = true;
@@ -3316,9 +7182,6 @@ mod tests {
             table // return the table back
         });
 
-	// sleep so we dont drain the actions accidentally right at the beginning of play_one_hand
-        thread::sleep(time::Duration::from_secs_f32(0.5)); 
-	
         // set the action that player1 calls
         incoming_actions
             .lock()
@@ -3334,5 +7197,3748 @@ mod tests {
         assert_eq!(table.players[1].as_ref().unwrap().money, 996);
         assert_eq!(table.players[1].as_ref().unwrap().money, 996);	
     }
-    
+
+    /// the big blind makes four kings, but loses to a straight flush,
+    /// so a bad_beat message is sent out with both hands
+    #[test]
+    fn bad_beat() {
+        let mut deck = RiggedDeck::new();
+
+        // the big blind has kings
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Diamond,
+        });
+        // now the small blind's hole cards
+        deck.push(Card {
+            rank: Rank::Nine,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Ten,
+            suit: Suit::Heart,
+        });
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
+        // now the full run out
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Heart,
+        });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Jack,
+            suit: Suit::Heart,
+        });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::Two,
+            suit: Suit::Club,
+        });
+
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.deck = Box::new(deck);
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        // player1 will start as the big blind
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Human1".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Human2".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the small blind goes all-in and the big blind calls
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Raise(992));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+
+        let table = handler.join().unwrap();
+        assert_eq!(table.players[0].as_ref().unwrap().money, 0);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 2000);
+
+        let bad_beats: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .filter(|message| message["msg_type"] == "bad_beat")
+            .collect();
+        assert_eq!(bad_beats.len(), 1);
+        assert_eq!(bad_beats[0]["losing_index"], 0);
+        assert_eq!(bad_beats[0]["losing_hand"], "FourOfAKind");
+        assert_eq!(bad_beats[0]["winning_index"], 1);
+        assert_eq!(bad_beats[0]["winning_hand"], "StraightFlush");
+    }
+
+    /// while the clocks are frozen, the small blind is not sat out for taking longer than the timeout.
+    /// Once the clocks are unfrozen, the big blind times out as usual
+    #[test]
+    fn freeze_clocks() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.action_timeout = 2;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the big blind, and is the admin
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Human1".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), None);
+        table.add_human(settings1, None, None).unwrap();
+	table.admin_id = id1;
+
+        // player2 will start as the button/small blind
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Human2".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+	// only game's with a password (private) can be updated
+	table.password = Some("arbitrary".to_string());
+
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(id1, AdminCommand::FreezeClocks));
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // wait well past the timeout before the small blind acts
+        thread::sleep(time::Duration::from_secs(6));
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(id1, AdminCommand::UnfreezeClocks));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Call);
+
+        // the big blind never acts
+        let table = handler.join().unwrap();
+
+        assert!(!table.clocks_frozen);
+        assert!(!table.players[1].as_ref().unwrap().is_sitting_out);
+        assert!(table.players[0].as_ref().unwrap().is_sitting_out);
+    }
+
+    /// both players are all-in on the flop with aces against kings, and agree to chop.
+    /// The pot is split by their equity on the flop, even though the kings would have won the run-out
+    #[test]
+    fn all_in_chop() {
+        let mut deck = RiggedDeck::new();
+
+        // the big blind has aces
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Spade,
+        });
+        // now the small blind's hole cards
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Diamond,
+        });
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
+        // the flop
+        deck.push(Card {
+            rank: Rank::Two,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Seven,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Nine,
+            suit: Suit::Spade,
+        });
+        // the kings would make quads on the turn and river
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Club,
+        });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Spade,
+        });
+
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.deck = Box::new(deck);
+        table.allow_chop = true;
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the big blind
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Human1".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let (addr, messages) = start_collector();
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Human2".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), Some(addr));
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the small blind calls, and the big blind checks
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Call);
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Check);
+
+        // wait for the flop
+        thread::sleep(time::Duration::from_secs(7));
+
+        // the big blind goes all-in with the rest of their money, and the small blind calls
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Bet(992));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Call);
+
+        // wait for the chop to be offered
+        let mut offered = false;
+        for _ in 0..30 {
+            offered = messages
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|message| json::parse(message).unwrap()["msg_type"] == "chop_offer");
+            if offered {
+                break;
+            }
+            thread::sleep(time::Duration::from_secs(1));
+        }
+        assert!(offered);
+
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::ProposeChop(id2));
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::AcceptChop(id1));
+
+        let table = handler.join().unwrap();
+
+        // the kings win 83 of the 990 run-outs, and the leftover chip goes to the aces
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1833);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 167);
+    }
+
+    /// with only a big blind heads-up, the player after the button posts it,
+    /// and then the button is first to act
+    #[test]
+    fn heads_up_single_blind() {
+        let mut table = Table::default();
+        table.blind_config.heads_up = vec![Blind::Big];
+        table.action_timeout = 2;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the button
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Human1".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will post the only blind
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Human2".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        // the button folds without having to post anything
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Fold);
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        let table = handler.join().unwrap();
+
+        let button = table.players[0].as_ref().unwrap();
+        assert!(matches!(button.last_action, Some(PlayerAction::Fold)));
+        assert!(!button.is_sitting_out);
+        assert_eq!(button.money, 1000);
+        let big_blind = table.players[1].as_ref().unwrap();
+        assert!(matches!(big_blind.last_action, Some(PlayerAction::PostBigBlind(8))));
+        assert_eq!(big_blind.money, 1000);
+    }
+
+    /// the big blind folds to a raise, and then leaves the table.
+    /// They receive a summary of their session on the way out
+    #[test]
+    fn leave_session_summary() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the big blind
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Human1".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Human2".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Raise(14));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Fold);
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        let mut table = handler.join().unwrap();
+
+        let raiser_stats = &table.players[1].as_ref().unwrap().stats;
+        assert_eq!(raiser_stats.hands_played, 1);
+        assert_eq!(raiser_stats.vpip_hands, 1);
+        assert_eq!(raiser_stats.pfr_hands, 1);
+        assert_eq!(raiser_stats.biggest_pot_won, 30);
+
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Leave(id1));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+        // give the collector a moment to receive the message
+        thread::sleep(time::Duration::from_millis(100));
+
+        let summary = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "session_summary")
+            .unwrap();
+        assert_eq!(summary["hands_played"], 1);
+        assert_eq!(summary["vpip"], 0.0);
+        assert_eq!(summary["net_result"], -8);
+    }
+
+    /// when only reshuffling when needed, short hands are dealt through the stub of one shuffle.
+    /// Once the stub can't cover a full heads-up hand, the deck is reshuffled
+    #[test]
+    fn reshuffle_when_needed() {
+        let mut table = Table::default();
+        table.reshuffle_policy = ReshufflePolicy::WhenNeeded;
+
+        // two short heads-up hands that only deal the hole cards
+        for _ in 0..2 {
+            assert!(!table.prepare_deck(2));
+            for _ in 0..4 {
+                table.deck.draw_card().unwrap();
+            }
+        }
+        assert_eq!(table.deck.remaining(), 44);
+
+        // deal until less than a full hand (9 cards and 3 burns) is left
+        while table.deck.remaining() >= 12 {
+            assert!(!table.prepare_deck(2));
+            table.deck.draw_card().unwrap();
+        }
+        assert!(table.prepare_deck(2));
+        assert_eq!(table.deck.remaining(), 52);
+
+        // every hand gets a fresh shuffle by default
+        table.reshuffle_policy = ReshufflePolicy::EveryHand;
+        assert!(table.prepare_deck(2));
+    }
+
+    /// the ace of hearts is dealt twice, which is detected once the blinds are posted.
+    /// The hand is cancelled and the blinds are refunded, so no chips are lost
+    #[test]
+    fn misdeal_refunds_blinds() {
+        let mut deck = RiggedDeck::new();
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Two,
+            suit: Suit::Club,
+        });
+
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.deck = Box::new(deck);
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Human1".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Human2".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        let was_played = table.play_one_hand(&incoming_actions, &incoming_meta_actions);
+        // give the collector a moment to receive the message
+        thread::sleep(time::Duration::from_millis(100));
+
+        assert!(!was_played);
+        for player in table.players.iter().flatten() {
+            assert_eq!(player.money, 1000);
+            assert!(player.hole_cards.is_empty());
+            assert_eq!(player.stats.hands_played, 0);
+        }
+        let misdeal = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "misdeal")
+            .unwrap();
+        assert_eq!(misdeal["hand_num"], 1);
+    }
+
+    /// with the deck audit on, the recorded deck order of a hand is the order the cards were dealt:
+    /// the hole cards seat by seat, then the board with a card burned before each deal
+    #[test]
+    fn audit_deck_order() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.set_audit_deck(true);
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        // player1 will start as the big blind, and is dealt first
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Human1".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Human2".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the small blind goes all-in and the big blind calls, so the whole board is dealt
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Raise(992));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+
+        let table = handler.join().unwrap();
+        let order = table.audited_deck_order.unwrap();
+        assert_eq!(order.len(), 52);
+
+        let messages = messages.lock().unwrap();
+        let last_state = messages
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .rfind(|message| message["msg_type"] == "game_state" && message["river"].is_string())
+            .unwrap();
+        assert_eq!(last_state["hole_cards"].as_str().unwrap(), format!("{}{}", order[0], order[1]));
+        assert_eq!(last_state["flop"].as_str().unwrap(), format!("{}{}{}", order[5], order[6], order[7]));
+        assert_eq!(last_state["turn"].as_str().unwrap(), format!("{}", order[9]));
+        assert_eq!(last_state["river"].as_str().unwrap(), format!("{}", order[11]));
+        let finish_hand = messages
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "finish_hand")
+            .unwrap();
+        let burned: Vec<String> = finish_hand["burned_cards"].members().map(|card| card.to_string()).collect();
+        assert_eq!(burned, vec![order[4].to_string(), order[8].to_string(), order[10].to_string()]);
+    }
+
+    /// the button raises, and the small blind goes all-in for less than a full raise.
+    /// The button still has to call the rest of the all-in, but since the betting was not
+    /// reopened by a full raise, the button is not allowed to raise again
+    #[test]
+    fn short_all_in_only_call_or_fold() {
+        let mut deck = RiggedDeck::new();
+        // the button has aces, the small blind kings, and the big blind queens
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Diamond,
+        });
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
+        // now the full run out
+        deck.push(Card {
+            rank: Rank::Two,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Seven,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Nine,
+            suit: Suit::Spade,
+        });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Jack,
+            suit: Suit::Heart,
+        });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::Three,
+            suit: Suit::Club,
+        });
+
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        // player1 will start as the button, and acts first preflop
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Button".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the small blind, with a short stack
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Small".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+        table.players[1].as_mut().unwrap().money = 30;
+
+        // player3 will start as the big blind
+        let id3 = uuid::Uuid::new_v4();
+        let name3 = "Big".to_string();
+        let settings3 = PlayerConfig::new(id3, Some(name3), None);
+        table.add_human(settings3, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the button raises to 24, the small blind is all-in for 30 (short of a raise to 40),
+        // and the big blind folds
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Raise(16));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::RaiseTo(30));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id3, PlayerAction::Fold);
+        while incoming_actions.lock().unwrap().contains_key(&id1) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+
+        // the button tries to raise again, which is rejected
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Raise(100));
+        let is_rejected = |message: &String| {
+            let message = json::parse(message).unwrap();
+            message["error"] == "invalid_action"
+                && message["reason"].to_string().contains("not reopened")
+        };
+        while !messages.lock().unwrap().iter().any(is_rejected) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        // so the button just calls the rest of the all-in
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+
+        let table = handler.join().unwrap();
+        // the button only put in 30, and won the 30 from the small blind and 8 from the big blind
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1038);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 0);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 992);
+    }
+
+    /// with check-it-down on, once the small blind is all-in and called,
+    /// the big blind can't bet into the dry side pot, and the hand is checked down
+    #[test]
+    fn check_it_down() {
+        let mut deck = RiggedDeck::new();
+        // the button has aces, the small blind kings, and the big blind queens
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Diamond,
+        });
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
+        // now the full run out
+        deck.push(Card {
+            rank: Rank::Two,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Seven,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Nine,
+            suit: Suit::Spade,
+        });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Jack,
+            suit: Suit::Heart,
+        });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::Three,
+            suit: Suit::Club,
+        });
+
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.check_it_down = true;
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the button, and acts first preflop
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Button".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the small blind, with a short stack
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Small".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+        table.players[1].as_mut().unwrap().money = 30;
+
+        // player3 will start as the big blind
+        let (addr, messages) = start_collector();
+        let id3 = uuid::Uuid::new_v4();
+        let name3 = "Big".to_string();
+        let settings3 = PlayerConfig::new(id3, Some(name3), Some(addr));
+        table.add_human(settings3, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the button limps, the small blind goes all-in, and both other players call
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::RaiseTo(30));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id3, PlayerAction::Call);
+        while incoming_actions.lock().unwrap().contains_key(&id1) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+        while incoming_actions.lock().unwrap().contains_key(&id3) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+
+        // the big blind is first to act on the flop, and tries to bet
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id3, PlayerAction::Bet(50));
+        let is_rejected = |message: &String| {
+            let message = json::parse(message).unwrap();
+            message["error"] == "invalid_action"
+                && message["reason"].to_string().contains("checked down")
+        };
+        while !messages.lock().unwrap().iter().any(is_rejected) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+
+        // so both players check it down
+        while !handler.is_finished() {
+            for id in [id1, id3].iter() {
+                incoming_actions
+                    .lock()
+                    .unwrap()
+                    .entry(*id)
+                    .or_insert(PlayerAction::Check);
+            }
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+
+        let table = handler.join().unwrap();
+        // the aces won the 90 chip pot, and the big blind only lost their call
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1060);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 0);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 970);
+    }
+
+    /// the small blind keeps trying to check facing the big blind. Each time they are told why not,
+    /// and after too many they are folded for the invalid actions, rather than sat out for a timeout
+    #[test]
+    fn invalid_actions_exhausted() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 1 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        while !handler.is_finished() {
+            incoming_actions
+                .lock()
+                .unwrap()
+                .entry(ids[1])
+                .or_insert(PlayerAction::Check);
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        let table = handler.join().unwrap();
+
+        let messages: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        let num_told_why = messages
+            .iter()
+            .filter(|message| message["error"] == "invalid_action" && message["reason"].to_string().contains("can't check"))
+            .count();
+        assert_eq!(num_told_why, MAX_INVALID_ACTIONS as usize);
+        let forced = messages
+            .iter()
+            .find(|message| message["msg_type"] == "forced_action")
+            .unwrap();
+        assert_eq!(forced["index"], 1);
+        assert_eq!(forced["action"], "fold");
+        assert_eq!(forced["reason"], "invalid_action");
+
+        let small_blind = table.players[1].as_ref().unwrap();
+        assert!(!small_blind.is_sitting_out);
+        assert_eq!(small_blind.money, 996);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1004);
+    }
+
+    /// everyone antes 2 before the blinds. The button raises and the blinds fold,
+    /// so the button collects all the antes as well as the blinds
+    #[test]
+    fn antes() {
+        let mut table = Table::default();
+        table.ante_mode = AnteMode::PerPlayer(2);
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the button, and acts first preflop
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Button".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the small blind
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Small".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        // player3 will start as the big blind
+        let id3 = uuid::Uuid::new_v4();
+        let name3 = "Big".to_string();
+        let settings3 = PlayerConfig::new(id3, Some(name3), None);
+        table.add_human(settings3, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Raise(16));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Fold);
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id3, PlayerAction::Fold);
+
+        let table = handler.join().unwrap();
+        // the button won the other two antes plus the 12 in blinds
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1016);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 994);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 990);
+    }
+
+    /// with bot decisions exposed, the admin hears what the bot decided when it acts
+    #[test]
+    fn expose_bot_decisions() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.expose_bot_decisions = true;
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // the admin will start as the big blind
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Admin".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+        table.admin_id = id1;
+
+        // the bot will start as the button/small blind, so acts first
+        table.add_bot("Bot 0".to_string()).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the admin gives up whenever it is their turn (which is a check if there is no bet)
+        while !handler.is_finished() {
+            incoming_actions
+                .lock()
+                .unwrap()
+                .entry(id1)
+                .or_insert(PlayerAction::Fold);
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        handler.join().unwrap();
+
+        let decision = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "bot_decision")
+            .unwrap();
+        assert_eq!(decision["player_name"], "Bot 0");
+        assert!(decision["action"].is_string());
+        assert!(decision["reason"].to_string().contains("rolled"));
+        // the bot is the small blind, facing the rest of the big blind
+        assert_eq!(decision["to_call"], 4);
+        assert_eq!(decision["total_pot"], 12);
+        assert_eq!(decision["invested"], 4);
+    }
+
+    /// the reasons given to seat 0 for each of their rejected actions
+    fn rejection_reasons(game: &TestTable) -> Vec<String> {
+        game.messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .filter(|message| message["error"] == "invalid_action")
+            .map(|message| message["reason"].to_string())
+            .collect()
+    }
+
+    /// a raise of less than the big blind on top of the big blind is turned down,
+    /// so the button has to act again, and folds
+    #[test]
+    fn undersized_raise_rejected() {
+        let mut game = TestTable::new(3);
+        game.table.button_idx = 0;
+        game.act(0, PlayerAction::Raise(4)).act(0, PlayerAction::Fold).act(1, PlayerAction::Fold);
+        assert!(game.play_hand());
+
+        assert_eq!(rejection_reasons(&game), vec!["the minimum raise is to 16!"]);
+        // the button put nothing in, and the big blind won the small blind
+        assert_eq!(game.table.players[0].as_ref().unwrap().money, 1000);
+        assert_eq!(game.table.players[2].as_ref().unwrap().money, 1004);
+    }
+
+    /// the smallest full raise, by the big blind, is accepted
+    #[test]
+    fn minimum_raise_accepted() {
+        let mut game = TestTable::new(3);
+        game.table.button_idx = 0;
+        game.act(0, PlayerAction::Raise(8)).act(1, PlayerAction::Fold).act(2, PlayerAction::Fold);
+        assert!(game.play_hand());
+
+        assert!(rejection_reasons(&game).is_empty());
+        let finish_hand = game.last_message("finish_hand");
+        assert_eq!(finish_hand["action_log"][2], object! { seat: 0, action: "raise:8", street: "preflop" });
+        assert_eq!(game.table.players[0].as_ref().unwrap().money, 1012);
+    }
+
+    /// a player without enough for a full raise can still go all-in for less
+    #[test]
+    fn all_in_under_raise_accepted() {
+        let mut game = TestTable::new(3);
+        game.table.button_idx = 0;
+        game.table.players[0].as_mut().unwrap().money = 12;
+        game.act(0, PlayerAction::Raise(4)).act(1, PlayerAction::Fold).act(2, PlayerAction::Fold);
+        assert!(game.play_hand());
+
+        assert!(rejection_reasons(&game).is_empty());
+        let finish_hand = game.last_message("finish_hand");
+        assert_eq!(finish_hand["action_log"][2], object! { seat: 0, action: "raise:4", street: "preflop" });
+        // the blinds both fold, so the button wins them on top of their 12
+        assert_eq!(game.table.players[0].as_ref().unwrap().money, 24);
+    }
+
+    /// the button raises, the small blind re-raises, and the big blind goes all-in for less
+    /// than a full raise on top of that. The small blind's full raise already reopened the betting
+    /// for the button, but the short all-in doesn't reopen it for the small blind,
+    /// who can only call or fold
+    #[test]
+    fn incomplete_raise_after_raise() {
+        let mut deck = RiggedDeck::new();
+        // the button has aces, the small blind kings, and the big blind queens
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Diamond,
+        });
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
+        // now the full run out
+        deck.push(Card {
+            rank: Rank::Two,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Seven,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Nine,
+            suit: Suit::Spade,
+        });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Jack,
+            suit: Suit::Heart,
+        });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::Three,
+            suit: Suit::Club,
+        });
+
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the button, and acts first preflop
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Button".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the small blind
+        let (addr, messages) = start_collector();
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Small".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), Some(addr));
+        table.add_human(settings2, None, None).unwrap();
+
+        // player3 will start as the big blind, with a short stack
+        let id3 = uuid::Uuid::new_v4();
+        let name3 = "Big".to_string();
+        let settings3 = PlayerConfig::new(id3, Some(name3), None);
+        table.add_human(settings3, None, None).unwrap();
+        table.players[2].as_mut().unwrap().money = 60;
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the button raises to 24, the small blind re-raises to 48,
+        // and the big blind is all-in for 60 (short of a raise to 72)
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Raise(16));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::RaiseTo(48));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id3, PlayerAction::RaiseTo(60));
+        while incoming_actions.lock().unwrap().contains_key(&id1) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        // the button just calls
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+        while incoming_actions.lock().unwrap().contains_key(&id2) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+
+        // the small blind tries to raise again, which is rejected
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::RaiseTo(200));
+        let is_rejected = |message: &String| {
+            let message = json::parse(message).unwrap();
+            message["error"] == "invalid_action"
+                && message["reason"].to_string().contains("not reopened")
+        };
+        while !messages.lock().unwrap().iter().any(is_rejected) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Call);
+
+        // then the two players with chips left check it down
+        while !handler.is_finished() {
+            for id in [id1, id2].iter() {
+                incoming_actions
+                    .lock()
+                    .unwrap()
+                    .entry(*id)
+                    .or_insert(PlayerAction::Check);
+            }
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+
+        let table = handler.join().unwrap();
+        // everyone put in 60, and the aces won it all
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1120);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 940);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 0);
+    }
+
+    /// with a big blind ante of 6, the big blind only has 10, so posts the full blind of 8
+    /// and just 2 of the ante. The ante is dead money, so the button only needs to match the blind,
+    /// and the big blind wins the blinds plus their partial ante
+    #[test]
+    fn big_blind_ante_all_in() {
+        let mut deck = RiggedDeck::new();
+        // the button and small blind have junk, and the big blind has aces
+        deck.push(Card {
+            rank: Rank::Seven,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Two,
+            suit: Suit::Diamond,
+        });
+        deck.push(Card {
+            rank: Rank::Eight,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Three,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Ace,
+            suit: Suit::Diamond,
+        });
+        // a card is burned before the flop
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Diamond,
+        });
+        // now the full run out
+        deck.push(Card {
+            rank: Rank::King,
+            suit: Suit::Heart,
+        });
+        deck.push(Card {
+            rank: Rank::Nine,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::Five,
+            suit: Suit::Diamond,
+        });
+        // and before the turn
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Club,
+        });
+        deck.push(Card {
+            rank: Rank::Jack,
+            suit: Suit::Club,
+        });
+        // and before the river
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Spade,
+        });
+        deck.push(Card {
+            rank: Rank::Four,
+            suit: Suit::Heart,
+        });
+
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.ante_mode = AnteMode::BigBlind(6);
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the button, and acts first preflop
+        let id1 = uuid::Uuid::new_v4();
+        let name1 = "Button".to_string();
+        let settings1 = PlayerConfig::new(id1, Some(name1), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the small blind
+        let id2 = uuid::Uuid::new_v4();
+        let name2 = "Small".to_string();
+        let settings2 = PlayerConfig::new(id2, Some(name2), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        // player3 will start as the big blind, with a short stack
+        let id3 = uuid::Uuid::new_v4();
+        let name3 = "Big".to_string();
+        let settings3 = PlayerConfig::new(id3, Some(name3), None);
+        table.add_human(settings3, None, None).unwrap();
+        table.players[2].as_mut().unwrap().money = 10;
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the button raises over the all-in big blind, and the small blind folds
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Raise(16));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Fold);
+
+        let table = handler.join().unwrap();
+        // the big blind won 8 from the button, 4 from the small blind, and their own 10 back
+        assert_eq!(table.players[0].as_ref().unwrap().money, 992);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 996);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 22);
+    }
+
+    /// setting a blind schedule applies its first level right away,
+    /// and the level shows up in the game state
+    #[test]
+    fn admin_blind_schedule() {
+        let mut table = Table::default();
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let id = uuid::Uuid::new_v4();
+	table.admin_id = id;
+	table.password = Some("arbitrary".to_string());
+
+	let schedule = BlindSchedule::parse("10 5/10/1,10/20/2").unwrap();
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(id, AdminCommand::SetBlindSchedule(schedule)));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+	assert_eq!(table.small_blind, 5);
+	assert_eq!(table.big_blind, 10);
+	assert_eq!(table.ante_mode, AnteMode::PerPlayer(1));
+
+	let state = table.game_state(None, false).to_json();
+	assert_eq!(state["blind_level"], 0);
+	assert!(state["level_time_remaining"].as_u64().unwrap() <= 600);
+    }
+
+    /// a blind schedule on a big blind ante table keeps the ante on the big blind as the levels go up
+    #[test]
+    fn blind_schedule_keeps_big_blind_ante() {
+        let mut table = Table::default();
+        table.ante_mode = AnteMode::BigBlind(8);
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let id = uuid::Uuid::new_v4();
+	table.admin_id = id;
+	table.password = Some("arbitrary".to_string());
+
+	let schedule = BlindSchedule::parse("10 5/10/0,10/20/20").unwrap();
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(id, AdminCommand::SetBlindSchedule(schedule)));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+	assert_eq!(table.ante_mode, AnteMode::None);
+
+	let level_up = time::Instant::now() + time::Duration::from_secs(600);
+	assert!(table.blind_schedule.as_mut().unwrap().advance_if_due(level_up));
+	table.apply_blind_level();
+	assert_eq!(table.big_blind, 20);
+	assert_eq!(table.ante_mode, AnteMode::BigBlind(20));
+    }
+
+    /// at the end of the session, each player's net (final stack minus all their buy-ins)
+    /// is reported, including the players who already left, and the nets sum to zero
+    #[test]
+    fn reconciliation_report() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let ids: Vec<Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), None);
+            table.add_human(settings, None, None).unwrap();
+        }
+        // the big blind rebought for another 500
+        let rebuyer = table.players[2].as_mut().unwrap();
+        rebuyer.money += 500;
+        rebuyer.stats.total_buy_in += 500;
+
+        // the button and small blind fold, so the big blind wins the small blind
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(ids[0], PlayerAction::Fold);
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(ids[1], PlayerAction::Fold);
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        let mut table = handler.join().unwrap();
+
+        // the small blind leaves before the table ends
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Leave(ids[1]));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+
+        let report = table.reconciliation_report();
+        let nets: HashMap<String, i64> = report["players"]
+            .members()
+            .map(|entry| {
+                (
+                    entry["player_name"].as_str().unwrap().to_string(),
+                    entry["net"].as_i64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(nets.len(), 3);
+        assert_eq!(nets["Human0"], 0);
+        assert_eq!(nets["Human1"], -4);
+        assert_eq!(nets["Human2"], 4);
+        assert_eq!(nets.values().sum::<i64>(), 0);
+    }
+
+    /// once the small blind's action timeout runs out, their time bank is drawn down
+    /// before they are sat out. The prompt tells them how much time bank they have
+    #[test]
+    fn time_bank() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.action_timeout = 1;
+        table.time_bank = 2;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the big blind
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let (addr, messages) = start_collector();
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), Some(addr));
+        table.add_human(settings2, None, None).unwrap();
+
+        // the small blind never acts
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        let table = handler.join().unwrap();
+        thread::sleep(time::Duration::from_millis(100));
+
+        let prompt = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "prompt")
+            .unwrap();
+        assert_eq!(prompt["time_bank_remaining"], 2);
+
+        let small_blind = table.players[1].as_ref().unwrap();
+        assert_eq!(small_blind.time_bank_seconds, 0);
+        assert!(small_blind.is_sitting_out);
+        // the big blind never had to use theirs
+        assert_eq!(table.players[0].as_ref().unwrap().time_bank_seconds, 2);
+    }
+
+    /// each orbit of the button adds to everyone's time bank
+    #[test]
+    fn replenish_time_banks() {
+        let mut table = Table::default();
+        table.time_bank_replenish = 5;
+        table.add_bot("Bot0".to_string()).unwrap();
+        table.add_bot("Bot1".to_string()).unwrap();
+        table.players[0].as_mut().unwrap().time_bank_seconds = 3;
+        table.replenish_time_banks();
+        assert_eq!(table.players[0].as_ref().unwrap().time_bank_seconds, 8);
+        assert_eq!(table.players[1].as_ref().unwrap().time_bank_seconds, 5);
+
+        // the biggest amount the admin can set fills the time bank up rather than overflowing it
+        table.time_bank_replenish = u32::MAX;
+        table.replenish_time_banks();
+        assert_eq!(table.players[0].as_ref().unwrap().time_bank_seconds, u32::MAX);
+    }
+
+    /// in pot limit, heads-up, the small blind can raise the pot (to 24),
+    /// but a raise of one chip more is rejected
+    #[test]
+    fn pot_limit_max_raise() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.betting_structure = BettingStructure::PotLimit;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the big blind
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let (addr, messages) = start_collector();
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), Some(addr));
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the pot is 12, so after calling 4 the pot is 16, and the max raise is to 8 + 16 = 24
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::RaiseTo(25));
+        let is_rejected = |message: &String| {
+            let message = json::parse(message).unwrap();
+            message["error"] == "invalid_action"
+                && message["reason"].to_string().contains("maximum bet is to 24")
+        };
+        while !messages.lock().unwrap().iter().any(is_rejected) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::RaiseTo(24));
+        while incoming_actions.lock().unwrap().contains_key(&id2) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Fold);
+
+        let table = handler.join().unwrap();
+        // the pot-sized raise went through, so the small blind won the big blind
+        assert_eq!(table.players[0].as_ref().unwrap().money, 992);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 1008);
+    }
+
+    /// on the flop, facing a bet of 30 into a pot of 100, a pot-sized raise counts the call:
+    /// after calling, the pot is 160, so the max raise is to 30 + 160 = 190
+    #[test]
+    fn pot_limit_max_includes_the_call() {
+        let mut table = Table::default();
+        table.betting_structure = BettingStructure::PotLimit;
+        let ids: Vec<Uuid> = (0..2).map(|_| Uuid::new_v4()).collect();
+        let mut gamehand = GameHand::default();
+        gamehand.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+        gamehand.contribute(0, ids[0], 50, false);
+        gamehand.contribute(1, ids[1], 50, false);
+        gamehand.street = Street::Flop;
+        gamehand.street_contributions.insert(Street::Flop, [0; MAX_SEATS]);
+        gamehand.contribute(0, ids[0], 30, false);
+        gamehand.current_bet = 30;
+
+        assert_eq!(gamehand.max_pot_limit_raise_to(0), 190);
+        assert!(table.check_betting_structure(&gamehand, 190, 0, 950).is_ok());
+        let rejected = table.check_betting_structure(&gamehand, 191, 0, 950);
+        assert_eq!(rejected, Err("In pot limit, the maximum bet is to 190!".to_owned()));
+    }
+
+    /// with clock scaling, a big multiway pot gets a longer clock than a small heads-up one
+    #[test]
+    fn scaled_action_timeout() {
+        let mut table = Table::default();
+        table.action_timeout = 10;
+        let ids: Vec<Uuid> = (0..4)
+            .map(|i| {
+                table.add_bot(format!("Bot{}", i)).unwrap();
+                table.players[i].as_ref().unwrap().id
+            })
+            .collect();
+
+        // heads-up for just the blinds
+        let mut small_pot = GameHand::default();
+        small_pot.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+        small_pot.contribute(0, ids[0], 4, false);
+        small_pot.contribute(1, ids[1], 8, false);
+
+        // four-way for 100 each
+        let mut big_pot = GameHand::default();
+        big_pot.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+        for (i, id) in ids.iter().enumerate() {
+            big_pot.contribute(i, *id, 100, false);
+        }
+
+        let set_active = |table: &mut Table, num_active: usize| {
+            for (i, player) in table.players.iter_mut().flatten().enumerate() {
+                player.is_active = i < num_active;
+            }
+        };
+
+        // without scaling, everyone gets the same clock
+        set_active(&mut table, 4);
+        assert_eq!(table.scaled_action_timeout(&big_pot), 10);
+
+        // 400 in the pot is 50 big blinds, so 5 more seconds
+        table.clock_scaling = ClockScaling::PotSize;
+        set_active(&mut table, 2);
+        assert_eq!(table.scaled_action_timeout(&small_pot), 10);
+        set_active(&mut table, 4);
+        assert_eq!(table.scaled_action_timeout(&big_pot), 15);
+
+        // two players past heads-up is 10 more seconds
+        table.clock_scaling = ClockScaling::PlayersInHand;
+        set_active(&mut table, 2);
+        assert_eq!(table.scaled_action_timeout(&small_pot), 10);
+        set_active(&mut table, 4);
+        assert_eq!(table.scaled_action_timeout(&big_pot), 20);
+
+        // but never more than double the base clock
+        table.action_timeout = 4;
+        assert_eq!(table.scaled_action_timeout(&big_pot), 8);
+    }
+
+    /// the pause after a hand is the base for each settlement, plus a second for every
+    /// 25 big blinds in the pot when scaling, up to the cap
+    #[test]
+    fn finish_delay_scales_with_pot() {
+        let mut table = Table::default();
+        table.finish_delay = 2;
+        table.max_finish_delay = 12;
+        let ids: Vec<Uuid> = (0..4).map(|_| uuid::Uuid::new_v4()).collect();
+
+        // heads-up for just the blinds
+        let mut small_pot = GameHand::default();
+        small_pot.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+        small_pot.contribute(0, ids[0], 4, false);
+        small_pot.contribute(1, ids[1], 8, false);
+
+        // four-way for 500 each, which is 250 big blinds
+        let mut big_pot = GameHand::default();
+        big_pot.street_contributions.insert(Street::Preflop, [0; MAX_SEATS]);
+        for (i, id) in ids.iter().enumerate() {
+            big_pot.contribute(i, *id, 500, false);
+        }
+
+        // without scaling, only the number of settlements matters
+        assert_eq!(table.finish_delay(&small_pot, 3), time::Duration::from_secs(6));
+        assert_eq!(table.finish_delay(&big_pot, 3), time::Duration::from_secs(6));
+
+        // the big pot is worth 10 more seconds, but is held to the cap
+        table.finish_delay_pot_scaling = true;
+        assert_eq!(table.finish_delay(&small_pot, 3), time::Duration::from_secs(6));
+        assert_eq!(table.finish_delay(&big_pot, 3), time::Duration::from_secs(12));
+        table.max_finish_delay = 30;
+        assert_eq!(table.finish_delay(&big_pot, 3), time::Duration::from_secs(16));
+
+        // the cap also applies to lots of settlements
+        table.finish_delay_pot_scaling = false;
+        assert_eq!(table.finish_delay(&small_pot, 20), time::Duration::from_secs(30));
+    }
+
+    /// while the button acts first preflop, the small blind is told they are on deck
+    #[test]
+    fn on_deck_notification() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the button, and acts first preflop
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Button".to_string()), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the small blind
+        let (addr, messages) = start_collector();
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Small".to_string()), Some(addr));
+        table.add_human(settings2, None, None).unwrap();
+
+        // player3 will start as the big blind
+        let id3 = uuid::Uuid::new_v4();
+        let settings3 = PlayerConfig::new(id3, Some("Big".to_string()), None);
+        table.add_human(settings3, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // before the button has acted, the small blind hears they are next
+        let is_on_deck = |message: &String| {
+            let message = json::parse(message).unwrap();
+            message["msg_type"] == "on_deck" && message["acting_index"] == 0
+        };
+        while !messages.lock().unwrap().iter().any(is_on_deck) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Fold);
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Fold);
+        let table = handler.join().unwrap();
+        assert_eq!(table.players[2].as_ref().unwrap().money, 1004);
+    }
+
+    /// in fixed limit, after four raises the betting is capped,
+    /// so the small blind's fifth raise is rejected
+    #[test]
+    fn fixed_limit_raise_cap() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.betting_structure = BettingStructure::FixedLimit;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the big blind
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let (addr, messages) = start_collector();
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), Some(addr));
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // preflop, each raise is one small bet (the big blind of 8)
+        for (id, raise_to) in [(id2, 16), (id1, 24), (id2, 32), (id1, 40)] {
+            incoming_actions
+                .lock()
+                .unwrap()
+                .insert(id, PlayerAction::RaiseTo(raise_to));
+            while incoming_actions.lock().unwrap().contains_key(&id) {
+                std::thread::sleep(time::Duration::from_millis(50));
+            }
+        }
+
+        // a fifth raise is too many
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::RaiseTo(48));
+        let is_rejected = |message: &String| {
+            let message = json::parse(message).unwrap();
+            message["error"] == "invalid_action"
+                && message["reason"].to_string().contains("capped")
+        };
+        while !messages.lock().unwrap().iter().any(is_rejected) {
+            std::thread::sleep(time::Duration::from_millis(50));
+        }
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Fold);
+
+        let table = handler.join().unwrap();
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1032);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 968);
+    }
+
+    /// heads-up, the button posts the small blind and acts first preflop,
+    /// and then the big blind acts first on the flop
+    #[test]
+    fn heads_up_blinds_and_order() {
+        let mut table = Table::default();
+        table.action_timeout = 30;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the button/small blind
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Button".to_string()), None);
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the big blind
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Big".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // preflop, the button completes first and then the big blind checks,
+        // and on the flop the big blind bets first and the button folds
+        for (id, action) in [
+            (id1, PlayerAction::Call),
+            (id2, PlayerAction::Check),
+            (id2, PlayerAction::Bet(8)),
+            (id1, PlayerAction::Fold),
+        ] {
+            incoming_actions.lock().unwrap().insert(id, action);
+            while incoming_actions.lock().unwrap().contains_key(&id) {
+                std::thread::sleep(time::Duration::from_millis(50));
+            }
+        }
+
+        let table = handler.join().unwrap();
+        let button = table.players[0].as_ref().unwrap();
+        let big_blind = table.players[1].as_ref().unwrap();
+        // nobody had to time out waiting for the other
+        assert!(!button.is_sitting_out);
+        assert!(!big_blind.is_sitting_out);
+        assert!(matches!(button.last_action, Some(PlayerAction::Fold)));
+        assert_eq!(button.money, 992);
+        assert_eq!(big_blind.money, 1008);
+    }
+
+    /// with the void-on-exposure policy, a hole card exposed during the deal
+    /// kills the hand, so it is dealt again
+    #[test]
+    fn exposed_card_voids_hand() {
+        let mut deck = RiggedDeck::new();
+        for (rank, suit) in [
+            (Rank::Ace, Suit::Heart),
+            (Rank::King, Suit::Heart),
+            (Rank::Queen, Suit::Club),
+            (Rank::Two, Suit::Club),
+        ] {
+            deck.push(Card { rank, suit });
+        }
+
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.exposure_policy = ExposurePolicy::VoidHand;
+        // simulate the queen of clubs being revealed before the betting
+        table.exposed_cards.push(Card {
+            rank: Rank::Queen,
+            suit: Suit::Club,
+        });
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        let was_played = table.play_one_hand(&incoming_actions, &incoming_meta_actions);
+        // give the collector a moment to receive the message
+        thread::sleep(time::Duration::from_millis(100));
+
+        // the hand doesn't count, so the same hand number is dealt again
+        assert!(!was_played);
+        assert_eq!(table.hand_num, 1);
+        assert!(table.exposed_cards.is_empty());
+        for player in table.players.iter().flatten() {
+            assert_eq!(player.money, 1000);
+            assert!(player.hole_cards.is_empty());
+            assert_eq!(player.stats.hands_played, 0);
+        }
+        let misdeal = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "misdeal")
+            .unwrap();
+        assert!(misdeal["reason"].to_string().contains("exposed"));
+    }
+
+    /// a player shows their hand to a friend, and nobody else at the table gets to see it
+    #[test]
+    fn show_to_player() {
+        let mut table = Table::default();
+        let mut ids = vec![];
+        let mut inboxes = vec![];
+        for i in 0..3 {
+            let (addr, messages) = start_collector();
+            let id = uuid::Uuid::new_v4();
+            table.add_human(PlayerConfig::new(id, Some(format!("Human{}", i)), Some(addr)), None, None).unwrap();
+            ids.push(id);
+            inboxes.push(messages);
+        }
+        table.players[0].as_mut().unwrap().hole_cards = vec![
+            Card { rank: Rank::Ace, suit: Suit::Spade },
+            Card { rank: Rank::King, suit: Suit::Heart },
+        ];
+        let meta_actions = Arc::new(Mutex::new(VecDeque::from([MetaAction::ShowToPlayer(ids[0], ids[1])])));
+        table.handle_meta_actions(&meta_actions, true, None);
+
+        let shown_cards = |messages: &Mutex<Vec<String>>| {
+            messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .find(|message| message["msg_type"] == "shown_cards")
+        };
+        let mut shown = None;
+        for _ in 0..50 {
+            shown = shown_cards(&inboxes[1]);
+            if shown.is_some() {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        let shown = shown.unwrap();
+        assert_eq!(shown["index"], 0);
+        assert_eq!(shown["hole_cards"], "AsKh");
+        assert!(shown_cards(&inboxes[0]).is_none());
+        assert!(shown_cards(&inboxes[2]).is_none());
+    }
+
+    /// in a tournament, a player who is away keeps posting their blinds rather than being skipped.
+    /// Once they are blinded down to nothing they are eliminated, finishing last
+    #[test]
+    fn absent_tournament_player_blinded_off() {
+        let mut game = TestTable::new(3);
+        game.table.remove_busted = true;
+        // seat 1 posts the small blind, and the absent seat 2 the big blind
+        game.table.button_idx = 0;
+        {
+            let absent = game.table.players[2].as_mut().unwrap();
+            absent.is_sitting_out = true;
+            absent.money = 12;
+        }
+        // the small blind calls, and the absent big blind folds without being asked
+        game.act(0, PlayerAction::Fold).act(1, PlayerAction::Call);
+        assert!(game.play_hand());
+        assert_eq!(game.table.players[2].as_ref().unwrap().money, 4);
+        game.table.move_button();
+
+        // the absent player has only enough left for the small blind, and is all-in.
+        // The button folds, and the big blind's aces hold up
+        game.act(1, PlayerAction::Fold).act(0, PlayerAction::Check);
+        game.table.deck = Box::new(RiggedDeck::from_str("As Ad 2c 7d 3h 9s 5c Kd 4h Qs 8s Jh Ts 6d").unwrap());
+        game.play(1);
+
+        assert!(game.table.players[2].is_none());
+        let departed = game.table.departed_results.last().unwrap();
+        assert_eq!(departed["player_name"], "Human2");
+        assert_eq!(departed["finishing_place"], 3);
+        let busted = game.last_message("player_busted");
+        assert_eq!(busted["index"], 2);
+        assert_eq!(busted["finishing_place"], 3);
+    }
+
+    /// the roster labels the admin, a seated player, and a spectator without a seat,
+    /// and a spectator who asks for it gets it too
+    #[test]
+    fn roster_roles() {
+        let mut table = Table::default();
+
+        let (addr, _messages) = start_collector();
+        let admin_id = uuid::Uuid::new_v4();
+        let settings = PlayerConfig::new(admin_id, Some("Admin".to_string()), Some(addr.clone()));
+        table.add_human(settings, None, None).unwrap();
+        table.admin_id = admin_id;
+
+        let player_id = uuid::Uuid::new_v4();
+        let settings = PlayerConfig::new(player_id, Some("Player".to_string()), Some(addr.clone()));
+        table.add_human(settings, None, None).unwrap();
+        table.players[1].as_mut().unwrap().is_sitting_out = true;
+
+        // a spectator is connected to the table without a seat
+        let (spectator_addr, spectator_messages) = start_collector();
+        let spectator_id = uuid::Uuid::new_v4();
+        table.spectators.insert(
+            spectator_id,
+            PlayerConfig::new(spectator_id, Some("Spectator".to_string()), Some(spectator_addr)),
+        );
+
+        let meta_actions = Arc::new(Mutex::new(VecDeque::new()));
+        meta_actions.lock().unwrap().push_back(MetaAction::Roster(spectator_id));
+        table.handle_meta_actions(&meta_actions, true, None);
+        let received = || {
+            spectator_messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .find(|message| message["msg_type"] == "roster")
+        };
+        let start = time::Instant::now();
+        let roster = loop {
+            if let Some(roster) = received() {
+                break roster;
+            }
+            assert!(start.elapsed() < time::Duration::from_secs(1), "the spectator got no roster");
+            thread::yield_now();
+        };
+        assert_eq!(roster["roster"].len(), 3);
+        let find = |name: &str| {
+            roster["roster"]
+                .members()
+                .find(|entry| entry["player_name"] == name)
+                .unwrap()
+                .clone()
+        };
+        let admin = find("Admin");
+        assert_eq!(admin["role"], "admin");
+        assert_eq!(admin["index"], 0);
+        assert_eq!(admin["status"], "active");
+        let player = find("Player");
+        assert_eq!(player["role"], "player");
+        assert_eq!(player["index"], 1);
+        assert_eq!(player["status"], "sitting_out");
+        let spectator = find("Spectator");
+        assert_eq!(spectator["role"], "spectator");
+        assert!(spectator["index"].is_null());
+        assert_eq!(spectator["status"], "active");
+    }
+
+    /// four-handed, the small blind busts. By the dead button rule, the big blind moves forward one seat,
+    /// last hand's big blind posts the small blind, and the button lands on the busted player's seat
+    #[test]
+    fn dead_button_after_small_blind_busts() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let ids: Vec<Uuid> = (0..4)
+            .map(|i| {
+                let id = uuid::Uuid::new_v4();
+                let settings = PlayerConfig::new(id, Some(format!("Human{}", i)), None);
+                table.add_human(settings, None, None).unwrap();
+                id
+            })
+            .collect();
+
+        // the button is at 0, the small blind at 1, the big blind at 2, and everyone folds to the big blind
+        for i in [3, 0, 1] {
+            incoming_actions.lock().unwrap().insert(ids[i], PlayerAction::Fold);
+        }
+        assert!(table.play_one_hand(&incoming_actions, &incoming_meta_actions));
+        assert_eq!(table.small_blind_idx, Some(1));
+        assert_eq!(table.big_blind_idx, Some(2));
+
+        // the small blind busts (e.g. lost the rest of their chips)
+        table.players[1].as_mut().unwrap().money = 0;
+        table.move_button();
+        assert_eq!(table.button_idx, 1); // a dead button
+        assert_eq!(table.small_blind_idx, Some(2));
+        assert_eq!(table.big_blind_idx, Some(3));
+
+        // the player at 0 is first to act, and everyone folds to the new big blind
+        for i in [0, 2] {
+            incoming_actions.lock().unwrap().insert(ids[i], PlayerAction::Fold);
+        }
+        assert!(table.play_one_hand(&incoming_actions, &incoming_meta_actions));
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1000);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 1000); // won 4, then lost 4
+        assert_eq!(table.players[3].as_ref().unwrap().money, 1004);
+    }
+
+    /// four-handed, the big blind busts, so the next small blind is dead:
+    /// only the new big blind posts
+    #[test]
+    fn dead_small_blind_after_big_blind_busts() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let ids: Vec<Uuid> = (0..4)
+            .map(|i| {
+                let id = uuid::Uuid::new_v4();
+                let settings = PlayerConfig::new(id, Some(format!("Human{}", i)), None);
+                table.add_human(settings, None, None).unwrap();
+                id
+            })
+            .collect();
+        table.small_blind_idx = Some(1);
+        table.big_blind_idx = Some(2);
+        table.players[2].as_mut().unwrap().money = 0;
+        table.move_button();
+        assert_eq!(table.button_idx, 1);
+        assert_eq!(table.small_blind_idx, Some(2)); // dead
+        assert_eq!(table.big_blind_idx, Some(3));
+
+        // the player at 0 is first to act after the big blind, then the button
+        for i in [0, 1] {
+            incoming_actions.lock().unwrap().insert(ids[i], PlayerAction::Fold);
+        }
+        assert!(table.play_one_hand(&incoming_actions, &incoming_meta_actions));
+        let big_blind = table.players[3].as_ref().unwrap();
+        assert!(matches!(big_blind.last_action, Some(PlayerAction::PostBigBlind(8))));
+        assert_eq!(big_blind.money, 1000);
+        for i in [0, 1] {
+            assert_eq!(table.players[i].as_ref().unwrap().money, 1000);
+        }
+    }
+
+    /// three-handed, the small blind busts, so the game goes heads-up.
+    /// The big blind still moves forward one seat, and last hand's big blind gets the button
+    #[test]
+    fn small_blind_busts_three_handed() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let ids: Vec<Uuid> = (0..3)
+            .map(|i| {
+                let id = uuid::Uuid::new_v4();
+                let settings = PlayerConfig::new(id, Some(format!("Human{}", i)), None);
+                table.add_human(settings, None, None).unwrap();
+                id
+            })
+            .collect();
+        table.small_blind_idx = Some(1);
+        table.big_blind_idx = Some(2);
+        table.players[1].as_mut().unwrap().money = 0;
+        table.move_button();
+        assert_eq!(table.button_idx, 2);
+        assert_eq!(table.small_blind_idx, None);
+        assert_eq!(table.big_blind_idx, None);
+
+        // heads-up, the button posts the small blind and folds to the big blind
+        incoming_actions.lock().unwrap().insert(ids[2], PlayerAction::Fold);
+        assert!(table.play_one_hand(&incoming_actions, &incoming_meta_actions));
+        assert_eq!(table.players[2].as_ref().unwrap().money, 996);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1004);
+    }
+
+    /// the rigged deck only has enough cards for the hole cards and part of the flop.
+    /// Rather than crashing the table, the hand is cancelled and everyone gets their chips back
+    #[test]
+    fn exhausted_deck_cancels_hand() {
+        let mut deck = RiggedDeck::new();
+        for rank in [Rank::Ace, Rank::King, Rank::Queen, Rank::Jack, Rank::Ten] {
+            deck.push(Card {
+                rank,
+                suit: Suit::Spade,
+            });
+        }
+
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.deck = Box::new(deck);
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        // the small blind calls and the big blind checks, so the flop needs to be dealt
+        incoming_actions.lock().unwrap().insert(id2, PlayerAction::Call);
+        incoming_actions.lock().unwrap().insert(id1, PlayerAction::Check);
+        let was_played = table.play_one_hand(&incoming_actions, &incoming_meta_actions);
+        // give the collector a moment to receive the message
+        thread::sleep(time::Duration::from_millis(100));
+
+        assert!(!was_played);
+        for player in table.players.iter().flatten() {
+            assert_eq!(player.money, 1000);
+            assert!(player.hole_cards.is_empty());
+            assert_eq!(player.stats.hands_played, 0);
+        }
+        let misdeal = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "misdeal")
+            .unwrap();
+        assert!(misdeal["reason"].to_string().contains("ran out of cards"));
+
+        // the deck is still empty, so the next deal is cancelled too, but the table carries on
+        assert!(!table.play_one_hand(&incoming_actions, &incoming_meta_actions));
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1000);
+    }
+
+    /// the admin shortens the action timeout mid-hand. The small blind is already on the old clock,
+    /// but the big blind gets the new one, and is sat out long before the old timeout
+    #[test]
+    fn admin_action_timeout() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.action_timeout = 30;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        // player1 will start as the big blind, and is the admin
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+        table.admin_id = id1;
+
+        // player2 will start as the button/small blind
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+        // only private tables can be updated
+        table.password = Some("arbitrary".to_string());
+
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(id1, AdminCommand::ActionTimeout(1)));
+        let start = time::Instant::now();
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        thread::sleep(time::Duration::from_secs(1));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Call);
+
+        // the big blind never acts
+        let table = handler.join().unwrap();
+        assert!(start.elapsed() < time::Duration::from_secs(20));
+        assert_eq!(table.action_timeout, 1);
+        assert!(table.players[0].as_ref().unwrap().is_sitting_out);
+        assert!(!table.players[1].as_ref().unwrap().is_sitting_out);
+
+        let broadcast = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "action_timeout")
+            .unwrap();
+        assert_eq!(broadcast["seconds"], 1);
+    }
+
+    /// in training mode, the game state shows how many cards are left in the deck.
+    /// Once the flop is out, that is the 52 less 4 hole cards, the burn card and 3 on the flop
+    #[test]
+    fn show_deck_count() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        assert!(!table.game_state(None, false).to_json().has_key("deck_remaining"));
+        table.set_show_deck_count(true);
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        // player2 will start as the button/small blind
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the small blind goes all-in and the big blind calls, so the whole board is dealt
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Raise(992));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+        handler.join().unwrap();
+
+        let flop_state = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "game_state" && message["flop"].is_string())
+            .unwrap();
+        assert_eq!(flop_state["deck_remaining"], 44);
+    }
+
+    /// the small blind only has 6 left after posting, so calling the button's raise puts them all-in for less.
+    /// The main pot is 10 from each player, and the rest of the button's and big blind's money is a side pot
+    #[test]
+    fn blind_calls_all_in_for_less() {
+        let mut deck = RiggedDeck::new();
+        let cards = [
+            (Rank::King, Suit::Club), (Rank::King, Suit::Diamond), // the button
+            (Rank::Ace, Suit::Club), (Rank::Ace, Suit::Diamond), // the small blind
+            (Rank::Queen, Suit::Club), (Rank::Queen, Suit::Diamond), // the big blind
+            (Rank::Four, Suit::Diamond), // burned
+            (Rank::Two, Suit::Club), (Rank::Seven, Suit::Diamond), (Rank::Nine, Suit::Heart),
+            (Rank::Four, Suit::Club), // burned
+            (Rank::Four, Suit::Spade),
+            (Rank::Four, Suit::Heart), // burned
+            (Rank::Jack, Suit::Spade),
+        ];
+        for (rank, suit) in cards {
+            deck.push(Card { rank, suit });
+        }
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.players[1].as_mut().unwrap().money = 10;
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the button raises to 100, the small blind calls for their last 6, and the big blind calls
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Raise(92));
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::Call);
+        incoming_actions.lock().unwrap().insert(ids[2], PlayerAction::Call);
+        while incoming_actions.lock().unwrap().contains_key(&ids[2]) {
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        // then the other two check it down
+        while !handler.is_finished() {
+            for id in [ids[0], ids[2]] {
+                incoming_actions.lock().unwrap().entry(id).or_insert(PlayerAction::Check);
+            }
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        let table = handler.join().unwrap();
+
+        let flop_state = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "game_state" && message["flop"].is_string())
+            .unwrap();
+        assert_eq!(flop_state["pots"], json::array![30, 180]);
+
+        // the aces win the main pot, and the kings win the side pot from the queens
+        assert_eq!(table.players[1].as_ref().unwrap().money, 30);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1080);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 900);
+    }
+
+    /// at showdown, the winner's settlement describes their hand, next to their revealed hole cards
+    #[test]
+    fn winning_hand_description() {
+        let mut deck = RiggedDeck::new();
+        let cards = [
+            (Rank::King, Suit::Club), (Rank::King, Suit::Diamond), // the big blind
+            (Rank::Queen, Suit::Club), (Rank::Jack, Suit::Diamond), // the button/small blind
+            (Rank::Four, Suit::Diamond), // burned
+            (Rank::King, Suit::Spade), (Rank::Three, Suit::Heart), (Rank::Three, Suit::Spade),
+            (Rank::Four, Suit::Club), // burned
+            (Rank::Eight, Suit::Diamond),
+            (Rank::Four, Suit::Spade), // burned
+            (Rank::Two, Suit::Club),
+        ];
+        for (rank, suit) in cards {
+            deck.push(Card { rank, suit });
+        }
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.deck = Box::new(deck);
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+
+        // the small blind goes all-in and the big blind calls, so the whole board is dealt
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id2, PlayerAction::Raise(992));
+        incoming_actions
+            .lock()
+            .unwrap()
+            .insert(id1, PlayerAction::Call);
+        handler.join().unwrap();
+
+        let finish_hand = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "finish_hand")
+            .unwrap();
+        let winner = finish_hand["settlements"]
+            .members()
+            .find(|settlement| settlement["winner"] == true)
+            .unwrap();
+        assert_eq!(winner["index"], 0);
+        assert_eq!(winner["hole_cards"], "KcKd");
+        assert_eq!(winner["winning_hand"], "Full House, Kings full of Threes");
+    }
+
+    /// as the closing time gets near, everyone is warned before each hand,
+    /// and once it passes the table stops after the hand in progress
+    #[test]
+    fn closing_countdown() {
+        let mut table = Table::default();
+        table.closing_at = Some(time::Instant::now() + time::Duration::from_secs(4));
+        table.add_bot("Bot0".to_string()).unwrap();
+        table.add_bot("Bot1".to_string()).unwrap();
+        // a human watching (sat out), so that the table doesn't end for having no humans
+        let (addr, messages) = start_collector();
+        let id = uuid::Uuid::new_v4();
+        let settings = PlayerConfig::new(id, Some("Human".to_string()), Some(addr));
+        table.add_human(settings, None, None).unwrap();
+        table.players[2].as_mut().unwrap().is_sitting_out = true;
+
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let start = time::Instant::now();
+        table.play(&incoming_actions, &incoming_meta_actions, None);
+        assert!(start.elapsed() >= time::Duration::from_secs(4));
+        thread::sleep(time::Duration::from_millis(100));
+
+        let countdowns: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .filter(|message| message["msg_type"] == "table_closing")
+            .collect();
+        assert!(!countdowns.is_empty());
+        assert!(countdowns[0]["seconds_remaining"].as_u64().unwrap() <= 4);
+        assert!(countdowns[0]["hands_remaining"].is_null());
+    }
+
+    /// a spectator gets the game state without anyone's hole cards, and does not take a seat
+    #[test]
+    fn spectator_gets_game_state() {
+        let mut table = Table::default();
+        let (addr, _messages) = start_collector();
+        for name in ["Alice", "Bob"] {
+            let settings = PlayerConfig::new(uuid::Uuid::new_v4(), Some(name.to_string()), Some(addr.clone()));
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.password = Some("secret".to_string());
+
+        let (spectator_addr, spectator_messages) = start_collector();
+        let spectator_id = uuid::Uuid::new_v4();
+        let meta_actions = Arc::new(Mutex::new(VecDeque::new()));
+        meta_actions.lock().unwrap().push_back(MetaAction::Spectate(
+            PlayerConfig::new(spectator_id, Some("Watcher".to_string()), Some(spectator_addr.clone())),
+            None, // a private table needs the password to watch too
+        ));
+        meta_actions.lock().unwrap().push_back(MetaAction::Spectate(
+            PlayerConfig::new(spectator_id, Some("Watcher".to_string()), Some(spectator_addr)),
+            Some("secret".to_string()),
+        ));
+        table.handle_meta_actions(&meta_actions, true, None);
+
+        assert_eq!(table.players.iter().flatten().count(), 2);
+        assert!(table.spectators.contains_key(&spectator_id));
+        assert!(!table.player_ids_to_configs.contains_key(&spectator_id));
+
+        table.send_game_state(None, false);
+        // once on joining, once more above
+        let game_states = || -> Vec<json::JsonValue> {
+            spectator_messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .filter(|message| message["msg_type"] == "game_state")
+                .collect()
+        };
+        while game_states().len() < 2 {
+            thread::yield_now();
+        }
+        let states = game_states();
+        for state in states {
+            assert!(state["hole_cards"].is_null());
+            assert!(state["your_index"].is_null());
+            assert_eq!(state["players"].members().filter(|info| !info.is_null()).count(), 2);
+        }
+
+        // leaving only drops the spectator
+        meta_actions.lock().unwrap().push_back(MetaAction::Leave(spectator_id));
+        table.handle_meta_actions(&meta_actions, true, None);
+        assert!(table.spectators.is_empty());
+        assert_eq!(table.players.iter().flatten().count(), 2);
+    }
+
+    /// a quick fold queued between hands is handled before the next hand is dealt.
+    /// It still posts the blind, then folds without a prompt, and is used up by that fold
+    #[test]
+    fn quick_fold_posts_blind() {
+        let mut game = TestTable::new(2);
+        game.table.button_idx = 0; // heads-up, the button posts the small blind
+
+        // the small blind queues a fold before the hand gets going
+        game.incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::QuickFold(game.ids[0], true));
+        game.play(1);
+
+        assert_eq!(game.table.players[0].as_ref().unwrap().money, 996);
+        assert_eq!(game.table.players[1].as_ref().unwrap().money, 1004);
+        assert!(!game.table.players[0].as_ref().unwrap().quick_fold);
+        let action_log = game.last_message("finish_hand")["action_log"].clone();
+        assert_eq!(action_log[2]["seat"], 0);
+        assert_eq!(action_log[2]["action"], "fold");
+        let prompted = game
+            .messages
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|message| json::parse(message).unwrap()["msg_type"] == "prompt");
+        assert!(!prompted);
+    }
+
+    /// with two straddles allowed, UTG straddles and the button re-straddles. The bet doubles each time,
+    /// and the last straddler gets the last action preflop
+    #[test]
+    fn straddle_and_restraddle() {
+        let mut table = Table::default();
+        table.max_straddles = 2;
+        table.action_timeout = 1;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (addr, messages) = start_collector();
+        let mut ids = vec![];
+        for i in 0..4 {
+            let id = uuid::Uuid::new_v4();
+            let addr = (i == 3).then(|| addr.clone());
+            table.add_human(PlayerConfig::new(id, Some(format!("Human{}", i)), addr), None, None).unwrap();
+            ids.push(id);
+        }
+        // seat 1 posts the small blind and seat 2 the big blind
+        table.button_idx = 0;
+        for &id in [ids[0], ids[3]].iter() {
+            incoming_meta_actions.lock().unwrap().push_back(MetaAction::Straddle(id, true));
+        }
+        {
+            let mut actions = incoming_actions.lock().unwrap();
+            actions.insert(ids[1], PlayerAction::Fold);
+            actions.insert(ids[2], PlayerAction::Fold);
+            actions.insert(ids[3], PlayerAction::Call);
+            // the re-straddler uses their option once the action comes back around
+            actions.insert(ids[0], PlayerAction::Raise(64));
+        }
+        // seat 3 never responds to the raise, so they fold when their time runs out
+        table.play_one_hand(&incoming_actions, &incoming_meta_actions);
+
+        let prompt = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "prompt")
+            .unwrap();
+        assert_eq!(prompt["current_bet"], 32);
+        assert_eq!(prompt["to_call"], 16);
+
+        let button = table.players[0].as_ref().unwrap();
+        assert!(matches!(button.last_action, Some(PlayerAction::Raise(64))));
+        assert_eq!(button.money, 1000 + 4 + 8 + 32);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 996);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 992);
+        assert_eq!(table.players[3].as_ref().unwrap().money, 968);
+    }
+
+    /// once both players are all-in preflop, betting_closed comes before the flop, turn, and river are dealt
+    #[test]
+    fn betting_closed_before_runout() {
+        let mut table = Table::default();
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), Some(addr.clone()));
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.button_idx = 1; // heads-up, the button posts the small blind
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        // the small blind shoves, and the big blind calls
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(1000));
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+        handler.join().unwrap();
+
+        let messages: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        let closed = messages
+            .iter()
+            .position(|message| message["msg_type"] == "betting_closed")
+            .unwrap();
+        assert_eq!(messages[closed]["street"], "preflop");
+        assert_eq!(messages.iter().filter(|message| message["msg_type"] == "betting_closed").count(), 2); // one per player
+        let first_flop = messages
+            .iter()
+            .position(|message| message["msg_type"] == "game_state" && message["flop"].is_string())
+            .unwrap();
+        assert!(closed < first_flop);
+    }
+
+    /// a player can pick an open seat, but not one that is taken
+    #[test]
+    fn choose_seat() {
+        let mut table = Table::default();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), None);
+        assert_eq!(table.add_human(settings1, None, Some(4)).unwrap(), 4);
+        assert_eq!(table.players[4].as_ref().unwrap().id, id1);
+        assert!(table.players[0].is_none());
+
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        assert!(matches!(
+            table.add_human(settings2.clone(), None, Some(4)),
+            Err(JoinTableError::SeatTaken(4))
+        ));
+        // nor a seat past the edge of the table
+        assert!(matches!(
+            table.add_human(settings2.clone(), None, Some(MAX_SEATS)),
+            Err(JoinTableError::InvalidSeat(MAX_SEATS))
+        ));
+        assert!(!table.player_ids_to_configs.contains_key(&id2));
+
+        // without a choice, they get the first open seat
+        assert_eq!(table.add_human(settings2, None, None).unwrap(), 0);
+    }
+
+    /// a player with auto rebuy on who loses the small blind is topped back up before the next hand,
+    /// and the rebuy counts towards what they have put in
+    #[test]
+    fn auto_rebuy_between_hands() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), Some(addr.clone()));
+            table.add_human(settings, None, None).unwrap();
+        }
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::AutoRebuy(ids[1], true));
+
+        // the small blind folds the one hand
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::Fold);
+        let handler = std::thread::spawn(move || {
+            table.play(&cloned_actions, &cloned_meta_actions, Some(1));
+            table // return the table back
+        });
+        let mut table = handler.join().unwrap();
+
+        let rebuyer = table.players[1].as_ref().unwrap();
+        assert_eq!(rebuyer.money, 1000);
+        assert_eq!(rebuyer.stats.total_buy_in, 1004);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1004);
+        let rebuy = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "rebuy")
+            .unwrap();
+        assert_eq!(rebuy["index"], 1);
+        assert_eq!(rebuy["amount"], 4);
+
+        // with rebuys turned off, nobody is topped up
+        table.allow_rebuys = false;
+        table.players[1].as_mut().unwrap().money = 500;
+        table.auto_rebuy();
+        assert_eq!(table.players[1].as_ref().unwrap().money, 500);
+    }
+
+    /// at a deep table, a player with a target of 200 big blinds is topped up to that
+    /// rather than the buy-in, but never past the table's max stack
+    #[test]
+    fn auto_rebuy_to_target() {
+        let mut table = Table::default();
+        let id = uuid::Uuid::new_v4();
+        let settings = PlayerConfig::new(id, Some("Human".to_string()), None);
+        table.add_human(settings, None, None).unwrap();
+        table.players[0].as_mut().unwrap().auto_rebuy = true;
+        table.players[0].as_mut().unwrap().money = 500;
+
+        // without a max stack, the target is held to the buy-in
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::from([
+            MetaAction::RebuyTarget(id, Some(200 * table.big_blind)),
+        ])));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+        table.auto_rebuy();
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1000);
+
+        table.max_stack = Some(2000);
+        table.players[0].as_mut().unwrap().money = 500;
+        table.auto_rebuy();
+        let player = table.players[0].as_ref().unwrap();
+        assert_eq!(player.money, 1600);
+        assert_eq!(player.stats.total_buy_in, 1000 + 500 + 1100);
+
+        // a target past the max stack only goes up to the max stack
+        table.players[0].as_mut().unwrap().rebuy_target = Some(5000);
+        table.auto_rebuy();
+        assert_eq!(table.players[0].as_ref().unwrap().money, 2000);
+    }
+
+    /// the commitment sent with new_hand is matched by the seed revealed in finish_hand,
+    /// and the seed rebuilds the deck the hand was dealt from
+    #[test]
+    fn shuffle_commitment_revealed() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        table.set_audit_deck(true);
+        table.commit_shuffle = true;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        // the small blind folds
+        incoming_actions.lock().unwrap().insert(id2, PlayerAction::Fold);
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        let table = handler.join().unwrap();
+
+        let messages: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        let new_hand = messages.iter().find(|message| message["msg_type"] == "new_hand").unwrap();
+        let finish_hand = messages.iter().find(|message| message["msg_type"] == "finish_hand").unwrap();
+        let hash = new_hand["shuffle_commitment"].as_str().unwrap();
+        let reveal = &finish_hand["shuffle_reveal"];
+        assert_eq!(reveal["hash"], hash);
+
+        let seed = reveal["seed"].as_str().unwrap().parse::<u64>().unwrap();
+        let rebuilt = crate::logic::deck::verify_shuffle(hash, seed, reveal["salt"].as_str().unwrap()).unwrap();
+        let order = table.audited_deck_order.unwrap();
+        assert!(order.iter().zip(rebuilt.iter()).all(|(a, b)| a.rank == b.rank && a.suit == b.suit));
+        assert!(crate::logic::deck::verify_shuffle(hash, seed + 1, reveal["salt"].as_str().unwrap()).is_none());
+    }
+
+    /// someone who just sat down can ask for the events of the last completed hand
+    #[test]
+    fn last_hand_replay() {
+        let mut table = Table::default();
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), None);
+        table.add_human(settings1, None, None).unwrap();
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+
+        // the small blind folds
+        incoming_actions.lock().unwrap().insert(id2, PlayerAction::Fold);
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        let mut table = handler.join().unwrap();
+
+        let (addr, messages) = start_collector();
+        let id3 = uuid::Uuid::new_v4();
+        let settings3 = PlayerConfig::new(id3, Some("Human3".to_string()), Some(addr));
+        table.add_human(settings3, None, None).unwrap();
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::from([MetaAction::LastHand(id3)])));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+
+        let mut replay = None;
+        for _ in 0..50 {
+            replay = messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .find(|message| message["msg_type"] == "last_hand");
+            if replay.is_some() {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        let replay = replay.unwrap();
+        let events: Vec<&json::JsonValue> = replay["events"].members().collect();
+        assert_eq!(events.first().unwrap()["msg_type"], "new_hand");
+        assert!(events.iter().any(|event| event["msg_type"] == "finish_hand"));
+    }
+
+    /// a player whose stack fell below the table minimum is sat out before the next deal, and told why
+    #[test]
+    fn short_stack_sat_out() {
+        let mut table = Table::default();
+        table.min_stack_to_play = table.big_blind;
+        let (addr, messages) = start_collector();
+        let id1 = uuid::Uuid::new_v4();
+        let settings1 = PlayerConfig::new(id1, Some("Human1".to_string()), Some(addr));
+        table.add_human(settings1, None, None).unwrap();
+        let id2 = uuid::Uuid::new_v4();
+        let settings2 = PlayerConfig::new(id2, Some("Human2".to_string()), None);
+        table.add_human(settings2, None, None).unwrap();
+        table.players[0].as_mut().unwrap().money = 6;
+        table.players[1].as_mut().unwrap().money = 8;
+
+        table.sit_out_short_stacks();
+        assert!(table.players[0].as_ref().unwrap().is_sitting_out);
+        assert!(!table.players[1].as_ref().unwrap().is_sitting_out);
+
+        let mut prompt = None;
+        for _ in 0..50 {
+            prompt = messages
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| json::parse(message).unwrap())
+                .find(|message| message["msg_type"] == "below_min_stack");
+            if prompt.is_some() {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        let prompt = prompt.unwrap();
+        assert_eq!(prompt["money"], 6);
+        assert_eq!(prompt["min_stack_to_play"], 8);
+    }
+
+    /// three-handed with a 2 ante, where the big blind only has 9 chips. The button and small blind call,
+    /// and the big blind's aces hold. Returns the last preflop game state, the flop game state, and the table
+    fn play_short_big_blind(ante_order: AnteOrder) -> (json::JsonValue, json::JsonValue, Table) {
+        let mut deck = RiggedDeck::new();
+        let cards = [
+            (Rank::Seven, Suit::Club), (Rank::Two, Suit::Diamond), // the button
+            (Rank::Eight, Suit::Heart), (Rank::Three, Suit::Spade), // the small blind
+            (Rank::Ace, Suit::Club), (Rank::Ace, Suit::Diamond), // the big blind
+            (Rank::Four, Suit::Diamond), // burned
+            (Rank::King, Suit::Heart), (Rank::Nine, Suit::Spade), (Rank::Five, Suit::Diamond),
+            (Rank::Four, Suit::Club), // burned
+            (Rank::Jack, Suit::Club),
+            (Rank::Four, Suit::Spade), // burned
+            (Rank::Four, Suit::Heart),
+        ];
+        for (rank, suit) in cards {
+            deck.push(Card { rank, suit });
+        }
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.ante_mode = AnteMode::PerPlayer(2);
+        table.ante_order = ante_order;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.players[2].as_mut().unwrap().money = 9;
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::Call);
+        while incoming_actions.lock().unwrap().contains_key(&ids[1]) {
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        // then the two of them check it down
+        while !handler.is_finished() {
+            for id in [ids[0], ids[1]] {
+                incoming_actions.lock().unwrap().entry(id).or_insert(PlayerAction::Check);
+            }
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        let table = handler.join().unwrap();
+
+        let states: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .filter(|message| message["msg_type"] == "game_state")
+            .collect();
+        let flop_idx = states.iter().position(|state| state["flop"].is_string()).unwrap();
+        (states[flop_idx - 1].clone(), states[flop_idx].clone(), table)
+    }
+
+    /// with the ante first, the short big blind posts the ante and is all-in on the blind for 7.
+    /// Either way around, they have 9 in, so the main pot is 27 and the other two play for a side pot of 2
+    #[test]
+    fn ante_before_blinds_short_stack() {
+        let (preflop_state, flop_state, table) = play_short_big_blind(AnteOrder::BeforeBlinds);
+        assert_eq!(preflop_state["players"][2]["preflop_cont"], 7);
+        assert_eq!(flop_state["pots"], json::array![27, 2]);
+        // the aces win the main pot, and the small blind's eight high beats the button's seven for the side pot
+        assert_eq!(table.players[2].as_ref().unwrap().money, 27);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 992);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 990);
+    }
+
+    /// with the ante after the blinds, the short big blind posts the full blind and is all-in on the ante for 1
+    #[test]
+    fn ante_after_blinds_short_stack() {
+        let (preflop_state, flop_state, table) = play_short_big_blind(AnteOrder::AfterBlinds);
+        assert_eq!(preflop_state["players"][2]["preflop_cont"], 8);
+        assert_eq!(flop_state["pots"], json::array![27, 2]);
+        assert_eq!(table.players[2].as_ref().unwrap().money, 27);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 992);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 990);
+    }
+
+    /// a big blind and ante that overflow when added together are rejected rather than wrapping around,
+    /// even against the biggest buy in
+    #[test]
+    fn validate_config_near_u32_max() {
+	assert_eq!(
+	    Table::validate_config(4, u32::MAX, AnteMode::PerPlayer(1), u32::MAX),
+	    Err(ConfigError::BlindsLargerThanBuyIn)
+	);
+	assert_eq!(
+	    Table::validate_config(u32::MAX / 2, u32::MAX / 2 + 1, AnteMode::BigBlind(u32::MAX), u32::MAX),
+	    Err(ConfigError::BlindsLargerThanBuyIn)
+	);
+	assert_eq!(Table::validate_config(4, u32::MAX - 1, AnteMode::PerPlayer(1), u32::MAX), Ok(()));
+    }
+
+    /// the admin can't lower the big blind below the small blind, or raise it past the buy in,
+    /// and a blind schedule is checked level by level
+    #[test]
+    fn admin_invalid_config() {
+        let mut table = Table::default();
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let id = uuid::Uuid::new_v4();
+	table.admin_id = id;
+	table.password = Some("arbitrary".to_string());
+
+	assert_eq!(table.check_admin_command(&AdminCommand::BigBlind(2)), Err(ConfigError::BigBlindBelowSmallBlind));
+	assert_eq!(table.check_admin_command(&AdminCommand::BigBlind(0)), Err(ConfigError::ZeroBigBlind));
+	assert_eq!(table.check_admin_command(&AdminCommand::BuyIn(6)), Err(ConfigError::BlindsLargerThanBuyIn));
+	assert_eq!(
+	    table.check_admin_command(&AdminCommand::AnteMode(AnteMode::BigBlind(995))),
+	    Err(ConfigError::BlindsLargerThanBuyIn)
+	);
+	let schedule = BlindSchedule::parse("10 5/10/1,600/1200/0").unwrap();
+	assert_eq!(
+	    table.check_admin_command(&AdminCommand::SetBlindSchedule(schedule)),
+	    Err(ConfigError::BlindsLargerThanBuyIn)
+	);
+	assert_eq!(table.check_admin_command(&AdminCommand::BigBlind(4)), Ok(()));
+
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(id, AdminCommand::BigBlind(2)));
+        incoming_meta_actions
+            .lock()
+            .unwrap()
+            .push_back(MetaAction::Admin(id, AdminCommand::SmallBlind(10)));
+        table.handle_meta_actions(&incoming_meta_actions, true, None);
+	// nothing changed
+	assert_eq!(table.small_blind, 4);
+	assert_eq!(table.big_blind, 8);
+    }
+
+    /// with busted players removed, the loser of an all-in loses their seat before the next hand
+    #[test]
+    fn busted_player_removed() {
+        let mut deck = RiggedDeck::new();
+        let cards = [
+            (Rank::Ace, Suit::Club), (Rank::Ace, Suit::Diamond), // the big blind
+            (Rank::King, Suit::Club), (Rank::King, Suit::Diamond), // the button, who posts the small blind
+            (Rank::Four, Suit::Diamond), // burned
+            (Rank::Two, Suit::Spade), (Rank::Seven, Suit::Diamond), (Rank::Nine, Suit::Heart),
+            (Rank::Four, Suit::Club), // burned
+            (Rank::Four, Suit::Spade),
+            (Rank::Four, Suit::Heart), // burned
+            (Rank::Jack, Suit::Spade),
+        ];
+        for (rank, suit) in cards {
+            deck.push(Card { rank, suit });
+        }
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.remove_busted = true;
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), Some(addr.clone()));
+            table.add_human(settings, None, None).unwrap();
+        }
+
+        // the small blind shoves with kings, and the big blind calls with aces
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(1000));
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+        let handler = std::thread::spawn(move || {
+            table.play(&cloned_actions, &cloned_meta_actions, Some(1));
+            table // return the table back
+        });
+        let table = handler.join().unwrap();
+
+        assert_eq!(table.players[0].as_ref().unwrap().money, 2000);
+        assert!(table.players[1].is_none());
+        assert!(!table.player_ids_to_configs.contains_key(&ids[1]));
+        assert_eq!(table.departed_results.len(), 1);
+        // the message is sent just before the table stops, so it may still be on its way
+        let is_busted = |message: &String| json::parse(message).unwrap()["msg_type"] == "player_busted";
+        while !messages.lock().unwrap().iter().any(is_busted) {
+            thread::sleep(time::Duration::from_millis(20));
+        }
+        let busted = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "player_busted")
+            .unwrap();
+        assert_eq!(busted["index"], 1);
+        assert_eq!(busted["player_name"], "Human1");
+    }
+
+    /// all-in preflop heads-up on a four spade board. The big blind's lone ace of spades makes the nut flush
+    /// in Hold'em, but in Omaha only two hole cards can play, so the button's kings make the best hand
+    #[test]
+    fn omaha_must_use_two_hole_cards() {
+        let play = |variant: GameVariant| {
+            let deck = RiggedDeck::from_str(
+                "As 9c 8c 4h Kh Kd Jc Tc \
+                 2c Ks Qs 7s 2h 2s 3c 3d",
+            )
+            .unwrap();
+            let mut table = Table::default();
+            table.deck = Box::new(deck);
+            table.variant = variant;
+            table.button_idx = 1; // heads-up, the button posts the small blind
+            let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+            let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+            let cloned_actions = incoming_actions.clone();
+            let cloned_meta_actions = incoming_meta_actions.clone();
+
+            let (addr, messages) = start_collector();
+            let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+            for (i, id) in ids.iter().enumerate() {
+                let addr = if i == 0 { Some(addr.clone()) } else { None };
+                let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+                table.add_human(settings, None, None).unwrap();
+            }
+            let handler = std::thread::spawn(move || {
+                table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+                table // return the table back
+            });
+            incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(1000));
+            incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+            handler.join().unwrap();
+            winning_payouts(&messages)
+        };
+        assert_eq!(play(GameVariant::Holdem), [(0, 0, 2000)]);
+        assert_eq!(play(GameVariant::Omaha), [(0, 1, 2000)]);
+    }
+
+    /// the finish_hand message of a heads-up hand where the button's action is given,
+    /// and the big blind calls anything. Returns the table too, to check the total raked
+    fn play_raked_hand(rake_percent: u32, rake_cap: Option<u32>, button_action: PlayerAction) -> (Table, json::JsonValue) {
+        let mut table = Table::default();
+        table.rake_percent = rake_percent;
+        table.rake_cap = rake_cap;
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        incoming_actions.lock().unwrap().insert(ids[1], button_action);
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+        table.play_one_hand(&incoming_actions, &incoming_meta_actions);
+        thread::sleep(time::Duration::from_millis(100));
+        let finish_hand = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .find(|message| message["msg_type"] == "finish_hand")
+            .unwrap();
+        (table, finish_hand)
+    }
+
+    /// an all-in pot is raked by its percentage, unless that would go over the cap
+    #[test]
+    fn rake_with_cap() {
+        let (table, finish_hand) = play_raked_hand(1, None, PlayerAction::RaiseTo(1000));
+        assert_eq!(finish_hand["rake"], 20);
+        assert_eq!(table.total_raked, 20);
+        let money: u32 = table.players.iter().flatten().map(|player| player.money).sum();
+        assert_eq!(money, 2000 - 20);
+
+        let (table, finish_hand) = play_raked_hand(5, Some(30), PlayerAction::RaiseTo(1000));
+        assert_eq!(finish_hand["rake"], 30);
+        assert_eq!(finish_hand["total_raked"], 30);
+        let money: u32 = table.players.iter().flatten().map(|player| player.money).sum();
+        assert_eq!(money, 2000 - 30);
+    }
+
+    /// a hand that ends before the flop isn't raked
+    #[test]
+    fn rake_no_flop_no_drop() {
+        let (table, finish_hand) = play_raked_hand(5, None, PlayerAction::Fold);
+        assert_eq!(finish_hand["rake"], 0);
+        assert_eq!(table.total_raked, 0);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1004);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 996);
+    }
+
+    /// in Stud the lowest card showing brings it in, rather than anyone posting blinds.
+    /// Two deuces are showing, and the deuce of clubs is the lower by suit
+    #[test]
+    fn stud_bring_in_by_lowest_up_card() {
+        let deck = RiggedDeck::from_str(
+            "Ac Ad 9h \
+             Kc Kd 2s \
+             Qc Qd 2c",
+        )
+        .unwrap();
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.variant = GameVariant::SevenStud;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        // after the bring-in, the action goes around from the next seat
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Fold);
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::Fold);
+        let table = handler.join().unwrap();
+
+        assert_eq!(winning_payouts(&messages), [(0, 2, 4)]);
+        let brought_in = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .any(|message| message["msg_type"] == "game_state" && message["players"][2]["last_action"] == "bring_in:4");
+        assert!(brought_in);
+        for player in table.players.iter().flatten() {
+            assert_eq!(player.money, 1000);
+        }
+    }
+
+    /// a Stud hand all-in on third street is dealt out street by street, with a burn before each.
+    /// The up-cards are shown to everyone, and the winner needs their last card (dealt down) for a straight
+    #[test]
+    fn stud_full_deal_sequence() {
+        let deck = RiggedDeck::from_str(
+            "4d 3d 5h  Ah Kd 4c \
+             9s 7s 7d \
+             9c 8h Qs \
+             9d Jh Jc \
+             Th 6d As",
+        )
+        .unwrap();
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.variant = GameVariant::SevenStud;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        // the four of clubs brings it in, then the five of hearts moves all-in
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::RaiseTo(1000));
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::Call);
+        handler.join().unwrap();
+
+        assert_eq!(winning_payouts(&messages), [(0, 0, 2000)]);
+        let states: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .filter(|message| message["msg_type"] == "game_state" && message["street"].is_string())
+            .collect();
+        let last_of = |street: &str| states.iter().rfind(|state| state["street"] == street).unwrap();
+        let third = last_of("preflop");
+        assert_eq!(third["players"][0]["up_cards"], "5h");
+        assert_eq!(third["players"][1]["up_cards"], "4c");
+        assert_eq!(third["hole_cards"], "4d3d5h");
+        let seventh = last_of("seventh");
+        assert_eq!(seventh["players"][0]["up_cards"], "5h7s8hJh");
+        assert_eq!(seventh["players"][1]["up_cards"], "4c7dQsJc");
+        // the last card is dealt down, so only its owner sees it
+        assert_eq!(seventh["hole_cards"], "4d3d5h7s8hJh6d");
+        assert!(seventh["flop"].is_null() && seventh["river"].is_null());
+    }
+
+    /// when both players are all-in preflop, both hands are turned face up before the flop
+    #[test]
+    fn all_in_reveal_preflop() {
+        let mut deck = RiggedDeck::new();
+        let cards = [
+            (Rank::Ace, Suit::Club), (Rank::Ace, Suit::Diamond), // the big blind
+            (Rank::King, Suit::Club), (Rank::King, Suit::Diamond), // the button, who posts the small blind
+            (Rank::Four, Suit::Diamond), // burned
+            (Rank::Two, Suit::Spade), (Rank::Seven, Suit::Diamond), (Rank::Nine, Suit::Heart),
+            (Rank::Four, Suit::Club), // burned
+            (Rank::Four, Suit::Spade),
+            (Rank::Four, Suit::Heart), // burned
+            (Rank::Jack, Suit::Spade),
+        ];
+        for (rank, suit) in cards {
+            deck.push(Card { rank, suit });
+        }
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(1000));
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+        handler.join().unwrap();
+
+        let messages: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        let reveal = messages
+            .iter()
+            .position(|message| message["msg_type"] == "all_in_reveal")
+            .unwrap();
+        let first_flop = messages
+            .iter()
+            .position(|message| message["msg_type"] == "game_state" && message["flop"].is_string())
+            .unwrap();
+        assert!(reveal < first_flop);
+        let hands = &messages[reveal]["hands"];
+        assert_eq!(hands.len(), 2);
+        // the button shoved, so as the last aggressor their hand is shown first
+        assert_eq!(hands[0]["index"], 1);
+        assert_eq!(hands[0]["hole_cards"], "KcKd");
+        assert_eq!(hands[1]["hole_cards"], "AcAd");
+        assert!(hands.members().all(|hand| hand["all_in"] == true));
+    }
+
+    /// with a reveal delay, each all-in hand is sent on its own, starting from the last aggressor
+    /// (or left of the button in position order), and spaced out by the delay
+    #[test]
+    fn all_in_reveal_order_and_delay() {
+        let mut table = Table::default();
+        table.max_pause = None;
+        table.all_in_reveal_delay = time::Duration::from_millis(50);
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..3).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+            let player = table.players[i].as_mut().unwrap();
+            player.is_active = true;
+            player.hole_cards = vec![Card { rank: Rank::Ace, suit: Suit::Club }, Card { rank: Rank::King, suit: Suit::Club }];
+        }
+        let mut gamehand = GameHand::default();
+        gamehand.action_log = vec![
+            (1, PlayerAction::Call, Street::Preflop),
+            (2, PlayerAction::Raise(100), Street::Preflop),
+            (0, PlayerAction::Call, Street::Preflop),
+            (1, PlayerAction::Call, Street::Preflop),
+        ];
+
+        // a chat sent while the hands are being revealed is passed on during the delay
+        let meta_actions = Arc::new(Mutex::new(VecDeque::from([MetaAction::Chat(ids[1], "nice hand".to_string())])));
+
+        let reveal_order = |table: &mut Table| {
+            let start = time::Instant::now();
+            table.reveal_all_in_hands(&meta_actions, &gamehand);
+            let elapsed = start.elapsed();
+            let mut received: Vec<json::JsonValue> = vec![];
+            for _ in 0..50 {
+                received.extend(
+                    messages
+                        .lock()
+                        .unwrap()
+                        .drain(..)
+                        .map(|message| json::parse(&message).unwrap()),
+                );
+                if received.iter().filter(|message| message["msg_type"] == "all_in_reveal").count() == 3 {
+                    break;
+                }
+                thread::sleep(time::Duration::from_millis(20));
+            }
+            let reveals: Vec<&json::JsonValue> = received.iter().filter(|message| message["msg_type"] == "all_in_reveal").collect();
+            assert!(reveals.iter().all(|reveal| reveal["hands"].len() == 1));
+            let order: Vec<usize> = reveals.iter().map(|reveal| reveal["hands"][0]["index"].as_usize().unwrap()).collect();
+            let chatted = received.iter().any(|message| message["msg_type"] == "chat");
+            (order, elapsed, chatted)
+        };
+        let (order, elapsed, chatted) = reveal_order(&mut table);
+        assert_eq!(order, vec![2, 0, 1]);
+        // two gaps between the three hands
+        assert!(elapsed >= time::Duration::from_millis(100));
+        assert!(chatted);
+        assert!(meta_actions.lock().unwrap().is_empty());
+
+        table.all_in_reveal_order = RevealOrder::Position;
+        let (order, _, _) = reveal_order(&mut table);
+        assert_eq!(order, vec![1, 2, 0]);
+    }
+
+    /// all-in preflop with the board run twice: the aces hold on the first run,
+    /// but the kings make a set on the second, so each player gets back half the pot
+    #[test]
+    fn run_it_twice_splits_pot() {
+        let mut deck = RiggedDeck::new();
+        let cards = [
+            (Rank::Ace, Suit::Club), (Rank::Ace, Suit::Diamond), // the big blind
+            (Rank::King, Suit::Club), (Rank::King, Suit::Diamond), // the button, who posts the small blind
+            (Rank::Four, Suit::Diamond), // burned
+            (Rank::Two, Suit::Spade), (Rank::Seven, Suit::Diamond), (Rank::Nine, Suit::Heart),
+            (Rank::Four, Suit::Club), // burned
+            (Rank::Four, Suit::Spade),
+            (Rank::Four, Suit::Heart), // burned
+            (Rank::Jack, Suit::Spade),
+            // the second run
+            (Rank::Six, Suit::Diamond), // burned
+            (Rank::King, Suit::Spade), (Rank::Three, Suit::Heart), (Rank::Five, Suit::Club),
+            (Rank::Six, Suit::Club), // burned
+            (Rank::Eight, Suit::Diamond),
+            (Rank::Six, Suit::Spade), // burned
+            (Rank::Queen, Suit::Heart),
+        ];
+        for (rank, suit) in cards {
+            deck.push(Card { rank, suit });
+        }
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.run_it_twice = true;
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(1000));
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+        let table = handler.join().unwrap();
+
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1000);
+        assert_eq!(table.players[1].as_ref().unwrap().money, 1000);
+
+        let messages: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        let run_it_twice = messages
+            .iter()
+            .find(|message| message["msg_type"] == "run_it_twice")
+            .unwrap();
+        assert_eq!(run_it_twice["boards"][0].members().map(|card| card.to_string()).collect::<String>(), "2s7d9h4sJs");
+        assert_eq!(run_it_twice["boards"][1].members().map(|card| card.to_string()).collect::<String>(), "Ks3h5c8dQh");
+        let finish = messages
+            .iter()
+            .find(|message| message["msg_type"] == "finish_hand")
+            .unwrap();
+        let winners: Vec<(usize, usize, u32)> = finish["settlements"]
+            .members()
+            .filter(|settlement| settlement["winner"] == true)
+            .map(|settlement| (
+                settlement["run"].as_usize().unwrap(),
+                settlement["index"].as_usize().unwrap(),
+                settlement["payout"].as_u32().unwrap(),
+            ))
+            .collect();
+        assert_eq!(winners, vec![(0, 0, 1000), (1, 1, 1000)]);
+    }
+
+    /// all-in preflop, the aces are the favorite. But the kings flop a set,
+    /// so the equities broadcast after the flop swing their way
+    #[test]
+    fn runout_equities_update_after_flop() {
+        let mut deck = RiggedDeck::new();
+        let cards = [
+            (Rank::Ace, Suit::Club), (Rank::Ace, Suit::Diamond), // the big blind
+            (Rank::King, Suit::Club), (Rank::King, Suit::Diamond), // the button, who posts the small blind
+            (Rank::Four, Suit::Diamond), // burned
+            (Rank::King, Suit::Heart), (Rank::Seven, Suit::Diamond), (Rank::Two, Suit::Spade),
+            (Rank::Four, Suit::Club), // burned
+            (Rank::Four, Suit::Spade),
+            (Rank::Four, Suit::Heart), // burned
+            (Rank::Jack, Suit::Spade),
+        ];
+        for (rank, suit) in cards {
+            deck.push(Card { rank, suit });
+        }
+        let mut table = Table::default();
+        table.deck = Box::new(deck);
+        table.show_runout_equities = true;
+        table.button_idx = 1; // heads-up, the button posts the small blind
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+
+        let handler = std::thread::spawn(move || {
+            table.play_one_hand(&cloned_actions, &cloned_meta_actions);
+            table // return the table back
+        });
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(1000));
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Call);
+        handler.join().unwrap();
+
+        let updates: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .filter(|message| message["msg_type"] == "runout_equities")
+            .collect();
+        let streets: Vec<String> = updates.iter().map(|update| update["street"].to_string()).collect();
+        assert_eq!(streets, vec!["preflop", "flop", "turn", "river"]);
+        let equity = |update: &json::JsonValue, index: usize| {
+            update["equities"]
+                .members()
+                .find(|equity| equity["index"] == index)
+                .unwrap()["equity"]
+                .as_f64()
+                .unwrap()
+        };
+        assert!(equity(&updates[0], 0) > 0.7);
+        assert!(equity(&updates[1], 1) > 0.9);
+        // on the river, the set has won
+        assert_eq!(equity(&updates[3], 1), 1.0);
+    }
+
+    /// with rebuys held until the start of an orbit, losing the small blind on the first hand
+    /// doesn't top the player up. After the second hand the button is back around, so they rebuy
+    /// for everything they lost in the orbit
+    #[test]
+    fn rebuy_waits_for_orbit() {
+        let mut table = Table::default();
+        table.rebuys_at_orbit_start = true;
+        let incoming_actions = Arc::new(Mutex::new(HashMap::<Uuid, PlayerAction>::new()));
+        let incoming_meta_actions = Arc::new(Mutex::new(VecDeque::<MetaAction>::new()));
+        let cloned_actions = incoming_actions.clone();
+        let cloned_meta_actions = incoming_meta_actions.clone();
+
+        let (addr, messages) = start_collector();
+        let ids: Vec<Uuid> = (0..2).map(|_| uuid::Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            let addr = if i == 0 { Some(addr.clone()) } else { None };
+            let settings = PlayerConfig::new(*id, Some(format!("Human{}", i)), addr);
+            table.add_human(settings, None, None).unwrap();
+        }
+        table.players[0].as_mut().unwrap().auto_rebuy = true;
+
+        // on the first hand, player0 has the button and folds the small blind
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Fold);
+        let handler = std::thread::spawn(move || {
+            table.play(&cloned_actions, &cloned_meta_actions, Some(2));
+            table // return the table back
+        });
+        let is_second_hand = |message: &String| {
+            let message = json::parse(message).unwrap();
+            message["msg_type"] == "new_hand" && message["hand_num"] == 2
+        };
+        while !messages.lock().unwrap().iter().any(is_second_hand) {
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        // on the second hand, player1 raises from the small blind and player0 folds the big blind
+        incoming_actions.lock().unwrap().insert(ids[1], PlayerAction::RaiseTo(100));
+        incoming_actions.lock().unwrap().insert(ids[0], PlayerAction::Fold);
+        let table = handler.join().unwrap();
+
+        let messages: Vec<json::JsonValue> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| json::parse(message).unwrap())
+            .collect();
+        let second_hand = messages
+            .iter()
+            .position(|message| message["msg_type"] == "new_hand" && message["hand_num"] == 2)
+            .unwrap();
+        // no top up mid-orbit
+        assert_eq!(messages[second_hand + 1]["players"][0]["money"], 996);
+        let rebuys: Vec<&json::JsonValue> = messages
+            .iter()
+            .filter(|message| message["msg_type"] == "rebuy")
+            .collect();
+        assert_eq!(rebuys.len(), 1);
+        assert_eq!(rebuys[0]["amount"], 12);
+        assert_eq!(table.players[0].as_ref().unwrap().money, 1000);
+    }
+
+    /// a player without a name is shown by their seat, rather than taking down the table
+    #[test]
+    fn game_state_player_without_name() {
+        let mut table = Table::default();
+        table.add_human(PlayerConfig::new(uuid::Uuid::new_v4(), Some("Human0".to_string()), None), None, None).unwrap();
+        table.add_human(PlayerConfig::new(uuid::Uuid::new_v4(), None, None), None, None).unwrap();
+        let state = table.game_state(None, false).to_json();
+        assert_eq!(state["players"][0]["player_name"], "Human0");
+        assert_eq!(state["players"][1]["player_name"], "Seat 2");
+    }
+
+    /// at an anonymous table, the broadcast state labels everyone by seat and never leaks a real name,
+    /// while the names are still kept on the server
+    #[test]
+    fn anonymous_table() {
+        let mut game = TestTable::new(2);
+        game.table.anonymous = true;
+        game.act(0, PlayerAction::Fold).act(1, PlayerAction::Fold);
+        assert!(game.play_hand());
+
+        let state = game.last_message("game_state");
+        let names: Vec<&str> = state["players"]
+            .members()
+            .filter_map(|player| player["player_name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["Seat 1", "Seat 2"]);
+        let finish_hand = game.last_message("finish_hand");
+        assert!(finish_hand["settlements"][0]["player_name"].as_str().unwrap().starts_with("Seat "));
+        for message in game.messages.lock().unwrap().iter() {
+            let message = json::parse(message).unwrap();
+            if message["msg_type"] == "game_state" || message["msg_type"] == "finish_hand" {
+                assert!(!message.dump().contains("Human"), "leaked a name: {}", message.dump());
+            }
+        }
+        let config = game.table.player_ids_to_configs.get(&game.ids[1]).unwrap();
+        assert_eq!(config.name.as_deref(), Some("Human1"));
+    }
 }
@@ -2,9 +2,10 @@ use clap::Parser;
 
 mod logic;
 
+use std::collections::{HashMap, VecDeque};
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 
 use actix::*;
@@ -20,6 +21,8 @@ mod messages;
 mod session;
 
 const LOCAL_HOST: &str = "localhost";
+// how many bots sit at the table when playing headless
+const HEADLESS_BOTS: usize = 3;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -31,6 +34,36 @@ struct Args {
     /// port
     #[arg(short, long, default_value_t = 8080)]
     port: u16,
+
+    /// the longest name a player can give a table they create
+    #[arg(long, default_value_t = hub::MAX_TABLE_NAME_LEN)]
+    max_table_name_len: usize,
+
+    /// for debugging shuffles, log the deck order of every hand.
+    /// Never use this in production, since it leaks the cards
+    #[arg(long, default_value_t = false)]
+    audit_deck: bool,
+
+    /// for reproducing games, every table shuffles from this seed and shares each hand's seed.
+    /// Never use this in production, since anyone with the seed knows the cards
+    #[arg(long)]
+    deck_seed: Option<u64>,
+
+    /// for training, show how many cards are left in the deck in the game state
+    #[arg(long, default_value_t = false)]
+    show_deck_count: bool,
+
+    /// the most sessions that can be connected at once, across every table and the lobby
+    #[arg(long)]
+    max_sessions: Option<usize>,
+
+    /// save every table to this directory after each hand, and restore them from it on startup
+    #[arg(long)]
+    state_dir: Option<std::path::PathBuf>,
+
+    /// instead of starting the server, play this many hands between bots and print them to stdout
+    #[arg(long)]
+    headless_hands: Option<u32>,
 }
 
 async fn index() -> impl Responder {
@@ -71,16 +104,64 @@ async fn reconnect(
     )
 }
 
+#[derive(serde::Deserialize)]
+struct EventsQuery {
+    tables: Option<String>, // comma separated table names
+}
+
+/// Entry point for external clients that want to listen to game events,
+/// e.g. "/events?tables=ABCD,EFGH" or "/events" for every table
+#[get("/events")]
+async fn events(
+    query: web::Query<EventsQuery>,
+    req: HttpRequest,
+    stream: web::Payload,
+    hub_addr: web::Data<Addr<hub::TableHub>>,
+) -> Result<HttpResponse, Error> {
+    let tables = query
+        .tables
+        .as_ref()
+        .map(|tables| tables.split(',').map(|name| name.trim().to_owned()).collect());
+    ws::start(
+        session::WsEventSession::new(tables, hub_addr.get_ref().clone()),
+        &req,
+        stream,
+    )
+}
+
 /// Displays state
 async fn get_count(count: web::Data<AtomicUsize>) -> impl Responder {
     let current_count = count.load(Ordering::SeqCst);
     format!("Visitors: {current_count}")
 }
 
+/// play some hands between bots on a table that isn't connected to a hub,
+/// which shows off the engine without needing a browser
+fn play_headless(num_hands: u32, num_bots: usize) {
+    let mut table = logic::Table::default();
+    for i in 0..num_bots {
+        table
+            .add_bot(format!("Bot {}", i))
+            .expect("error adding bot to the headless table");
+    }
+    let actions = Arc::new(Mutex::new(HashMap::new()));
+    let meta_actions = Arc::new(Mutex::new(VecDeque::new()));
+    table.play(&actions, &meta_actions, Some(num_hands));
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     let args = Args::parse();
 
+    if let Some(num_hands) = args.headless_hands {
+        // the hands are followed through the table's debug logging, which goes to stdout
+        env_logger::Builder::from_env(env_logger::Env::new().default_filter_or("debug"))
+            .target(env_logger::Target::Stdout)
+            .init();
+        play_headless(num_hands, HEADLESS_BOTS);
+        return Ok(());
+    }
+
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
     // set up applications state
@@ -88,7 +169,14 @@ async fn main() -> std::io::Result<()> {
     let app_state = Arc::new(AtomicUsize::new(0));
 
     // start main hub actor
-    let hub = hub::TableHub::new().start();
+    let hub = hub::TableHub::new()
+        .with_max_table_name_len(args.max_table_name_len)
+        .with_audit_deck(args.audit_deck)
+        .with_deck_seed(args.deck_seed)
+        .with_show_deck_count(args.show_deck_count)
+        .with_max_sessions(args.max_sessions)
+        .with_state_dir(args.state_dir)
+        .start();
 
     log::info!("starting HTTP server at http://{}:{}", args.ip, args.port);
 
@@ -99,6 +187,7 @@ async fn main() -> std::io::Result<()> {
             .service(web::resource("/").to(index))
             .service(reconnect)
             .service(new_connection)	    
+            .service(events)
             .route("/count", web::get().to(get_count))
             .service(Files::new("/", "./site/"))
             .wrap(Logger::default())
@@ -108,3 +197,45 @@ async fn main() -> std::io::Result<()> {
     .run()
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::play_headless;
+    use std::sync::Mutex;
+
+    /// a table of bots plays a hand all the way through on its own
+    #[test]
+    fn headless_hand() {
+        play_headless(1, 2);
+    }
+
+    /// keeps the log lines written by the headless_hand_is_logged test's own thread
+    struct CaptureLogger;
+
+    static CAPTURED: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            if std::thread::current().name().is_some_and(|name| name.ends_with("headless_hand_is_logged")) {
+                CAPTURED.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// the headless hands are followed through the logs, so playing one has to log it
+    #[test]
+    fn headless_hand_is_logged() {
+        log::set_logger(&CaptureLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+        play_headless(1, 2);
+        let captured = CAPTURED.lock().unwrap();
+        assert!(captured.iter().any(|line| line.starts_with("Playing hand 1")));
+        assert!(captured.iter().any(|line| line.starts_with("settlements")));
+    }
+}
@@ -1,5 +1,6 @@
-use crate::logic::{player::PlayerAction, PlayerConfig};
+use crate::logic::{player::PlayerAction, AnteMode, AnteOrder, BettingStructure, BlindSchedule, ClockScaling, ExposurePolicy, GameVariant, HandRanking, MinRaiseRule, PlayerConfig, ReshufflePolicy, RevealOrder};
 use actix::prelude::{Message, Recipient};
+use std::collections::HashSet;
 use std::fmt;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
@@ -8,13 +9,26 @@ use serde::{Deserialize, Serialize};
 /// to the running games Player name change. player join/leave
 #[derive(Debug)]
 pub enum MetaAction {
-    Join(PlayerConfig, Option<String>), // player config and optional password
+    Join(PlayerConfig, Option<String>, Option<usize>), // player config, optional password, and optional seat index
+    Spectate(PlayerConfig, Option<String>), // watch the table without a seat, with the optional password
     UpdateAddress(Uuid, Recipient<WsMessage>), // update a player with an existing uuid and new message address
     Leave(Uuid),
     SitOut(Uuid),    
     ImBack(Uuid),
     SetPlayerName(Uuid, String),
+    AutoMuckDelay(Uuid, u32), // how many seconds to keep a losing hand before mucking it
+    AutoMuck(Uuid, bool), // whether to muck a losing hand at showdown when not required to show it
+    AutoRebuy(Uuid, bool), // whether to top back up to the buy-in between hands
+    RebuyTarget(Uuid, Option<u32>), // the stack to top back up to instead of the buy-in, or None for the buy-in
+    QuickFold(Uuid, bool), // queue (or cancel) a fold for the current (or next) hand, after posting any blind
+    Straddle(Uuid, bool), // whether to straddle when the action reaches them before anyone has acted preflop
+    ProposeChop(Uuid), // an all-in player proposes to split the pots by equity
+    AcceptChop(Uuid), // another all-in player agrees to the proposed chop
+    Rematch(Uuid), // once a heads-up match is over, a player agrees to play again from fresh stacks
     SendPlayerName(Uuid),    
+    ShowToPlayer(Uuid, Uuid), // privately show the first player's hole cards to the second, and nobody else
+    Roster(Uuid), // send the list of everyone at the table, and their roles, to this player
+    LastHand(Uuid), // replay the events of the last completed hand to this player
     Chat(Uuid, String),
     Admin(Uuid, AdminCommand),
     TableInfo(Recipient<WsMessage>), // send the table info to the given address
@@ -27,11 +41,54 @@ pub enum MetaAction {
 pub enum AdminCommand {
     SmallBlind(u32),
     BigBlind(u32),
+    AnteMode(AnteMode),
+    AnteOrder(AnteOrder),
+    SetBlindSchedule(BlindSchedule),
     BuyIn(u32),
     SetPassword(String),
     ShowPassword,    
+    MinRaiseRule(MinRaiseRule),
+    BettingStructure(BettingStructure),
+    GameVariant(GameVariant),
+    BadBeatThreshold(HandRanking),
+    FreezeClocks,
+    ActionTimeout(u32),
+    ClockScaling(ClockScaling),
+    TimeBank(u32),
+    TimeBankReplenish(u32),
+    FinishDelay(u32),
+    MaxFinishDelay(u32),
+    BotsOnlyMaxPause(u32), // milliseconds
+    FinishDelayPotScaling(bool),
+    AllowChop(bool),
+    RunItTwice(bool),
+    ShowRunoutEquities(bool),
+    AllInRevealOrder(RevealOrder),
+    AllInRevealDelay(u32), // milliseconds
+    Anonymous(bool), // the players are shown as "Seat N" instead of by name
+    AllowRebuys(bool),
+    MaxStack(u32),
+    RakePercent(u32),
+    RakeCap(u32), // the most raked from a single hand, 0 for no cap
+    MinStackToPlay(u32),
+    MinPlayersToStart(usize),
+    RebuysAtOrbitStart(bool),
+    RemoveBusted(bool),
+    MaxStraddles(u32), // how long a chain of straddles (and re-straddles) can get, 0 for no straddling
+    RematchWindow(u32), // seconds to agree to a rematch after a heads-up match, 0 for no rematches
+    CheckItDown(bool),
+    ExposeBotDecisions(bool),
+    ReshufflePolicy(ReshufflePolicy),
+    CommitShuffle(bool),
+    ExposurePolicy(ExposurePolicy),
+    UnfreezeClocks,
+    CloseIn(u32), // minutes until the table closes
     AddBot,
     RemoveBot,
+    Kick(Uuid), // remove a player from the table, as if they had left
+    KickSeat(usize), // the same, for whoever is in the seat
+    Mute(Uuid), // the player's (or spectator's) chat is no longer shown to the table
+    Unmute(Uuid),
     Restart,
     // NewAdmin(Uuid), // todo? would they give the name of the player or what?
 }
@@ -42,13 +99,14 @@ pub struct WsMessage(pub String);
 
 /// New ws session is created
 #[derive(Message)]
-#[rtype(result = "Uuid")]
+#[rtype(result = "Result<Uuid, ConnectError>")]
 pub struct Connect {
     pub id: Uuid,    
     pub addr: Recipient<WsMessage>,
 }
 
 /// Session is disconnected
+#[allow(dead_code)]
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct Disconnect {
@@ -62,16 +120,27 @@ impl actix::Message for ListTables {
     type Result = Vec<String>;
 }
 
-#[derive(Debug)]
+/// why a player couldn't join a table. The Display text is relayed to the client as is
+#[derive(Debug, PartialEq, Eq)]
 pub enum JoinTableError {
+    NameNotSet,
+    TableDoesNotExist(String), // contains the table name
     GameIsFull,
     InvalidPassword,
-    MissingPassword
+    MissingPassword,
+    SeatTaken(usize), // contains the seat index they asked for
+    InvalidSeat(usize), // contains the seat index they asked for, which isn't at the table
 }
 
 impl fmt::Display for JoinTableError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            JoinTableError::NameNotSet => {
+                write!(f, "You cannot join a game until you set your name!")
+            }
+            JoinTableError::TableDoesNotExist(table_name) => {
+                write!(f, "No table named {} exists.", table_name)
+            }
             JoinTableError::GameIsFull => {
                 write!(f, "Game is full.",)
             }
@@ -81,6 +150,44 @@ impl fmt::Display for JoinTableError {
             JoinTableError::MissingPassword => {
                 write!(f, "Password is required.")
             }
+            JoinTableError::SeatTaken(index) => {
+                write!(f, "Seat {} is not available.", index)
+            }
+            JoinTableError::InvalidSeat(index) => {
+                write!(f, "There is no seat {} at this table.", index)
+            }
+        }
+    }
+}
+
+/// a combination of table settings that can't be played, e.g. a big blind smaller than the small blind
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    ZeroBigBlind,
+    BigBlindBelowSmallBlind,
+    BlindsLargerThanBuyIn, // the big blind plus any ante can't be covered by the buy in
+    RakeOverWholePot, // the rake can't be more than 100 percent
+    MinPlayersToStart, // a hand needs at least two players, and no more than the table seats
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::ZeroBigBlind => {
+                write!(f, "The big blind must be more than 0.")
+            }
+            ConfigError::BigBlindBelowSmallBlind => {
+                write!(f, "The big blind can't be smaller than the small blind.")
+            }
+            ConfigError::BlindsLargerThanBuyIn => {
+                write!(f, "The big blind and ante must be smaller than the buy in.")
+            }
+            ConfigError::RakeOverWholePot => {
+                write!(f, "The rake can't be more than 100 percent of the pot.")
+            }
+            ConfigError::MinPlayersToStart => {
+                write!(f, "The players needed to start a hand must be at least 2, and no more than the seats at the table.")
+            }
         }
     }
 }
@@ -96,10 +203,18 @@ pub struct Join {
     pub table_name: String,
 
     pub password: Option<String>,
+
+    /// watch the table without taking a seat
+    pub spectate: bool,
+
+    /// which seat to sit in, or None for the first open one
+    pub seat: Option<usize>,
 }
 
 pub enum ReturnedReason {
     Left, // the player left
+    Busted, // the player ran out of chips at a table that removes busted players
+    Kicked, // the admin removed the player from the table
     HeartBeatFailed,
     FailureToJoin(JoinTableError),
 }
@@ -121,13 +236,31 @@ pub struct PlayerName {
     pub name: String,
 }
 
+#[derive(Debug)]
+pub enum ConnectError {
+    ServerFull(usize), // contains the max number of sessions
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConnectError::ServerFull(max_sessions) => {
+                write!(f, "The server is full ({} players). Please try again later.", max_sessions)
+            }
+        }
+    }
+}
+
 pub enum CreateTableError {
     NameNotSet,
     UnableToParseJson(String),
     PlayerDoesNotExist, // cannot be found in the lobby or at a table
     AlreadyAtTable(String),    // contains the table name
     TooManyBots,
-    TooLargeBlinds,
+    InvalidConfig(ConfigError),
+    EmptyTableName,
+    TableNameTooLong(usize), // contains the max length
+    TableNameTaken(String),  // contains the table name
 }
 
 impl fmt::Display for CreateTableError {
@@ -148,8 +281,17 @@ impl fmt::Display for CreateTableError {
             CreateTableError::TooManyBots => {
                 write!(f, "Too many bots selected")
             }
-            CreateTableError::TooLargeBlinds => {
-                write!(f, "Blinds must be smaller than the starting stacks.")
+            CreateTableError::InvalidConfig(err) => {
+                write!(f, "{}", err)
+            }
+            CreateTableError::EmptyTableName => {
+                write!(f, "The table name can't be empty.")
+            }
+            CreateTableError::TableNameTooLong(max_len) => {
+                write!(f, "The table name can be at most {} characters.", max_len)
+            }
+            CreateTableError::TableNameTaken(table_name) => {
+                write!(f, "There is already a table named {}", table_name)
             }
         }
     }
@@ -163,6 +305,7 @@ pub struct CreateFields {
     pub buy_in: u32,
     pub num_bots: u8,
     pub password: Option<String>,
+    pub table_name: Option<String>, // if not given, a random name is generated
 }
 
 /// Session wants to create a game
@@ -188,6 +331,7 @@ pub struct PlayerActionMessage {
 #[rtype(result = "()")]
 pub struct GameOver {
     pub table_name: String,
+    pub reconciliation_report: json::JsonValue, // each player's buy-ins versus final stack, for settling up
 }
 
 #[derive(Message)]
@@ -196,3 +340,83 @@ pub struct MetaActionMessage {
     pub id: Uuid,
     pub meta_action: MetaAction,
 }
+
+/// a table publishes an event (e.g. a new hand, the game state, the result of a hand)
+/// so that the hub can relay it to any external subscribers
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct GameEvent {
+    pub table_name: String,
+    pub event: String, // the json message of the event
+}
+
+/// an external client (e.g. analytics) wants to receive the GameEvents of every table,
+/// or only of the given table names
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SubscribeEvents {
+    pub addr: Recipient<WsMessage>,
+    pub tables: Option<HashSet<String>>,
+}
+
+#[cfg(test)]
+pub mod test_helpers {
+    use super::WsMessage;
+    use actix::prelude::{Actor, Context, Handler, Recipient, System};
+    use std::sync::{Arc, Mutex};
+
+    /// an actor that records every WsMessage it receives,
+    /// so that tests can check what was sent to a given address
+    pub struct MessageCollector(Arc<Mutex<Vec<String>>>);
+
+    impl Actor for MessageCollector {
+        type Context = Context<Self>;
+    }
+
+    impl Handler<WsMessage> for MessageCollector {
+        type Result = ();
+
+        fn handle(&mut self, msg: WsMessage, _: &mut Context<Self>) {
+            self.0.lock().unwrap().push(msg.0);
+        }
+    }
+
+    /// start a MessageCollector in its own thread and actix system, so that it can be used
+    /// from a plain blocking test. Returns the address to send to and the received messages.
+    pub fn start_collector() -> (Recipient<WsMessage>, Arc<Mutex<Vec<String>>>) {
+        let messages = Arc::new(Mutex::new(vec![]));
+        let cloned_messages = messages.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let system = System::new();
+            system.block_on(async move {
+                let addr = MessageCollector(cloned_messages).start();
+                tx.send(addr.recipient()).unwrap();
+            });
+            system.run().unwrap();
+        });
+        (rx.recv().unwrap(), messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the join errors are shown to the player as is, so they should read well
+    #[test]
+    fn join_table_error_messages() {
+        let messages = [
+            (JoinTableError::NameNotSet, "You cannot join a game until you set your name!"),
+            (JoinTableError::TableDoesNotExist("Home Game".to_owned()), "No table named Home Game exists."),
+            (JoinTableError::GameIsFull, "Game is full."),
+            (JoinTableError::InvalidPassword, "Invalid password."),
+            (JoinTableError::MissingPassword, "Password is required."),
+            (JoinTableError::SeatTaken(3), "Seat 3 is not available."),
+            (JoinTableError::InvalidSeat(12), "There is no seat 12 at this table."),
+        ];
+        for (err, expected) in messages {
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! This file is adapted from the actix-web chat websocket example
 
+use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::time::{Duration, Instant};
 
 use actix::prelude::*;
@@ -9,7 +11,7 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use crate::hub;
-use crate::logic::{PlayerAction, PLAYER_TIMEOUT};
+use crate::logic::{AnteMode, AnteOrder, BettingStructure, BlindSchedule, ClockScaling, ExposurePolicy, GameVariant, HandRanking, MinRaiseRule, PlayerAction, ReshufflePolicy, RevealOrder, PLAYER_TIMEOUT};
 use crate::messages;
 
 /// How often heartbeat pings are sent
@@ -21,11 +23,54 @@ const CLIENT_TIMEOUT: Duration = Duration::from_secs(20);
 pub fn get_help_message() -> Vec<String> {
     vec!["/small_blind AMOUNT".to_string(),
 	 "/big_blind AMOUNT".to_string(),
+	 "/ante AMOUNT".to_string(),
+	 "/big_blind_ante AMOUNT".to_string(),
+	 "/ante_order before_blinds|after_blinds".to_string(),
+	 "/set_blind_schedule MINUTES SMALL/BIG/ANTE,SMALL/BIG/ANTE,...".to_string(),
 	 "/starting_stack AMOUNT".to_string(),
 	 "/set_password PASSWORD".to_string(),
 	 "/show_password".to_string(),	 
+	 "/min_raise_rule raise_increment|full_bet".to_string(),
+	 "/betting_structure no_limit|pot_limit|fixed_limit".to_string(),
+	 "/game_variant holdem|omaha|seven_stud|short_deck".to_string(),
+	 "/bad_beat_threshold HAND_RANKING".to_string(),
+	 "/freeze_clocks".to_string(),
+	 "/action_timeout SECONDS".to_string(),
+	 "/clock_scaling off|pot_size|players_in_hand".to_string(),
+	 "/time_bank SECONDS".to_string(),
+	 "/time_bank_replenish SECONDS".to_string(),
+	 "/finish_delay SECONDS".to_string(),
+	 "/max_finish_delay SECONDS".to_string(),
+	 "/bots_only_max_pause MILLISECONDS".to_string(),
+	 "/finish_delay_pot_scaling true|false".to_string(),
+	 "/allow_chop true|false".to_string(),
+	 "/run_it_twice true|false".to_string(),
+	 "/show_runout_equities true|false".to_string(),
+	 "/all_in_reveal_order last_aggressor|position".to_string(),
+	 "/all_in_reveal_delay MILLISECONDS".to_string(),
+	 "/anonymous true|false".to_string(),
+	 "/allow_rebuys true|false".to_string(),
+	 "/max_stack AMOUNT".to_string(),
+	 "/rake_percent PERCENT".to_string(),
+	 "/rake_cap AMOUNT".to_string(),
+	 "/min_players_to_start NUMBER".to_string(),
+	 "/min_stack_to_play AMOUNT".to_string(),
+	 "/rebuys_at_orbit_start true|false".to_string(),
+	 "/remove_busted true|false".to_string(),
+	 "/max_straddles NUMBER".to_string(),
+	 "/rematch_window SECONDS".to_string(),
+	 "/check_it_down true|false".to_string(),
+	 "/expose_bot_decisions true|false".to_string(),
+	 "/reshuffle_policy every_hand|when_needed".to_string(),
+	 "/commit_shuffle true|false".to_string(),
+	 "/exposure_policy continue|void_hand".to_string(),
+	 "/unfreeze_clocks".to_string(),
+	 "/close_in MINUTES".to_string(),
 	 "/add_bot".to_string(),
 	 "/remove_bot".to_string(),
+	 "/kick PLAYER_ID|SEAT".to_string(),
+	 "/mute PLAYER_ID".to_string(),
+	 "/unmute PLAYER_ID".to_string(),
 	 "/restart".to_string()	 
     ]
 }
@@ -51,7 +96,7 @@ pub struct WsPlayerSession {
 impl WsPlayerSession {
     pub fn new(hub_addr: Addr<hub::TableHub>) -> Self {
         let id = Uuid::new_v4();
-	println!("brand new uuid = {id}");
+	log::info!("brand new uuid = {id}");
         Self {
             id,
             client_hb: Instant::now(),
@@ -84,7 +129,7 @@ impl WsPlayerSession {
                 // command heartbeat timed out
 		// Note: we wait a bit longer than the PLAYER_TIMEOUT, so that we might first receive
 		// the message from the hub that we timed out
-                println!("Session PLAYER heartbeat failed, disconnecting!");
+                log::info!("Session PLAYER heartbeat failed, disconnecting!");
 
                 // stop actor
                 ctx.stop();
@@ -96,7 +141,7 @@ impl WsPlayerSession {
 	    let client_gap = Instant::now().duration_since(act.client_hb);	    
             if client_gap > CLIENT_TIMEOUT {
                 // client heartbeat timed out
-                println!("Websocket Client heartbeat failed, disconnecting!");
+                log::info!("Websocket Client heartbeat failed, disconnecting!");
 		// Note: here we do NOT tell the hub that we want to leave the table.
 		// This allows for the client to rejoin with the same UUID and a new session
 		// (Up to the PLAYER_TIMEOUT)
@@ -136,9 +181,19 @@ impl Actor for WsPlayerSession {
             .into_actor(self)
             .then(|res, act, ctx| {
                 match res {
-                    Ok(res) => {
+                    Ok(Ok(res)) => {
 			act.id = res;
 		    },
+                    Ok(Err(e)) => {
+			// the hub turned us away, so let the client know why before closing
+			let message = json::object! {
+			    msg_type: "error".to_owned(),
+			    error: "server_full".to_owned(),
+			    reason: e.to_string(),
+			};
+			ctx.text(message.dump());
+			ctx.stop();
+		    },
                     // something is wrong with the hub
                     _ => ctx.stop(),
                 }
@@ -186,13 +241,13 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsPlayerSession {
 
                 if let Ok(object) = serde_json::from_str(m) {
                     self.command_hb = Instant::now(); // we got a command, so set the heartbeat
-                    println!("parsed: {}", object);
+                    log::debug!("parsed: {}", object);
                     self.handle_client_command(object, m, ctx);
                 } else {
-                    println!("message unable to parse as json: {}", m);
+                    log::warn!("message unable to parse as json: {}", m);
                 };
             }
-            ws::Message::Binary(_) => println!("Unexpected binary"),
+            ws::Message::Binary(_) => log::warn!("Unexpected binary"),
             ws::Message::Close(reason) => {
                 ctx.close(reason);
                 ctx.stop();
@@ -205,6 +260,80 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsPlayerSession {
     }
 }
 
+/// A websocket session for external clients (e.g. analytics) that only listen to the
+/// GameEvents published by the tables. It has no player config and ignores any commands.
+#[derive(Debug)]
+pub struct WsEventSession {
+    /// Client must send ping at least once per 10 seconds (CLIENT_TIMEOUT),
+    /// otherwise we drop connection.
+    pub client_hb: Instant,
+
+    /// only listen to these tables (or every table if None)
+    pub tables: Option<HashSet<String>>,
+
+    /// Table hub address
+    pub hub_addr: Addr<hub::TableHub>,
+}
+
+impl WsEventSession {
+    pub fn new(tables: Option<HashSet<String>>, hub_addr: Addr<hub::TableHub>) -> Self {
+        Self {
+            client_hb: Instant::now(),
+            tables,
+            hub_addr,
+        }
+    }
+}
+
+impl Actor for WsEventSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    /// subscribe to the hub's game events, and start pinging the client
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.client_hb) > CLIENT_TIMEOUT {
+                log::info!("Websocket event client heartbeat failed, disconnecting!");
+                ctx.stop();
+                return;
+            }
+            ctx.ping(b"");
+        });
+        self.hub_addr.do_send(messages::SubscribeEvents {
+            addr: ctx.address().recipient(),
+            tables: self.tables.clone(),
+        });
+    }
+}
+
+/// Handle game events relayed from the hub, we simply send it to peer websocket
+impl Handler<messages::WsMessage> for WsEventSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: messages::WsMessage, ctx: &mut Self::Context) {
+        ctx.text(msg.0);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsEventSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => {
+                self.client_hb = Instant::now();
+                ctx.pong(&msg);
+            }
+            Ok(ws::Message::Pong(_)) => {
+                self.client_hb = Instant::now();
+            }
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(_) => (), // the event stream is one way
+            Err(_) => ctx.stop(),
+        }
+    }
+}
+
 impl WsPlayerSession {
     fn handle_client_command(
         &mut self,
@@ -212,10 +341,10 @@ impl WsPlayerSession {
 	m: &str, // the original string in case we want to use it to parse
         ctx: &mut <WsPlayerSession as Actor>::Context,
     ) {
-        println!("Entered handle_client_command {:?}", object);
+        log::debug!("Entered handle_client_command {:?}", object);
         let msg_type_opt = object.get("msg_type");
         if msg_type_opt.is_none() {
-            println!("missing message type!");
+            log::warn!("missing message type!");
             return;
         }
         let msg_type = msg_type_opt.unwrap();
@@ -228,7 +357,10 @@ impl WsPlayerSession {
                     self.handle_list_tables(ctx);
                 }
                 "join" => {
-                    self.handle_join_table(object, ctx);
+                    self.handle_join_table(object, false, ctx);
+                }
+                "spectate" => {
+                    self.handle_join_table(object, true, ctx);
                 }
                 "create" => {
                     self.handle_create_table(m, ctx);
@@ -267,6 +399,57 @@ impl WsPlayerSession {
                 "chat" => {
                     self.handle_chat(object, ctx);
                 }
+                "auto_muck_delay" => {
+                    self.handle_auto_muck_delay(object, ctx);
+                }
+                "auto_muck" => {
+                    self.handle_auto_muck(object, ctx);
+                }
+                "quick_fold" => {
+                    self.handle_quick_fold(object, ctx);
+                }
+                "straddle" => {
+                    self.handle_straddle(object, ctx);
+                }
+                "auto_rebuy" => {
+                    self.handle_auto_rebuy(object, ctx);
+                }
+                "rebuy_target" => {
+                    self.handle_rebuy_target(object, ctx);
+                }
+                "propose_chop" => {
+                    self.hub_addr.do_send(messages::MetaActionMessage {
+                        id: self.id,
+                        meta_action: messages::MetaAction::ProposeChop(self.id),
+                    });
+                }
+                "accept_chop" => {
+                    self.hub_addr.do_send(messages::MetaActionMessage {
+                        id: self.id,
+                        meta_action: messages::MetaAction::AcceptChop(self.id),
+                    });
+                }
+                "rematch" => {
+                    self.hub_addr.do_send(messages::MetaActionMessage {
+                        id: self.id,
+                        meta_action: messages::MetaAction::Rematch(self.id),
+                    });
+                }
+                "last_hand" => {
+                    self.hub_addr.do_send(messages::MetaActionMessage {
+                        id: self.id,
+                        meta_action: messages::MetaAction::LastHand(self.id),
+                    });
+                }
+                "show_to_player" => {
+                    self.handle_show_to_player(object, ctx);
+                }
+                "roster" => {
+                    self.hub_addr.do_send(messages::MetaActionMessage {
+                        id: self.id,
+                        meta_action: messages::MetaAction::Roster(self.id),
+                    });
+                }
 		"help" => {
                     let message = json::object! {
 			msg_type: "help_message".to_owned(),
@@ -291,7 +474,7 @@ impl WsPlayerSession {
                 match res {
                     Ok(create_table_result) => match create_table_result {
                         Ok(table_name) => {
-                            println!("created table = {}", table_name);
+                            log::info!("created table = {}", table_name);
                             let message = json::object! {
                                 msg_type: "created_table".to_owned(),
                                 table_name: table_name,
@@ -299,7 +482,7 @@ impl WsPlayerSession {
                             ctx.text(message.dump());
                         }
                         Err(e) => {
-                            println!("{}", e);
+                            log::warn!("{}", e);
                             let message = json::object! {
                                             msg_type: "error".to_owned(),
                             error: "unable_to_create".to_owned(),
@@ -308,7 +491,7 @@ impl WsPlayerSession {
                             ctx.text(message.dump());
                         }
                     },
-                    _ => println!("MailBox error"),
+                    _ => log::warn!("MailBox error"),
                 }
                 fut::ready(())
             })
@@ -320,7 +503,7 @@ impl WsPlayerSession {
     
     fn handle_list_tables(&self, ctx: &mut <WsPlayerSession as Actor>::Context) {
         // Send ListTables message to the hub and wait for response
-        println!("List tables");
+        log::debug!("List tables");
         let addr = ctx.address();	
         self.hub_addr
             .send(messages::ListTables(addr.recipient()))
@@ -334,7 +517,7 @@ impl WsPlayerSession {
                         };
                         ctx.text(message.dump());
                     }
-                    _ => println!("Something is wrong"),
+                    _ => log::warn!("Something is wrong"),
                 }
                 fut::ready(())
             })
@@ -344,23 +527,28 @@ impl WsPlayerSession {
         // of tables back
     }
 
-    fn handle_join_table(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+    // e.g. {"msg_type": "join", "table_name": "ABCD", "password": null, "seat": 3}
+    // the seat is optional, without it the player gets the first open seat.
+    // spectating takes the same fields (minus the seat), but watches without taking a seat
+    fn handle_join_table(&self, object: Value, spectate: bool, ctx: &mut <WsPlayerSession as Actor>::Context) {
         if let (Some(Value::String(table_name)), Some(password)) =
             (object.get("table_name"), object.get("password"))
         {
             let table_name = table_name.to_string();
-            let password = if let Some(password) = password.as_str()  {
-                Some(password.to_owned())
-            } else {
-                None
-            };
+            let password = password.as_str().map(|password| password.to_owned());
+            let seat = object
+                .get("seat")
+                .and_then(|seat| seat.as_u64())
+                .map(|seat| seat as usize);
             self.hub_addr.do_send(messages::Join {
                 id: self.id,
                 table_name,
                 password,
+                spectate,
+                seat,
             });
         } else {
-            println!("missing table name or password!");
+            log::warn!("missing table name or password!");
             ctx.text("!!! table_name and password (possibly null) are required");
         }
     }
@@ -399,6 +587,39 @@ impl WsPlayerSession {
                         ctx.text("!!!You much specify how much to bet!");
                     }
                 }
+                "raise" => {
+                    let amount = match object.get("amount") {
+                        Some(Value::String(amount)) => amount.parse::<u32>().ok(),
+                        _ => None,
+                    };
+                    if let Some(amount) = amount {
+                        self.hub_addr.do_send(messages::PlayerActionMessage {
+                            id: self.id,
+                            player_action: PlayerAction::Raise(amount),
+                        });
+                    } else {
+                        ctx.text("!!!You much specify how much to raise!");
+                    }
+                }
+                "raise_to" | "raise_by" => {
+                    let amount = match object.get("amount") {
+                        Some(Value::String(amount)) => amount.parse::<u32>().ok(),
+                        _ => None,
+                    };
+                    if let Some(amount) = amount {
+                        let player_action = if player_action == "raise_to" {
+                            PlayerAction::RaiseTo(amount)
+                        } else {
+                            PlayerAction::RaiseBy(amount)
+                        };
+                        self.hub_addr.do_send(messages::PlayerActionMessage {
+                            id: self.id,
+                            player_action,
+                        });
+                    } else {
+                        ctx.text("!!!You much specify how much to raise!");
+                    }
+                }
                 other => {
                     ctx.text(format!(
                         "invalid action set for type:player_action: {:?}",
@@ -413,7 +634,7 @@ impl WsPlayerSession {
 
     fn handle_player_name(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
         if let Some(Value::String(name)) = object.get("player_name") {
-            println!("{}", name);
+            log::debug!("{}", name);
             self.hub_addr.do_send(messages::PlayerName {
                 id: self.id,
                 name: name.to_string(),
@@ -431,11 +652,106 @@ impl WsPlayerSession {
                 meta_action: messages::MetaAction::Chat(self.id, text),
             })
         } else {
-            println!("missing chat_message!");
+            log::warn!("missing chat_message!");
             ctx.text("!!! chat_message is required");
         }
     }
 
+    // e.g. {"msg_type": "auto_muck_delay", "seconds": 3}
+    fn handle_auto_muck_delay(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+        // a number too big for the delay is as invalid as a missing one
+        let seconds = object
+            .get("seconds")
+            .and_then(|seconds| seconds.as_u64())
+            .and_then(|seconds| u32::try_from(seconds).ok());
+        if let Some(seconds) = seconds {
+            self.hub_addr.do_send(messages::MetaActionMessage {
+                id: self.id,
+                meta_action: messages::MetaAction::AutoMuckDelay(self.id, seconds),
+            })
+        } else {
+            ctx.text("!!! seconds is required");
+        }
+    }
+
+    // e.g. {"msg_type": "auto_muck", "auto_muck": true}
+    fn handle_auto_muck(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+        if let Some(auto_muck) = object.get("auto_muck").and_then(|auto_muck| auto_muck.as_bool()) {
+            self.hub_addr.do_send(messages::MetaActionMessage {
+                id: self.id,
+                meta_action: messages::MetaAction::AutoMuck(self.id, auto_muck),
+            })
+        } else {
+            ctx.text("!!! auto_muck is required");
+        }
+    }
+
+    // e.g. {"msg_type": "auto_rebuy", "auto_rebuy": true}
+    fn handle_auto_rebuy(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+        if let Some(auto_rebuy) = object.get("auto_rebuy").and_then(|auto_rebuy| auto_rebuy.as_bool()) {
+            self.hub_addr.do_send(messages::MetaActionMessage {
+                id: self.id,
+                meta_action: messages::MetaAction::AutoRebuy(self.id, auto_rebuy),
+            })
+        } else {
+            ctx.text("!!! auto_rebuy is required");
+        }
+    }
+
+    // e.g. {"msg_type": "rebuy_target", "rebuy_target": 1600}, or null to go back to the buy-in
+    fn handle_rebuy_target(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+        let target = match object.get("rebuy_target") {
+            Some(Value::Null) => None,
+            Some(target) if target.as_u64().is_some_and(|target| u32::try_from(target).is_ok()) => {
+                target.as_u64().and_then(|target| u32::try_from(target).ok())
+            }
+            _ => {
+                ctx.text("!!! rebuy_target is required");
+                return;
+            }
+        };
+        self.hub_addr.do_send(messages::MetaActionMessage {
+            id: self.id,
+            meta_action: messages::MetaAction::RebuyTarget(self.id, target),
+        })
+    }
+
+    // e.g. {"msg_type": "quick_fold", "quick_fold": true}
+    fn handle_quick_fold(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+        if let Some(quick_fold) = object.get("quick_fold").and_then(|quick_fold| quick_fold.as_bool()) {
+            self.hub_addr.do_send(messages::MetaActionMessage {
+                id: self.id,
+                meta_action: messages::MetaAction::QuickFold(self.id, quick_fold),
+            })
+        } else {
+            ctx.text("!!! quick_fold is required");
+        }
+    }
+
+    // e.g. {"msg_type": "show_to_player", "player_id": "67e55044-10b1-426f-9247-bb680e5fe0c8"}
+    fn handle_show_to_player(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+        if let Some(Ok(to_id)) = object.get("player_id").and_then(|id| id.as_str()).map(Uuid::parse_str) {
+            self.hub_addr.do_send(messages::MetaActionMessage {
+                id: self.id,
+                meta_action: messages::MetaAction::ShowToPlayer(self.id, to_id),
+            })
+        } else {
+            ctx.text("!!! player_id is required");
+        }
+    }
+
+    // e.g. {"msg_type": "straddle", "straddle": true}
+    fn handle_straddle(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
+        if let Some(straddle) = object.get("straddle").and_then(|straddle| straddle.as_bool()) {
+            self.hub_addr.do_send(messages::MetaActionMessage {
+                id: self.id,
+                meta_action: messages::MetaAction::Straddle(self.id, straddle),
+            })
+        } else {
+            ctx.text("!!! straddle is required");
+        }
+    }
+
     // e.g. {"msg_type": "admin_command", "admin_command": "big_blind", "big_blind": 24}
     fn handle_admin_command(&self, object: Value, ctx: &mut <WsPlayerSession as Actor>::Context) {
         if let Some(Value::String(admin_command)) = object.get("admin_command") {
@@ -459,6 +775,32 @@ impl WsPlayerSession {
 			true
 		    }
                 }
+                "ante" | "big_blind_ante" => {
+		    if let Some(Value::String(amount)) = object.get(admin_command.as_str()) {
+			if let Ok(amount) = amount.to_string().parse::<u32>() {
+			    let ante_mode = if amount == 0 {
+				AnteMode::None
+			    } else if admin_command == "ante" {
+				AnteMode::PerPlayer(amount)
+			    } else {
+				AnteMode::BigBlind(amount)
+			    };
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::AnteMode(ante_mode),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid_json
+			true
+		    }
+                }
                 "big_blind" => {
 		    if let Some(Value::String(amount)) = object.get("big_blind") {
 			if let Ok(amount) = amount.to_string().parse::<u32>() {			
@@ -497,6 +839,25 @@ impl WsPlayerSession {
 			true
 		    }		    
                 }		
+                "set_blind_schedule" => {
+		    let schedule = match object.get("set_blind_schedule") {
+			Some(Value::String(schedule)) => BlindSchedule::parse(schedule),
+			_ => None,
+		    };
+		    if let Some(schedule) = schedule {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::SetBlindSchedule(schedule),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
                 "set_password" => {
 		    if let Some(Value::String(amount)) = object.get("set_password") {
 			let amount = amount.to_string();
@@ -513,6 +874,98 @@ impl WsPlayerSession {
 			true
 		    }
                 }
+                "min_raise_rule" => {
+		    let rule = match object.get("min_raise_rule") {
+			Some(Value::String(rule)) if rule == "raise_increment" => {
+			    Some(MinRaiseRule::LastRaiseIncrement)
+			}
+			Some(Value::String(rule)) if rule == "full_bet" => {
+			    Some(MinRaiseRule::LastFullBet)
+			}
+			_ => None,
+		    };
+		    if let Some(rule) = rule {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::MinRaiseRule(rule),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "betting_structure" => {
+		    let structure = match object.get("betting_structure") {
+			Some(Value::String(structure)) if structure == "no_limit" => {
+			    Some(BettingStructure::NoLimit)
+			}
+			Some(Value::String(structure)) if structure == "pot_limit" => {
+			    Some(BettingStructure::PotLimit)
+			}
+			Some(Value::String(structure)) if structure == "fixed_limit" => {
+			    Some(BettingStructure::FixedLimit)
+			}
+			_ => None,
+		    };
+		    if let Some(structure) = structure {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::BettingStructure(structure),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "game_variant" => {
+		    let variant = match object.get("game_variant") {
+			Some(Value::String(variant)) if variant == "holdem" => Some(GameVariant::Holdem),
+			Some(Value::String(variant)) if variant == "omaha" => Some(GameVariant::Omaha),
+			Some(Value::String(variant)) if variant == "seven_stud" => Some(GameVariant::SevenStud),
+			Some(Value::String(variant)) if variant == "short_deck" => Some(GameVariant::ShortDeck),
+			_ => None,
+		    };
+		    if let Some(variant) = variant {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::GameVariant(variant),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "bad_beat_threshold" => {
+		    if let Some(Value::String(ranking)) = object.get("bad_beat_threshold") {
+			if let Some(ranking) = HandRanking::from_name(ranking) {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::BadBeatThreshold(ranking),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
                 "show_password" => {
 		    self.hub_addr.do_send(messages::MetaActionMessage {
 			id: self.id,
@@ -522,23 +975,655 @@ impl WsPlayerSession {
                     });
 		    false
 		}		
-                "add_bot" => {
-		    self.hub_addr.do_send(messages::MetaActionMessage {
-			id: self.id,
-			meta_action: messages::MetaAction::Admin(
-			    self.id,				
-			    messages::AdminCommand::AddBot),
-                    });
-		    false
-		}
-                "remove_bot" => {
-		    self.hub_addr.do_send(messages::MetaActionMessage {
-			id: self.id,
-			meta_action: messages::MetaAction::Admin(
-			    self.id,				
-			    messages::AdminCommand::RemoveBot),
-                    });
-		    false
+                "ante_order" => {
+		    let order = match object.get("ante_order") {
+			Some(Value::String(order)) if order == "before_blinds" => Some(AnteOrder::BeforeBlinds),
+			Some(Value::String(order)) if order == "after_blinds" => Some(AnteOrder::AfterBlinds),
+			_ => None,
+		    };
+		    if let Some(order) = order {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::AnteOrder(order),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "clock_scaling" => {
+		    let scaling = match object.get("clock_scaling") {
+			Some(Value::String(scaling)) if scaling == "off" => Some(ClockScaling::Off),
+			Some(Value::String(scaling)) if scaling == "pot_size" => Some(ClockScaling::PotSize),
+			Some(Value::String(scaling)) if scaling == "players_in_hand" => {
+			    Some(ClockScaling::PlayersInHand)
+			}
+			_ => None,
+		    };
+		    if let Some(scaling) = scaling {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::ClockScaling(scaling),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "action_timeout" => {
+		    if let Some(Value::String(seconds)) = object.get("action_timeout") {
+			if let Ok(seconds) = seconds.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::ActionTimeout(seconds),
+				),
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "time_bank" | "time_bank_replenish" => {
+		    if let Some(Value::String(seconds)) = object.get(admin_command.as_str()) {
+			if let Ok(seconds) = seconds.parse::<u32>() {
+			    let command = if admin_command == "time_bank" {
+				messages::AdminCommand::TimeBank(seconds)
+			    } else {
+				messages::AdminCommand::TimeBankReplenish(seconds)
+			    };
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(self.id, command),
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "all_in_reveal_order" => {
+		    let order = match object.get("all_in_reveal_order") {
+			Some(Value::String(order)) if order == "last_aggressor" => Some(RevealOrder::LastAggressor),
+			Some(Value::String(order)) if order == "position" => Some(RevealOrder::Position),
+			_ => None,
+		    };
+		    if let Some(order) = order {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::AllInRevealOrder(order),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "all_in_reveal_delay" => {
+		    if let Some(Value::String(millis)) = object.get("all_in_reveal_delay") {
+			if let Ok(millis) = millis.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::AllInRevealDelay(millis),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "anonymous" => {
+		    if let Some(Value::String(anonymous)) = object.get("anonymous") {
+			if let Ok(anonymous) = anonymous.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::Anonymous(anonymous),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "bots_only_max_pause" => {
+		    if let Some(Value::String(millis)) = object.get("bots_only_max_pause") {
+			if let Ok(millis) = millis.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::BotsOnlyMaxPause(millis),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "max_straddles" => {
+		    if let Some(Value::String(max)) = object.get("max_straddles") {
+			if let Ok(max) = max.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::MaxStraddles(max),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "rematch_window" => {
+		    if let Some(Value::String(seconds)) = object.get("rematch_window") {
+			if let Ok(seconds) = seconds.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::RematchWindow(seconds),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "finish_delay" | "max_finish_delay" => {
+		    if let Some(Value::String(seconds)) = object.get(admin_command.as_str()) {
+			if let Ok(seconds) = seconds.parse::<u32>() {
+			    let command = if admin_command == "finish_delay" {
+				messages::AdminCommand::FinishDelay(seconds)
+			    } else {
+				messages::AdminCommand::MaxFinishDelay(seconds)
+			    };
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(self.id, command),
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "finish_delay_pot_scaling" => {
+		    if let Some(Value::String(scaling)) = object.get("finish_delay_pot_scaling") {
+			if let Ok(scaling) = scaling.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::FinishDelayPotScaling(scaling),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "commit_shuffle" => {
+		    if let Some(Value::String(commit)) = object.get("commit_shuffle") {
+			if let Ok(commit) = commit.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::CommitShuffle(commit),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "allow_chop" => {
+		    if let Some(Value::String(allow)) = object.get("allow_chop") {
+			if let Ok(allow) = allow.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::AllowChop(allow),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "run_it_twice" => {
+		    if let Some(Value::String(run_twice)) = object.get("run_it_twice") {
+			if let Ok(run_twice) = run_twice.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::RunItTwice(run_twice),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "show_runout_equities" => {
+		    if let Some(Value::String(show)) = object.get("show_runout_equities") {
+			if let Ok(show) = show.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::ShowRunoutEquities(show),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "max_stack" => {
+		    if let Some(Value::String(amount)) = object.get("max_stack") {
+			if let Ok(amount) = amount.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::MaxStack(amount),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "rake_percent" => {
+		    if let Some(Value::String(percent)) = object.get("rake_percent") {
+			if let Ok(percent) = percent.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::RakePercent(percent),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "rake_cap" => {
+		    if let Some(Value::String(amount)) = object.get("rake_cap") {
+			if let Ok(amount) = amount.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::RakeCap(amount),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "min_players_to_start" => {
+		    if let Some(Value::String(num)) = object.get("min_players_to_start") {
+			if let Ok(num) = num.parse::<usize>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::MinPlayersToStart(num),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "min_stack_to_play" => {
+		    if let Some(Value::String(amount)) = object.get("min_stack_to_play") {
+			if let Ok(amount) = amount.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::MinStackToPlay(amount),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "allow_rebuys" => {
+		    if let Some(Value::String(allow)) = object.get("allow_rebuys") {
+			if let Ok(allow) = allow.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::AllowRebuys(allow),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "rebuys_at_orbit_start" => {
+		    if let Some(Value::String(at_orbit_start)) = object.get("rebuys_at_orbit_start") {
+			if let Ok(at_orbit_start) = at_orbit_start.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::RebuysAtOrbitStart(at_orbit_start),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "remove_busted" => {
+		    if let Some(Value::String(remove)) = object.get("remove_busted") {
+			if let Ok(remove) = remove.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::RemoveBusted(remove),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "check_it_down" => {
+		    if let Some(Value::String(check_it_down)) = object.get("check_it_down") {
+			if let Ok(check_it_down) = check_it_down.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::CheckItDown(check_it_down),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "expose_bot_decisions" => {
+		    if let Some(Value::String(expose)) = object.get("expose_bot_decisions") {
+			if let Ok(expose) = expose.parse::<bool>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::ExposeBotDecisions(expose),
+				)
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "reshuffle_policy" => {
+		    let policy = match object.get("reshuffle_policy") {
+			Some(Value::String(policy)) if policy == "every_hand" => {
+			    Some(ReshufflePolicy::EveryHand)
+			}
+			Some(Value::String(policy)) if policy == "when_needed" => {
+			    Some(ReshufflePolicy::WhenNeeded)
+			}
+			_ => None,
+		    };
+		    if let Some(policy) = policy {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::ReshufflePolicy(policy),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "exposure_policy" => {
+		    let policy = match object.get("exposure_policy") {
+			Some(Value::String(policy)) if policy == "continue" => {
+			    Some(ExposurePolicy::Continue)
+			}
+			Some(Value::String(policy)) if policy == "void_hand" => {
+			    Some(ExposurePolicy::VoidHand)
+			}
+			_ => None,
+		    };
+		    if let Some(policy) = policy {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(
+				self.id,
+				messages::AdminCommand::ExposurePolicy(policy),
+			    )
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "freeze_clocks" => {
+		    self.hub_addr.do_send(messages::MetaActionMessage {
+			id: self.id,
+			meta_action: messages::MetaAction::Admin(
+			    self.id,
+			    messages::AdminCommand::FreezeClocks),
+                    });
+		    false
+		}
+                "unfreeze_clocks" => {
+		    self.hub_addr.do_send(messages::MetaActionMessage {
+			id: self.id,
+			meta_action: messages::MetaAction::Admin(
+			    self.id,
+			    messages::AdminCommand::UnfreezeClocks),
+                    });
+		    false
+		}
+                "close_in" => {
+		    if let Some(Value::String(minutes)) = object.get("close_in") {
+			if let Ok(minutes) = minutes.parse::<u32>() {
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(
+				    self.id,
+				    messages::AdminCommand::CloseIn(minutes),
+				),
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "add_bot" => {
+		    self.hub_addr.do_send(messages::MetaActionMessage {
+			id: self.id,
+			meta_action: messages::MetaAction::Admin(
+			    self.id,				
+			    messages::AdminCommand::AddBot),
+                    });
+		    false
+		}
+                "remove_bot" => {
+		    self.hub_addr.do_send(messages::MetaActionMessage {
+			id: self.id,
+			meta_action: messages::MetaAction::Admin(
+			    self.id,				
+			    messages::AdminCommand::RemoveBot),
+                    });
+		    false
+                }
+                "kick" => {
+		    let command = match object.get("kick") {
+			Some(Value::String(target)) => {
+			    if let Ok(id) = Uuid::parse_str(target) {
+				Some(messages::AdminCommand::Kick(id))
+			    } else {
+				target.parse::<usize>().ok().map(messages::AdminCommand::KickSeat)
+			    }
+			}
+			_ => None,
+		    };
+		    if let Some(command) = command {
+			self.hub_addr.do_send(messages::MetaActionMessage {
+			    id: self.id,
+			    meta_action: messages::MetaAction::Admin(self.id, command),
+			});
+			false
+		    } else {
+			// invalid json
+			true
+		    }
+                }
+                "mute" | "unmute" => {
+		    if let Some(Value::String(target)) = object.get(admin_command.as_str()) {
+			if let Ok(target) = Uuid::parse_str(target) {
+			    let command = if admin_command == "mute" {
+				messages::AdminCommand::Mute(target)
+			    } else {
+				messages::AdminCommand::Unmute(target)
+			    };
+			    self.hub_addr.do_send(messages::MetaActionMessage {
+				id: self.id,
+				meta_action: messages::MetaAction::Admin(self.id, command),
+			    });
+			    false
+			} else {
+			    true
+			}
+		    } else {
+			// invalid json
+			true
+		    }
                 }
                 "restart" => {
 		    self.hub_addr.do_send(messages::MetaActionMessage {